@@ -48,6 +48,7 @@ pub struct SettingsApp {
     nav_model: nav_bar::Model,
     page_sender: Option<tokio::sync::mpsc::Sender<crate::pages::Message>>,
     pages: page::Binder<crate::pages::Message>,
+    revert: crate::revert::Pending,
     search_active: bool,
     search_id: cosmic::widget::Id,
     search_input: String,
@@ -94,6 +95,7 @@ impl SettingsApp {
 #[derive(Clone, Debug)]
 pub enum Message {
     CloseContextDrawer,
+    Daytime(bool),
     DelayedInit(page::Entity),
     DesktopInfo,
     Error(String),
@@ -104,6 +106,7 @@ pub enum Message {
     PageMessage(crate::pages::Message),
     PanelConfig(CosmicPanelConfig),
     RegisterSubscriptionSender(tokio::sync::mpsc::Sender<pages::Message>),
+    Revert(crate::revert::Message),
     SearchActivate,
     SearchChanged(String),
     SearchClear,
@@ -135,6 +138,7 @@ impl cosmic::Application for SettingsApp {
             nav_model: nav_bar::Model::default(),
             page_sender: None,
             pages: page::Binder::default(),
+            revert: crate::revert::Pending::default(),
             search_active: false,
             search_id: cosmic::widget::Id::unique(),
             search_input: String::new(),
@@ -242,12 +246,38 @@ impl cosmic::Application for SettingsApp {
                     futures::future::pending().await
                 },
             ),
-            crate::subscription::daytime().map(|daytime| {
-                Message::PageMessage(pages::Message::Appearance(appearance::Message::Daytime(
-                    daytime,
+            crate::subscription::daytime().map(Message::Daytime),
+            wayland_events,
+            // Apply the automatic power profile rules while settings is running.
+            power::rules::subscription(
+                self.pages
+                    .page::<power::Page>()
+                    .map(|page| page.rules.clone())
+                    .unwrap_or_default(),
+            )
+            .map(|battery| {
+                Message::PageMessage(pages::Message::Power(power::Message::UpdateBattery(
+                    battery,
+                )))
+            }),
+            // Keep the brightness of the built-in panel in sync with brightness keys.
+            display::brightness::subscription().map(|backlight| {
+                Message::PageMessage(pages::Message::Displays(display::Message::Backlight(
+                    backlight,
+                )))
+            }),
+            // Start and end night light on its schedule while settings is running.
+            display::night_light::subscription().map(|()| {
+                Message::PageMessage(pages::Message::Displays(display::Message::NightLight(
+                    display::NightLight::Tick,
+                )))
+            }),
+            // Restore saved display profiles when monitors are connected or disconnected.
+            display::profiles::subscription().map(|result| {
+                Message::PageMessage(pages::Message::Displays(display::Message::RandrResult(
+                    std::sync::Arc::new(result),
                 )))
             }),
-            wayland_events,
             // Watch for changes to installed desktop entries
             desktop_files(0).map(|_| Message::DesktopInfo),
             // Watch for configuration changes to the panel.
@@ -305,6 +335,20 @@ impl cosmic::Application for SettingsApp {
                 self.search_active = true;
             }
 
+            Message::Daytime(daytime) => {
+                let mut commands = Vec::with_capacity(2);
+
+                if let Some(page) = self.pages.page_mut::<appearance::Page>() {
+                    commands.push(page.update(appearance::Message::Daytime(daytime)));
+                }
+
+                if let Some(page) = self.pages.page_mut::<display::Page>() {
+                    commands.push(page.update(display::Message::Daytime(daytime)));
+                }
+
+                return Command::batch(commands);
+            }
+
             Message::PageMessage(message) => match message {
                 crate::pages::Message::About(message) => {
                     page::update!(self.pages, message, system::about::Page);
@@ -343,7 +387,9 @@ impl cosmic::Application for SettingsApp {
                 }
 
                 crate::pages::Message::Dock(message) => {
-                    page::update!(self.pages, message, dock::Page);
+                    if let Some(page) = self.pages.page_mut::<dock::Page>() {
+                        return page.update(message);
+                    }
                 }
 
                 crate::pages::Message::DockApplet(message) => {
@@ -449,7 +495,9 @@ impl cosmic::Application for SettingsApp {
                 }
 
                 crate::pages::Message::Power(message) => {
-                    page::update!(self.pages, message, power::Page);
+                    if let Some(page) = self.pages.page_mut::<power::Page>() {
+                        return page.update(message);
+                    }
                 }
 
                 crate::pages::Message::WindowManagement(message) => {
@@ -467,9 +515,16 @@ impl cosmic::Application for SettingsApp {
                 // dock
                 if let Some(page) = self.pages.page_mut::<dock::Page>() {
                     page.update(dock::Message::Inner(_panel::Message::OutputAdded(
+                        info.name.clone().unwrap_or_default(),
+                        output.clone(),
+                    )));
+                }
+                // displays
+                if let Some(page) = self.pages.page_mut::<display::Page>() {
+                    return page.update(display::Message::OutputAdded(
                         info.name.unwrap_or_default(),
                         output,
-                    )));
+                    ));
                 }
             }
 
@@ -481,7 +536,13 @@ impl cosmic::Application for SettingsApp {
                 }
                 // dock
                 if let Some(page) = self.pages.page_mut::<dock::Page>() {
-                    page.update(dock::Message::Inner(_panel::Message::OutputRemoved(output)));
+                    page.update(dock::Message::Inner(_panel::Message::OutputRemoved(
+                        output.clone(),
+                    )));
+                }
+                // displays
+                if let Some(page) = self.pages.page_mut::<display::Page>() {
+                    return page.update(display::Message::OutputRemoved(output));
                 }
             }
 
@@ -552,6 +613,10 @@ impl cosmic::Application for SettingsApp {
                 self.page_sender = Some(sender);
             }
 
+            Message::Revert(message) => {
+                return self.revert.update(message).map(Into::into);
+            }
+
             // It is necessary to delay init to allow time for the page sender to be initialized
             Message::DelayedInit(active_id) => {
                 if self.page_sender.is_none() {
@@ -609,6 +674,14 @@ impl cosmic::Application for SettingsApp {
             }
         }
 
+        if let Some(view) = self
+            .pages
+            .page::<display::Page>()
+            .and_then(|page| page.identify_view(id))
+        {
+            return view;
+        }
+
         panic!("unknown window ID: {id:?}");
     }
 
@@ -623,6 +696,10 @@ impl cosmic::Application for SettingsApp {
     }
 
     fn dialog(&self) -> Option<Element<Self::Message>> {
+        if let Some(dialog) = self.revert.dialog() {
+            return Some(dialog);
+        }
+
         self.pages
             .dialog(self.active_page)
             .map(|e| e.map(Message::PageMessage))
@@ -3,7 +3,7 @@
 
 use cosmic::cosmic_config::{self, ConfigGet, ConfigSet};
 
-const NAME: &str = "com.system76.CosmicSettings";
+pub const NAME: &str = "com.system76.CosmicSettings";
 
 const ACTIVE_PAGE: &str = "active-page";
 
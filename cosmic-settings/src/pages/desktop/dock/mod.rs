@@ -1,10 +1,9 @@
 use std::collections::HashMap;
 
-use cosmic::Apply;
 use cosmic::{
     cosmic_config::{ConfigSet, CosmicConfigEntry},
     widget::{settings, text, toggler},
-    Element,
+    Apply, Command, Element,
 };
 use cosmic_panel_config::{CosmicPanelConfig, CosmicPanelContainerConfig};
 use cosmic_settings_page::{self as page, section, Section};
@@ -31,25 +30,47 @@ pub enum Message {
 }
 
 impl Page {
-    pub fn update(&mut self, message: Message) {
+    pub fn update(&mut self, message: Message) -> Command<crate::app::Message> {
         match message {
             Message::EnableDock(enabled) => {
                 let Some(container_config) = self.inner.container_config.as_mut() else {
-                    return;
+                    return Command::none();
                 };
                 let Some(panel_config) = self.inner.panel_config.as_ref() else {
-                    return;
+                    return Command::none();
                 };
                 let Ok(helper) = CosmicPanelContainerConfig::cosmic_config() else {
-                    return;
+                    return Command::none();
                 };
 
+                let mut revert = None;
+
                 if enabled {
-                    container_config.config_list.push(panel_config.clone());
+                    if !container_config
+                        .config_list
+                        .iter()
+                        .any(|c| c.name.as_str() == "Dock")
+                    {
+                        container_config.config_list.push(panel_config.clone());
+                    }
                 } else {
                     container_config
                         .config_list
                         .retain(|c| c.name.as_str() != "Dock");
+
+                    // Disabling the only panel leaves nothing to launch applications from,
+                    // so it is reverted unless kept.
+                    if container_config.config_list.is_empty() {
+                        revert = Some(
+                            crate::revert::Revert::new(fl!("dialog", "title-settings")).command(
+                                || {
+                                    cosmic::command::message(crate::app::Message::PageMessage(
+                                        crate::pages::Message::Dock(Message::EnableDock(true)),
+                                    ))
+                                },
+                            ),
+                        );
+                    }
                 }
 
                 let entry_names = container_config
@@ -61,11 +82,17 @@ impl Page {
                 if let Err(err) = helper.set("entries", entry_names) {
                     error!("{:?}", err);
                 }
+
+                if let Some(revert) = revert {
+                    return revert.confirm();
+                }
             }
             Message::Inner(inner) => {
                 self.inner.update(inner);
             }
         };
+
+        Command::none()
     }
 }
 
@@ -0,0 +1,29 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Adaptive sync (variable refresh rate) modes of a display.
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum AdaptiveSync {
+    Disabled,
+    /// Only while a fullscreen application is shown.
+    #[default]
+    Automatic,
+    Always,
+}
+
+impl AdaptiveSync {
+    pub const ALL: [Self; 3] = [Self::Disabled, Self::Automatic, Self::Always];
+
+    pub fn title(self) -> String {
+        match self {
+            Self::Disabled => fl!("adaptive-sync", "disabled"),
+            Self::Automatic => fl!("adaptive-sync", "automatic"),
+            Self::Always => fl!("adaptive-sync", "always"),
+        }
+    }
+
+    pub fn position(self) -> usize {
+        Self::ALL.iter().position(|&mode| mode == self).unwrap_or(0)
+    }
+}
@@ -1,8 +1,10 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: MPL-2.0
 
+use cosmic::iced_core::keyboard::{self, key::Named, Key};
 use cosmic::iced_core::renderer::Quad;
-use cosmic::iced_core::widget::{tree, Tree};
+use cosmic::iced_core::widget::operation::Focusable;
+use cosmic::iced_core::widget::{tree, Operation, OperationOutputWrapper, Tree};
 use cosmic::iced_core::{
     self as core, Border, Clipboard, Element, Layout, Length, Rectangle, Renderer as IcedRenderer,
     Shell, Size, Widget,
@@ -14,8 +16,20 @@ use cosmic::Renderer;
 use cosmic_randr_shell::{self as randr, OutputKey};
 use randr::Transform;
 
+mod geometry;
+#[cfg(test)]
+mod tests;
+
+pub use geometry::{Guide, Invalid, Rect};
+
 const UNIT_PIXELS: f32 = 12.0;
 
+/// Logical pixels that a display is moved by with the arrow keys.
+const NUDGE_STEP: i32 = 10;
+
+/// Logical pixels that a display is moved by with the arrow keys while shift is held.
+const NUDGE_STEP_LARGE: i32 = 100;
+
 pub type OnPlacementFunc<Message> = Box<dyn Fn(OutputKey, i32, i32) -> Message>;
 pub type OnSelectFunc<Message> = Box<dyn Fn(segmented_button::Entity) -> Message>;
 
@@ -122,9 +136,9 @@ impl<'a, Message: Clone> Widget<Message, cosmic::Theme, Renderer> for Arrangemen
                 (mode.size.1, mode.size.0)
             };
 
-            // Scale dimensions of the display with the output scale.
-            width = (width as f64 / output.scale) as u32;
-            height = (height as f64 / output.scale) as u32;
+            // Scale dimensions of the display with the output scale, rounded like the compositor.
+            width = (width as f64 / output.scale).round() as u32;
+            height = (height as f64 / output.scale).round() as u32;
 
             max_dimensions.0 = max_dimensions.0.max(width);
             max_dimensions.1 = max_dimensions.1.max(height);
@@ -151,6 +165,17 @@ impl<'a, Message: Clone> Widget<Message, cosmic::Theme, Renderer> for Arrangemen
         layout::Node::new(size)
     }
 
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        _layout: Layout<'_>,
+        _renderer: &Renderer,
+        operation: &mut dyn Operation<OperationOutputWrapper<Message>>,
+    ) {
+        let state = tree.state.downcast_mut::<State>();
+        operation.focusable(state, None);
+    }
+
     fn on_event(
         &mut self,
         tree: &mut Tree,
@@ -169,7 +194,7 @@ impl<'a, Message: Clone> Widget<Message, cosmic::Theme, Renderer> for Arrangemen
             | core::Event::Touch(touch::Event::FingerMoved { position, .. }) => {
                 let state = tree.state.downcast_mut::<State>();
 
-                if let Some((output_key, region)) = state.dragging.as_mut() {
+                if let Some((output_key, rect)) = state.dragging.as_mut() {
                     if let Some(ref mut on_pan) = self.on_pan {
                         if bounds.x + viewport.width - 150.0 < position.x {
                             shell.publish(on_pan(Pan::Right));
@@ -179,18 +204,28 @@ impl<'a, Message: Clone> Widget<Message, cosmic::Theme, Renderer> for Arrangemen
                     }
 
                     if let Some(inner_position) = cursor.position() {
-                        update_dragged_region(
-                            self.tab_model,
-                            self.list,
-                            &bounds,
-                            *output_key,
-                            region,
-                            state.max_dimensions,
-                            (
-                                inner_position.x - state.offset.0,
-                                inner_position.y - state.offset.1,
-                            ),
-                        );
+                        let dragged = Rect {
+                            x: ((inner_position.x
+                                - state.offset.0
+                                - state.max_dimensions.0
+                                - bounds.x)
+                                * UNIT_PIXELS) as i32,
+                            y: ((inner_position.y
+                                - state.offset.1
+                                - state.max_dimensions.1
+                                - bounds.y)
+                                * UNIT_PIXELS) as i32,
+                            ..*rect
+                        };
+
+                        let rects = display_rects(self.tab_model, self.list).collect::<Vec<_>>();
+                        let others = other_rects(self.list, &rects, *output_key);
+
+                        // Keep the last valid placement if the display can't be placed here.
+                        if let Some(attached) = geometry::attach(dragged, &others) {
+                            *rect = attached;
+                            state.guides = geometry::guides(&attached, &others);
+                        }
 
                         return event::Status::Captured;
                     }
@@ -208,27 +243,34 @@ impl<'a, Message: Clone> Widget<Message, cosmic::Theme, Renderer> for Arrangemen
                         state.max_dimensions,
                         position,
                     ) {
+                        let Some(rect) = output_rect(self.list, output_key) else {
+                            return event::Status::Ignored;
+                        };
+
                         state.drag_from = position;
                         state.offset = (position.x - output_region.x, position.y - output_region.y);
-                        state.dragging = Some((output_key, output_region));
+                        state.dragging = Some((output_key, rect));
+                        state.focused = true;
+                        state.selected = Some(output_key);
+                        state.nudging = None;
                         return event::Status::Captured;
                     }
+
+                    state.unfocus();
                 }
             }
 
             core::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
             | core::Event::Touch(touch::Event::FingerLifted { .. }) => {
                 let state = tree.state.downcast_mut::<State>();
-                if let Some((output_key, region)) = state.dragging.take() {
+                if let Some((output_key, rect)) = state.dragging.take() {
+                    state.guides.clear();
+
                     if let Some(position) = cursor.position() {
                         if position.distance(state.drag_from) < 4.0 {
                             if let Some(ref on_select) = self.on_select {
-                                for id in self.tab_model.iter() {
-                                    if let Some(&key) = self.tab_model.data::<OutputKey>(id) {
-                                        if key == output_key {
-                                            shell.publish(on_select(id));
-                                        }
-                                    }
+                                if let Some(id) = entity(self.tab_model, output_key) {
+                                    shell.publish(on_select(id));
                                 }
                             }
 
@@ -237,11 +279,120 @@ impl<'a, Message: Clone> Widget<Message, cosmic::Theme, Renderer> for Arrangemen
                     }
 
                     if let Some(ref on_placement) = self.on_placement {
-                        shell.publish(on_placement(
-                            output_key,
-                            ((region.x - state.max_dimensions.0 - bounds.x) * UNIT_PIXELS) as i32,
-                            ((region.y - state.max_dimensions.1 - bounds.y) * UNIT_PIXELS) as i32,
-                        ));
+                        shell.publish(on_placement(output_key, rect.x, rect.y));
+                    }
+
+                    return event::Status::Captured;
+                }
+            }
+
+            core::Event::Keyboard(keyboard::Event::KeyPressed {
+                key: Key::Named(named),
+                modifiers,
+                ..
+            }) => {
+                let state = tree.state.downcast_mut::<State>();
+
+                if !state.focused {
+                    return event::Status::Ignored;
+                }
+
+                let rects = display_rects(self.tab_model, self.list).collect::<Vec<_>>();
+
+                let Some(selected) = state
+                    .selected
+                    .or_else(|| self.tab_model.active_data::<OutputKey>().copied())
+                    .filter(|selected| rects.iter().any(|(key, _)| key == selected))
+                    .or_else(|| rects.first().map(|&(key, _)| key))
+                else {
+                    return event::Status::Ignored;
+                };
+
+                state.selected = Some(selected);
+
+                let step = if modifiers.shift() {
+                    NUDGE_STEP_LARGE
+                } else {
+                    NUDGE_STEP
+                };
+
+                let (dx, dy) = match named {
+                    Named::ArrowLeft => (-step, 0),
+                    Named::ArrowRight => (step, 0),
+                    Named::ArrowUp => (0, -step),
+                    Named::ArrowDown => (0, step),
+
+                    Named::Tab if state.nudging.is_none() => {
+                        let position = rects
+                            .iter()
+                            .position(|&(key, _)| key == selected)
+                            .unwrap_or(0);
+
+                        let next = if modifiers.shift() {
+                            position.checked_sub(1)
+                        } else {
+                            Some(position + 1)
+                        };
+
+                        let Some(&(next, _)) = next.and_then(|next| rects.get(next)) else {
+                            // Past the last display, focus moves on to the next widget.
+                            state.unfocus();
+                            return event::Status::Ignored;
+                        };
+
+                        state.selected = Some(next);
+
+                        if let Some(ref on_select) = self.on_select {
+                            if let Some(id) = entity(self.tab_model, next) {
+                                shell.publish(on_select(id));
+                            }
+                        }
+
+                        return event::Status::Captured;
+                    }
+
+                    Named::Escape if state.nudging.is_some() => {
+                        state.nudging = None;
+                        state.guides.clear();
+                        return event::Status::Captured;
+                    }
+
+                    _ => return event::Status::Ignored,
+                };
+
+                let current = match state.nudging {
+                    Some((key, rect)) if key == selected => Some(rect),
+                    _ => rects
+                        .iter()
+                        .find(|&&(key, _)| key == selected)
+                        .map(|&(_, rect)| rect),
+                };
+
+                if let Some(current) = current {
+                    let others = other_rects(self.list, &rects, selected);
+
+                    // Moves which would overlap or detach the display are refused.
+                    if let Some(moved) = geometry::nudge(current, &others, dx, dy) {
+                        state.guides = geometry::guides(&moved, &others);
+                        state.nudging = Some((selected, moved));
+                    }
+                }
+
+                return event::Status::Captured;
+            }
+
+            core::Event::Keyboard(keyboard::Event::KeyReleased {
+                key:
+                    Key::Named(Named::ArrowLeft | Named::ArrowRight | Named::ArrowUp | Named::ArrowDown),
+                ..
+            }) => {
+                let state = tree.state.downcast_mut::<State>();
+
+                if let Some((output_key, rect)) = state.nudging.take() {
+                    state.guides.clear();
+
+                    if let Some(ref on_placement) = self.on_placement {
+                        shell.publish(on_placement(output_key, rect.x, rect.y));
                     }
 
                     return event::Status::Captured;
@@ -296,16 +447,42 @@ impl<'a, Message: Clone> Widget<Message, cosmic::Theme, Renderer> for Arrangemen
 
         let active_key = self.tab_model.active_data::<OutputKey>();
 
-        for (id, (output_key, mut region)) in
-            display_regions(self.tab_model, self.list, &bounds, state.max_dimensions).enumerate()
-        {
-            // If the output is being dragged, show its dragged position instead.
-            if let Some((dragged_key, dragged_region)) = state.dragging {
-                if dragged_key == output_key {
-                    region = dragged_region;
+        let focused_key = if state.focused {
+            state.selected.as_ref().or(active_key)
+        } else {
+            None
+        };
+
+        for (output_key, mut rect) in display_rects(self.tab_model, self.list) {
+            // If the output is being moved, show its new position instead.
+            for (moved_key, moved_rect) in state.dragging.iter().chain(state.nudging.iter()) {
+                if *moved_key == output_key {
+                    rect = *moved_rect;
                 }
             }
 
+            let region = region(rect, &bounds, state.max_dimensions);
+
+            if Some(&output_key) == focused_key {
+                renderer.fill_quad(
+                    Quad {
+                        bounds: Rectangle {
+                            x: region.x - 4.0,
+                            y: region.y - 4.0,
+                            width: region.width + 8.0,
+                            height: region.height + 8.0,
+                        },
+                        border: Border {
+                            color: cosmic_theme.accent_color().into(),
+                            radius: 8.0.into(),
+                            width: 2.0,
+                        },
+                        shadow: Default::default(),
+                    },
+                    core::Background::Color(core::Color::TRANSPARENT),
+                );
+            }
+
             let (background, border_color) = if Some(&output_key) == active_key {
                 let mut border_color = border_color;
                 border_color.alpha = 0.4;
@@ -350,7 +527,8 @@ impl<'a, Message: Clone> Widget<Message, cosmic::Theme, Renderer> for Arrangemen
             core::text::Renderer::fill_text(
                 renderer,
                 core::Text {
-                    content: itoa::Buffer::new().format(id),
+                    content: itoa::Buffer::new()
+                        .format(display_number(self.tab_model, output_key).unwrap_or_default()),
                     size: core::Pixels(24.0),
                     line_height: core::text::LineHeight::Relative(1.2),
                     font: cosmic::font::FONT_BOLD,
@@ -368,6 +546,49 @@ impl<'a, Message: Clone> Widget<Message, cosmic::Theme, Renderer> for Arrangemen
                 *viewport,
             );
         }
+
+        for guide in &state.guides {
+            let bounds = match *guide {
+                Guide::Vertical(x) => Rectangle {
+                    x: region(
+                        Rect {
+                            x,
+                            ..Rect::default()
+                        },
+                        &bounds,
+                        state.max_dimensions,
+                    )
+                    .x - 0.5,
+                    y: bounds.y,
+                    width: 1.0,
+                    height: bounds.height,
+                },
+
+                Guide::Horizontal(y) => Rectangle {
+                    x: bounds.x,
+                    y: region(
+                        Rect {
+                            y,
+                            ..Rect::default()
+                        },
+                        &bounds,
+                        state.max_dimensions,
+                    )
+                    .y - 0.5,
+                    width: bounds.width,
+                    height: 1.0,
+                },
+            };
+
+            renderer.fill_quad(
+                Quad {
+                    bounds,
+                    border: Border::default(),
+                    shadow: Default::default(),
+                },
+                core::Background::Color(cosmic_theme.accent_color().into()),
+            );
+        }
     }
 }
 
@@ -380,59 +601,127 @@ impl<'a, Message: 'static + Clone> From<Arrangement<'a, Message>> for cosmic::El
 #[derive(Default)]
 struct State {
     drag_from: Point,
-    dragging: Option<(OutputKey, Rectangle)>,
+    dragging: Option<(OutputKey, Rect)>,
     offset: (f32, f32),
     max_dimensions: (f32, f32),
+    /// Whether the arrangement has keyboard focus.
+    focused: bool,
+    /// The display moved by the keyboard, which defaults to the active display.
+    selected: Option<OutputKey>,
+    /// A display being moved with the arrow keys, which is placed when the key is released.
+    nudging: Option<(OutputKey, Rect)>,
+    /// Alignment guides shown while a display is being moved.
+    guides: Vec<Guide>,
 }
 
-/// Iteratively calculate display regions for each display output in the list.
-fn display_regions<'a>(
-    model: &'a SingleSelectModel,
-    list: &'a randr::List,
-    bounds: &'a Rectangle,
-    max_dimensions: (f32, f32),
-) -> impl Iterator<Item = (OutputKey, Rectangle)> + 'a {
+impl Focusable for State {
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn focus(&mut self) {
+        self.focused = true;
+        self.selected = None;
+    }
+
+    fn unfocus(&mut self) {
+        self.focused = false;
+        self.nudging = None;
+        self.guides.clear();
+    }
+}
+
+/// The number which identifies an output, counting from 1 in the order of the display tabs.
+pub fn display_number(model: &SingleSelectModel, key: OutputKey) -> Option<usize> {
     model
         .iter()
-        .filter_map(move |id| model.data::<OutputKey>(id))
-        .filter_map(move |&key| {
-            let Some(output) = list.outputs.get(key) else {
-                return None;
-            };
+        .filter_map(|id| model.data::<OutputKey>(id))
+        .position(|&output| output == key)
+        .map(|position| position + 1)
+}
 
-            if !output.enabled {
-                return None;
-            }
+/// Checks that the enabled displays in the list neither overlap nor are disconnected.
+pub fn validate(list: &randr::List) -> Result<(), Invalid> {
+    let layout = list
+        .outputs
+        .iter()
+        .filter(|(_, output)| output.mirroring.is_none())
+        .filter_map(|(key, _)| output_rect(list, key))
+        .collect::<Vec<_>>();
 
-            let Some(mode_key) = output.current else {
-                return None;
-            };
+    geometry::validate(&layout)
+}
 
-            let Some(mode) = list.modes.get(mode_key) else {
-                return None;
-            };
+/// The logical rectangle of an enabled display.
+fn output_rect(list: &randr::List, key: OutputKey) -> Option<Rect> {
+    let output = list.outputs.get(key)?;
 
-            let (mut width, mut height) = (
-                (mode.size.0 as f32 / output.scale as f32) / UNIT_PIXELS,
-                (mode.size.1 as f32 / output.scale as f32) / UNIT_PIXELS,
-            );
+    if !output.enabled {
+        return None;
+    }
 
-            (width, height) = if output.transform.map_or(true, is_landscape) {
-                (width, height)
-            } else {
-                (height, width)
-            };
+    let mode = list.modes.get(output.current?)?;
+
+    let (width, height) = if output.transform.map_or(true, is_landscape) {
+        (mode.size.0, mode.size.1)
+    } else {
+        (mode.size.1, mode.size.0)
+    };
+
+    Some(Rect {
+        x: output.position.0,
+        y: output.position.1,
+        // Rounded like the compositor does when it computes the logical size of an output.
+        width: (width as f64 / output.scale).round() as i32,
+        height: (height as f64 / output.scale).round() as i32,
+    })
+}
 
-            Some((
-                key,
-                Rectangle {
-                    width,
-                    height,
-                    x: max_dimensions.0 + bounds.x + (output.position.0 as f32) / UNIT_PIXELS,
-                    y: max_dimensions.1 + bounds.y + (output.position.1 as f32) / UNIT_PIXELS,
-                },
-            ))
+/// The logical rectangles of enabled displays, in the order of the display tabs.
+fn display_rects<'a>(
+    model: &'a SingleSelectModel,
+    list: &'a randr::List,
+) -> impl Iterator<Item = (OutputKey, Rect)> + 'a {
+    model
+        .iter()
+        .filter_map(move |id| model.data::<OutputKey>(id))
+        .filter_map(move |&key| Some((key, output_rect(list, key)?)))
+}
+
+/// The displays that a display is placed among. Mirrored displays are left out, since
+/// they share the position of the display they mirror.
+fn other_rects(list: &randr::List, rects: &[(OutputKey, Rect)], output: OutputKey) -> Vec<Rect> {
+    rects
+        .iter()
+        .filter(|&&(key, _)| {
+            key != output
+                && list
+                    .outputs
+                    .get(key)
+                    .map_or(false, |o| o.mirroring.is_none())
         })
+        .map(|&(_, rect)| rect)
+        .collect()
+}
+
+/// Converts the logical rectangle of a display to its region in the widget.
+fn region(rect: Rect, bounds: &Rectangle, max_dimensions: (f32, f32)) -> Rectangle {
+    Rectangle {
+        x: max_dimensions.0 + bounds.x + rect.x as f32 / UNIT_PIXELS,
+        y: max_dimensions.1 + bounds.y + rect.y as f32 / UNIT_PIXELS,
+        width: rect.width as f32 / UNIT_PIXELS,
+        height: rect.height as f32 / UNIT_PIXELS,
+    }
+}
+
+/// Iteratively calculate display regions for each display output in the list.
+fn display_regions<'a>(
+    model: &'a SingleSelectModel,
+    list: &'a randr::List,
+    bounds: &'a Rectangle,
+    max_dimensions: (f32, f32),
+) -> impl Iterator<Item = (OutputKey, Rectangle)> + 'a {
+    display_rects(model, list).map(move |(key, rect)| (key, region(rect, bounds, max_dimensions)))
 }
 
 fn display_region_hovers(
@@ -451,130 +740,11 @@ fn display_region_hovers(
     None
 }
 
-/// Updates a display's region, preventing coordinates from overlapping with existing displays.
-fn update_dragged_region(
-    model: &SingleSelectModel,
-    list: &randr::List,
-    bounds: &Rectangle,
-    output: OutputKey,
-    region: &mut Rectangle,
-    max_dimensions: (f32, f32),
-    (x, y): (f32, f32),
-) {
-    let mut dragged_region = Rectangle { x, y, ..*region };
-
-    let mut nearest = f32::MAX;
-    let mut nearest_region = Rectangle::default();
-    let mut nearest_side = NearestSide::East;
-
-    // Find the nearest adjacent display to the dragged display.
-    for (other_output, other_region) in display_regions(model, list, bounds, max_dimensions) {
-        if other_output == output {
-            continue;
-        }
-
-        let center = dragged_region.center();
-
-        let eastward = distance(east_point(&other_region), center) * 1.25;
-        let westward = distance(west_point(&other_region), center) * 1.25;
-        let northward = distance(north_point(&other_region), center);
-        let southward = distance(south_point(&other_region), center);
-
-        let mut nearer = false;
-
-        if nearest > eastward {
-            (nearest, nearest_side, nearer) = (eastward, NearestSide::East, true);
-        }
-
-        if nearest > westward {
-            (nearest, nearest_side, nearer) = (westward, NearestSide::West, true);
-        }
-
-        if nearest > northward {
-            (nearest, nearest_side, nearer) = (northward, NearestSide::North, true);
-        }
-
-        if nearest > southward {
-            (nearest, nearest_side, nearer) = (southward, NearestSide::South, true);
-        }
-
-        if nearer {
-            nearest_region = other_region;
-        }
-    }
-
-    // Attach dragged display to nearest adjacent display.
-    match nearest_side {
-        NearestSide::East => {
-            dragged_region.x = nearest_region.x - dragged_region.width;
-            dragged_region.y = dragged_region
-                .y
-                .max(nearest_region.y - dragged_region.height + 8.0)
-                .min(nearest_region.y + nearest_region.height - 8.0);
-        }
-
-        NearestSide::North => {
-            dragged_region.y = nearest_region.y - dragged_region.height;
-            dragged_region.x = dragged_region
-                .x
-                .max(nearest_region.x - dragged_region.width + 8.0)
-                .min(nearest_region.x + nearest_region.width - 8.0);
-        }
-
-        NearestSide::West => {
-            dragged_region.x = nearest_region.x + nearest_region.width;
-            dragged_region.y = dragged_region
-                .y
-                .max(nearest_region.y - dragged_region.height + 8.0)
-                .min(nearest_region.y + nearest_region.height - 8.0);
-        }
-
-        NearestSide::South => {
-            dragged_region.y = nearest_region.y + nearest_region.height;
-            dragged_region.x = dragged_region
-                .x
-                .max(nearest_region.x - dragged_region.width + 8.0)
-                .min(nearest_region.x + nearest_region.width - 8.0);
-        }
-    }
-
-    // Snap-align on x-axis when alignment is near.
-    if (dragged_region.x - nearest_region.x).abs() <= 8.0 {
-        dragged_region.x = nearest_region.x;
-    }
-
-    // Snap-align on x-axis when alignment is near bottom edge.
-    if ((dragged_region.x + dragged_region.width) - (nearest_region.x + nearest_region.width)).abs()
-        <= 8.0
-    {
-        dragged_region.x = nearest_region.x + nearest_region.width - dragged_region.width;
-    }
-
-    // Snap-align on y-axis when alignment is near.
-    if (dragged_region.y - nearest_region.y).abs() <= 8.0 {
-        dragged_region.y = nearest_region.y;
-    }
-
-    // Snap-align on y-axis when alignment is near bottom edge.
-    if ((dragged_region.y + dragged_region.height) - (nearest_region.y + nearest_region.height))
-        .abs()
-        <= 8.0
-    {
-        dragged_region.y = nearest_region.y + nearest_region.height - dragged_region.height;
-    }
-
-    // Prevent display from overlapping with other displays.
-    for (other_output, other_region) in display_regions(model, list, bounds, max_dimensions) {
-        if other_output == output {
-            continue;
-        }
-
-        if other_region.intersects(&dragged_region) {
-            return;
-        }
-    }
-
-    *region = dragged_region;
+/// The tab of an output.
+fn entity(model: &SingleSelectModel, output: OutputKey) -> Option<segmented_button::Entity> {
+    model
+        .iter()
+        .find(|&id| model.data::<OutputKey>(id) == Some(&output))
 }
 
 fn is_landscape(transform: Transform) -> bool {
@@ -583,43 +753,3 @@ fn is_landscape(transform: Transform) -> bool {
         Transform::Normal | Transform::Rotate180 | Transform::Flipped | Transform::Flipped180
     )
 }
-
-#[derive(Debug)]
-enum NearestSide {
-    East,
-    North,
-    South,
-    West,
-}
-
-fn distance(a: Point, b: Point) -> f32 {
-    ((b.x - a.x).powf(2.0) + (b.y - a.y).powf(2.0)).sqrt()
-}
-
-fn east_point(r: &Rectangle) -> Point {
-    Point {
-        x: r.x,
-        y: r.center_y(),
-    }
-}
-
-fn north_point(r: &Rectangle) -> Point {
-    Point {
-        x: r.center_x(),
-        y: r.y,
-    }
-}
-
-fn west_point(r: &Rectangle) -> Point {
-    Point {
-        x: r.x + r.width,
-        y: r.center_y(),
-    }
-}
-
-fn south_point(r: &Rectangle) -> Point {
-    Point {
-        x: r.center_x(),
-        y: r.y + r.height,
-    }
-}
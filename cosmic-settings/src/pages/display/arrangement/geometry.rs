@@ -0,0 +1,283 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+//! Display layout geometry in logical pixels, independent of how the arrangement is drawn.
+
+/// Distance within which a display snaps into alignment with the other displays.
+pub const SNAP_DISTANCE: i32 = 96;
+
+/// Minimum length of the edge shared by a dragged display and the display it is attached to.
+pub const MIN_SHARED_EDGE: i32 = 96;
+
+/// Distance by which displays may overlap or be apart while still counting as adjacent, which
+/// absorbs rounding of logical sizes at fractional scales.
+pub const TOLERANCE: i32 = 1;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn right(&self) -> i32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height
+    }
+
+    pub fn center_x(&self) -> i32 {
+        self.x + self.width / 2
+    }
+
+    pub fn center_y(&self) -> i32 {
+        self.y + self.height / 2
+    }
+
+    /// Whether the rectangles share more area than the [`TOLERANCE`] allows.
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.x + TOLERANCE < other.right()
+            && other.x + TOLERANCE < self.right()
+            && self.y + TOLERANCE < other.bottom()
+            && other.y + TOLERANCE < self.bottom()
+    }
+
+    /// Whether the rectangles share part of an edge, within the [`TOLERANCE`]. Touching corners
+    /// are not enough.
+    pub fn touches(&self, other: &Rect) -> bool {
+        let beside = ((self.right() - other.x).abs() <= TOLERANCE
+            || (other.right() - self.x).abs() <= TOLERANCE)
+            && self.y + TOLERANCE < other.bottom()
+            && other.y + TOLERANCE < self.bottom();
+
+        let stacked = ((self.bottom() - other.y).abs() <= TOLERANCE
+            || (other.bottom() - self.y).abs() <= TOLERANCE)
+            && self.x + TOLERANCE < other.right()
+            && other.x + TOLERANCE < self.right();
+
+        beside || stacked
+    }
+}
+
+/// A line along which displays are aligned.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Guide {
+    /// A vertical line at an x coordinate.
+    Vertical(i32),
+    /// A horizontal line at a y coordinate.
+    Horizontal(i32),
+}
+
+/// Why a layout can't be applied.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Invalid {
+    /// Two displays overlap each other.
+    Overlapping,
+    /// A display is not attached to the others.
+    Disconnected,
+}
+
+/// Checks that no displays overlap, and that every display shares an edge with the rest.
+pub fn validate(layout: &[Rect]) -> Result<(), Invalid> {
+    for (id, rect) in layout.iter().enumerate() {
+        if layout[id + 1..].iter().any(|other| rect.overlaps(other)) {
+            return Err(Invalid::Overlapping);
+        }
+    }
+
+    // Every display must be reachable from the first through shared edges.
+    let mut connected = vec![false; layout.len()];
+    let mut pending = Vec::with_capacity(layout.len());
+
+    if !layout.is_empty() {
+        connected[0] = true;
+        pending.push(0);
+    }
+
+    while let Some(id) = pending.pop() {
+        for (other_id, other) in layout.iter().enumerate() {
+            if !connected[other_id] && layout[id].touches(other) {
+                connected[other_id] = true;
+                pending.push(other_id);
+            }
+        }
+    }
+
+    if connected.into_iter().all(|connected| connected) {
+        Ok(())
+    } else {
+        Err(Invalid::Disconnected)
+    }
+}
+
+/// Whether `rect` can be placed among the `others`.
+pub fn is_valid(rect: Rect, others: &[Rect]) -> bool {
+    let mut layout = Vec::with_capacity(others.len() + 1);
+    layout.extend_from_slice(others);
+    layout.push(rect);
+    validate(&layout).is_ok()
+}
+
+/// Moves a display by an offset, if the resulting layout is valid.
+pub fn nudge(rect: Rect, others: &[Rect], dx: i32, dy: i32) -> Option<Rect> {
+    let moved = Rect {
+        x: rect.x + dx,
+        y: rect.y + dy,
+        ..rect
+    };
+
+    is_valid(moved, others).then_some(moved)
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Side {
+    Left,
+    Right,
+    Above,
+    Below,
+}
+
+/// Attaches a dragged display to the nearest side of another display, and snaps it into
+/// alignment with the edges and centers of the other displays.
+///
+/// Returns `None` if the display can't be placed there without making the layout invalid.
+pub fn attach(rect: Rect, others: &[Rect]) -> Option<Rect> {
+    if others.is_empty() {
+        return Some(rect);
+    }
+
+    let center = (
+        rect.x as f32 + rect.width as f32 / 2.0,
+        rect.y as f32 + rect.height as f32 / 2.0,
+    );
+
+    let distance = |x: i32, y: i32| (x as f32 - center.0).hypot(y as f32 - center.1);
+
+    let (side, other) = others
+        .iter()
+        .flat_map(|other| {
+            [
+                // Placing displays side by side is slightly disfavored over stacking them.
+                (
+                    Side::Left,
+                    distance(other.x, other.center_y()) * 1.25,
+                    other,
+                ),
+                (
+                    Side::Right,
+                    distance(other.right(), other.center_y()) * 1.25,
+                    other,
+                ),
+                (Side::Above, distance(other.center_x(), other.y), other),
+                (
+                    Side::Below,
+                    distance(other.center_x(), other.bottom()),
+                    other,
+                ),
+            ]
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(side, _, other)| (side, other))?;
+
+    let mut attached = rect;
+
+    match side {
+        Side::Left | Side::Right => {
+            attached.x = match side {
+                Side::Left => other.x - rect.width,
+                _ => other.right(),
+            };
+
+            attached.y = attached
+                .y
+                .max(other.y - rect.height + MIN_SHARED_EDGE)
+                .min(other.bottom() - MIN_SHARED_EDGE);
+        }
+
+        Side::Above | Side::Below => {
+            attached.y = match side {
+                Side::Above => other.y - rect.height,
+                _ => other.bottom(),
+            };
+
+            attached.x = attached
+                .x
+                .max(other.x - rect.width + MIN_SHARED_EDGE)
+                .min(other.right() - MIN_SHARED_EDGE);
+        }
+    }
+
+    let mut aligned = attached;
+
+    match side {
+        Side::Left | Side::Right => {
+            aligned.y = align(
+                attached.y,
+                attached.height,
+                others.iter().map(|o| (o.y, o.height)),
+            );
+        }
+
+        Side::Above | Side::Below => {
+            aligned.x = align(
+                attached.x,
+                attached.width,
+                others.iter().map(|o| (o.x, o.width)),
+            );
+        }
+    }
+
+    [aligned, attached]
+        .into_iter()
+        .find(|&rect| is_valid(rect, others))
+}
+
+/// Snaps a span to the nearest start, end, or center of another span within `SNAP_DISTANCE`.
+fn align(start: i32, length: i32, others: impl Iterator<Item = (i32, i32)>) -> i32 {
+    others
+        .flat_map(|(other_start, other_length)| {
+            [
+                other_start - start,
+                (other_start + other_length) - (start + length),
+                (other_start + other_length / 2) - (start + length / 2),
+            ]
+        })
+        .filter(|delta| delta.abs() <= SNAP_DISTANCE)
+        .min_by_key(|delta| delta.abs())
+        .map_or(start, |delta| start + delta)
+}
+
+/// Lines along which a display is aligned with the edges or centers of other displays.
+pub fn guides(rect: &Rect, others: &[Rect]) -> Vec<Guide> {
+    let mut guides = Vec::new();
+
+    for other in others {
+        for x in [rect.x, rect.right()] {
+            if x == other.x || x == other.right() {
+                guides.push(Guide::Vertical(x));
+            }
+        }
+
+        if rect.center_x() == other.center_x() {
+            guides.push(Guide::Vertical(rect.center_x()));
+        }
+
+        for y in [rect.y, rect.bottom()] {
+            if y == other.y || y == other.bottom() {
+                guides.push(Guide::Horizontal(y));
+            }
+        }
+
+        if rect.center_y() == other.center_y() {
+            guides.push(Guide::Horizontal(rect.center_y()));
+        }
+    }
+
+    guides.sort_unstable();
+    guides.dedup();
+    guides
+}
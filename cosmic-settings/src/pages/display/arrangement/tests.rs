@@ -0,0 +1,146 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+use super::geometry::{self, Guide, Invalid, Rect};
+
+const fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+    Rect {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+const PRIMARY: Rect = rect(0, 0, 1920, 1080);
+
+#[test]
+fn validate_layouts() {
+    assert_eq!(geometry::validate(&[]), Ok(()));
+    assert_eq!(geometry::validate(&[PRIMARY]), Ok(()));
+
+    // Side by side, and stacked.
+    assert_eq!(
+        geometry::validate(&[PRIMARY, rect(1920, 200, 1280, 1024)]),
+        Ok(())
+    );
+    assert_eq!(
+        geometry::validate(&[PRIMARY, rect(-300, -900, 1440, 900)]),
+        Ok(())
+    );
+
+    // Chained through a display in the middle.
+    assert_eq!(
+        geometry::validate(&[
+            PRIMARY,
+            rect(3840, 0, 1920, 1080),
+            rect(1920, 0, 1920, 1080)
+        ]),
+        Ok(())
+    );
+
+    assert_eq!(
+        geometry::validate(&[PRIMARY, rect(1900, 0, 1920, 1080)]),
+        Err(Invalid::Overlapping)
+    );
+
+    assert_eq!(
+        geometry::validate(&[PRIMARY, rect(1930, 0, 1920, 1080)]),
+        Err(Invalid::Disconnected)
+    );
+
+    // Displays which only meet at a corner are not connected.
+    assert_eq!(
+        geometry::validate(&[PRIMARY, rect(1920, 1080, 1920, 1080)]),
+        Err(Invalid::Disconnected)
+    );
+
+    // Logical sizes rounded at fractional scales may leave a pixel of overlap or gap.
+    assert_eq!(
+        geometry::validate(&[PRIMARY, rect(1919, 0, 1920, 1080)]),
+        Ok(())
+    );
+    assert_eq!(
+        geometry::validate(&[PRIMARY, rect(1921, 0, 1920, 1080)]),
+        Ok(())
+    );
+    assert_eq!(
+        geometry::validate(&[PRIMARY, rect(1918, 0, 1920, 1080)]),
+        Err(Invalid::Overlapping)
+    );
+}
+
+#[test]
+fn nudge_along_shared_edge() {
+    let others = [PRIMARY];
+    let display = rect(1920, 0, 1280, 1024);
+
+    assert_eq!(
+        geometry::nudge(display, &others, 0, 100),
+        Some(rect(1920, 100, 1280, 1024))
+    );
+
+    // Moving into the other display, or away from it, is refused.
+    assert_eq!(geometry::nudge(display, &others, -10, 0), None);
+    assert_eq!(geometry::nudge(display, &others, 10, 0), None);
+    assert_eq!(geometry::nudge(display, &others, 0, 1080), None);
+}
+
+#[test]
+fn attach_to_nearest_side() {
+    let others = [PRIMARY];
+
+    // Dropped beside the right edge, with its top edge near the other's top edge.
+    assert_eq!(
+        geometry::attach(rect(2000, 40, 1920, 1080), &others),
+        Some(rect(1920, 0, 1920, 1080))
+    );
+
+    // Dropped above, with its center near the other's center.
+    assert_eq!(
+        geometry::attach(rect(280, -1000, 1280, 1024), &others),
+        Some(rect(320, -1024, 1280, 1024))
+    );
+
+    // Out of snapping distance, it keeps its offset along the shared edge.
+    assert_eq!(
+        geometry::attach(rect(2000, 400, 1920, 1080), &others),
+        Some(rect(1920, 400, 1920, 1080))
+    );
+
+    // The shared edge is never shorter than the minimum.
+    assert_eq!(
+        geometry::attach(rect(2000, 1060, 1920, 1080), &others),
+        Some(rect(1920, 1080 - geometry::MIN_SHARED_EDGE, 1920, 1080))
+    );
+}
+
+#[test]
+fn attach_without_disconnecting() {
+    // Without the display in the middle, the outer displays would be disconnected.
+    let others = [PRIMARY, rect(3840, 0, 1920, 1080)];
+
+    assert_eq!(geometry::attach(rect(0, -1200, 1920, 1080), &others), None);
+    assert_eq!(
+        geometry::attach(rect(1900, 60, 1920, 1080), &others),
+        Some(rect(1920, 0, 1920, 1080))
+    );
+}
+
+#[test]
+fn alignment_guides() {
+    assert_eq!(
+        geometry::guides(&rect(1920, 0, 1920, 1080), &[PRIMARY]),
+        vec![
+            Guide::Vertical(1920),
+            Guide::Horizontal(0),
+            Guide::Horizontal(540),
+            Guide::Horizontal(1080),
+        ]
+    );
+
+    assert_eq!(
+        geometry::guides(&rect(320, -1024, 1280, 1024), &[PRIMARY]),
+        vec![Guide::Vertical(960), Guide::Horizontal(0)]
+    );
+}
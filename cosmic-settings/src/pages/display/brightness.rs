@@ -0,0 +1,152 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Brightness of the built-in panel, through its backlight device.
+
+use std::any::TypeId;
+
+use cosmic::iced::{self, futures::SinkExt};
+use tokio::io::unix::AsyncFd;
+use zbus::proxy;
+
+const SUBSYSTEM: &str = "backlight";
+
+/// The lowest brightness percentage offered, so that the panel is never turned off entirely.
+pub const MIN_PERCENT: u32 = 1;
+
+#[proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1/session/auto"
+)]
+trait Session {
+    fn set_brightness(&self, subsystem: &str, name: &str, brightness: u32) -> zbus::Result<()>;
+}
+
+/// A backlight device, such as `intel_backlight`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Backlight {
+    pub name: String,
+    pub brightness: u32,
+    pub max_brightness: u32,
+}
+
+impl Backlight {
+    /// Brightness as a percentage of the maximum brightness.
+    pub fn percent(&self) -> u32 {
+        if self.max_brightness == 0 {
+            return 100;
+        }
+
+        (u64::from(self.brightness) * 100 / u64::from(self.max_brightness)) as u32
+    }
+
+    /// Converts a percentage into a brightness of this device.
+    pub fn brightness_from_percent(&self, percent: u32) -> u32 {
+        let percent = percent.clamp(MIN_PERCENT, 100);
+        (u64::from(self.max_brightness) * u64::from(percent)).div_ceil(100) as u32
+    }
+}
+
+/// Finds the backlight of the built-in panel.
+///
+/// Firmware interfaces are preferred over platform and raw interfaces, as the kernel does.
+pub fn backlight() -> Option<Backlight> {
+    let mut enumerator = udev::Enumerator::new().ok()?;
+    enumerator.match_subsystem(SUBSYSTEM).ok()?;
+
+    enumerator
+        .scan_devices()
+        .ok()?
+        .filter_map(|device| {
+            let priority = match device.attribute_value("type")?.to_str()? {
+                "firmware" => 0,
+                "platform" => 1,
+                "raw" => 2,
+                _ => return None,
+            };
+
+            let attribute = |name: &str| -> Option<u32> {
+                device.attribute_value(name)?.to_str()?.trim().parse().ok()
+            };
+
+            let backlight = Backlight {
+                name: device.sysname().to_str()?.to_owned(),
+                brightness: attribute("brightness")?,
+                max_brightness: attribute("max_brightness")?,
+            };
+
+            Some((priority, backlight))
+        })
+        .min_by_key(|(priority, _)| *priority)
+        .map(|(_, backlight)| backlight)
+}
+
+/// Sets the brightness of a backlight through logind, which doesn't require privileges.
+pub async fn set_brightness(name: &str, brightness: u32) -> zbus::Result<()> {
+    let connection = zbus::Connection::system().await?;
+    let session = SessionProxy::new(&connection).await?;
+    session.set_brightness(SUBSYSTEM, name, brightness).await
+}
+
+/// Emits the backlight of the built-in panel, and again whenever its brightness changes,
+/// such as from brightness keys.
+pub fn subscription() -> iced::Subscription<Backlight> {
+    struct BacklightWatcher;
+
+    iced::subscription::channel(
+        TypeId::of::<BacklightWatcher>(),
+        1,
+        |mut output| async move {
+            let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+
+            // The udev monitor is not `Send`, so it must be driven from its own thread.
+            let runtime = tokio::runtime::Handle::current();
+            tokio::task::spawn_blocking(move || {
+                if let Err(why) = runtime.block_on(watch(tx)) {
+                    tracing::error!(?why, "failed to watch backlight changes");
+                }
+            });
+
+            while let Some(backlight) = rx.recv().await {
+                _ = output.send(backlight).await;
+            }
+
+            iced::futures::future::pending().await
+        },
+    )
+}
+
+async fn watch(tx: tokio::sync::mpsc::Sender<Backlight>) -> std::io::Result<()> {
+    let Some(mut current) = backlight() else {
+        return Ok(());
+    };
+
+    let monitor = udev::MonitorBuilder::new()?
+        .match_subsystem(SUBSYSTEM)?
+        .listen()?;
+
+    let monitor = AsyncFd::new(monitor)?;
+
+    if tx.send(current.clone()).await.is_err() {
+        return Ok(());
+    }
+
+    loop {
+        let mut guard = monitor.readable().await?;
+        let events = guard.get_inner().iter().count();
+        guard.clear_ready();
+
+        if events == 0 {
+            continue;
+        }
+
+        if let Some(backlight) = backlight().filter(|backlight| *backlight != current) {
+            current = backlight.clone();
+
+            if tx.send(backlight).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,314 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Color temperature of every output, applied through the wlr gamma control protocol.
+//!
+//! The compositor restores the gamma of an output once the gamma control which changed it is
+//! destroyed, so the temperature is only kept while its [`Gamma`] handle exists.
+
+use std::io::{Seek, SeekFrom, Write};
+use std::os::fd::AsFd;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use cosmic::cctk::wayland_client::{
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::{
+        wl_output::WlOutput,
+        wl_registry::{self, WlRegistry},
+    },
+    Connection, Dispatch, QueueHandle,
+};
+use futures::channel::oneshot;
+use wayland_protocols_wlr::gamma_control::v1::client::{
+    zwlr_gamma_control_manager_v1::ZwlrGammaControlManagerV1,
+    zwlr_gamma_control_v1::{self, ZwlrGammaControlV1},
+};
+
+/// Temperature at which the colors of an output are left as they are, in Kelvin.
+const NEUTRAL: u32 = 6500;
+
+/// Minimum time between two changes, which limits how often a dragged slider is applied.
+const THROTTLE: Duration = Duration::from_millis(50);
+
+/// Handle to the thread which owns the gamma controls of every output.
+pub struct Gamma {
+    tx: mpsc::Sender<u32>,
+}
+
+impl Gamma {
+    /// Starts the thread, which exits along with its connection once the handle is dropped,
+    /// restoring the gamma of every output.
+    ///
+    /// The receiver gets the reason if the thread stops because the temperature can't be
+    /// applied, such as when the compositor doesn't support gamma control.
+    pub fn spawn() -> (Self, oneshot::Receiver<String>) {
+        let (tx, rx) = mpsc::channel();
+        let (failed_tx, failed_rx) = oneshot::channel();
+
+        std::thread::spawn(move || {
+            if let Err(why) = run(rx) {
+                tracing::error!(?why, "failed to apply night light");
+                _ = failed_tx.send(why.to_string());
+            }
+        });
+
+        (Self { tx }, failed_rx)
+    }
+
+    /// Sets the color temperature of every output, in Kelvin.
+    pub fn set(&self, temperature: u32) {
+        _ = self.tx.send(temperature);
+    }
+}
+
+struct Control {
+    /// Registry name of the output.
+    output: u32,
+    control: ZwlrGammaControlV1,
+    /// Number of elements in each ramp, once the compositor has sent it.
+    size: Option<u32>,
+    failed: bool,
+}
+
+#[derive(Default)]
+struct State {
+    /// Outputs by their registry names.
+    outputs: Vec<(u32, WlOutput)>,
+    controls: Vec<Control>,
+}
+
+fn run(rx: mpsc::Receiver<u32>) -> anyhow::Result<()> {
+    let connection = Connection::connect_to_env()?;
+    let (globals, mut event_queue) = registry_queue_init::<State>(&connection)?;
+    let qh = event_queue.handle();
+
+    let manager = globals.bind::<ZwlrGammaControlManagerV1, _, _>(&qh, 1..=1, ())?;
+
+    let mut state = State::default();
+
+    for global in globals.contents().clone_list() {
+        if global.interface == "wl_output" {
+            let output = globals
+                .registry()
+                .bind(global.name, global.version.min(4), &qh, ());
+            state.outputs.push((global.name, output));
+        }
+    }
+
+    while let Ok(mut temperature) = rx.recv() {
+        // Only the latest of the temperatures sent in the meantime is applied.
+        while let Ok(latest) = rx.try_recv() {
+            temperature = latest;
+        }
+
+        // Picks up outputs which were connected or disconnected since the last change.
+        event_queue.roundtrip(&mut state)?;
+
+        if temperature < NEUTRAL {
+            for (name, output) in &state.outputs {
+                if !state.controls.iter().any(|c| c.output == *name) {
+                    state.controls.push(Control {
+                        output: *name,
+                        control: manager.get_gamma_control(output, &qh, *name),
+                        size: None,
+                        failed: false,
+                    });
+                }
+            }
+
+            // Receive the ramp size of the new gamma controls.
+            event_queue.roundtrip(&mut state)?;
+
+            let white = whitepoint(temperature);
+
+            for control in &state.controls {
+                let Some(size) = control.size.filter(|_| !control.failed) else {
+                    continue;
+                };
+
+                let ramps = ramps(white, size);
+
+                if let Err(why) =
+                    ramp_file(&ramps).map(|file| control.control.set_gamma(file.as_fd()))
+                {
+                    tracing::error!(?why, "failed to create gamma ramp");
+                }
+            }
+        } else {
+            for control in state.controls.drain(..) {
+                control.control.destroy();
+            }
+        }
+
+        connection.flush()?;
+        std::thread::sleep(THROTTLE);
+    }
+
+    Ok(())
+}
+
+/// Relative intensity of red, green, and blue in white light of a color temperature.
+///
+/// Based on Tanner Helland's approximation of the blackbody spectrum.
+fn whitepoint(kelvin: u32) -> [f64; 3] {
+    let blackbody = |kelvin: u32| {
+        let t = f64::from(kelvin) / 100.0;
+
+        let red = if t <= 66.0 {
+            255.0
+        } else {
+            329.698_727_446 * (t - 60.0).powf(-0.133_204_759_2)
+        };
+
+        let green = if t <= 66.0 {
+            99.470_802_586_1 * t.ln() - 161.119_568_166_1
+        } else {
+            288.122_169_528_3 * (t - 60.0).powf(-0.075_514_849_2)
+        };
+
+        let blue = if t >= 66.0 {
+            255.0
+        } else if t <= 19.0 {
+            0.0
+        } else {
+            138.517_731_223_1 * (t - 10.0).ln() - 305.044_792_730_7
+        };
+
+        [red, green, blue].map(|channel| channel.clamp(0.0, 255.0))
+    };
+
+    // Relative to the neutral temperature, so that it leaves the colors as they are.
+    let neutral = blackbody(NEUTRAL);
+    let color = blackbody(kelvin);
+
+    [0, 1, 2].map(|channel| (color[channel] / neutral[channel]).clamp(0.0, 1.0))
+}
+
+/// Red, green, and blue ramps of `size` elements each, scaled by the whitepoint.
+fn ramps(white: [f64; 3], size: u32) -> Vec<u16> {
+    let last = f64::from(size.saturating_sub(1).max(1));
+
+    white
+        .into_iter()
+        .flat_map(|scale| {
+            (0..size).map(move |step| {
+                (f64::from(step) / last * scale * f64::from(u16::MAX)).round() as u16
+            })
+        })
+        .collect()
+}
+
+/// Writes the ramps to an unlinked file, which is how the protocol receives them.
+fn ramp_file(ramps: &[u16]) -> std::io::Result<std::fs::File> {
+    let dir = dirs::runtime_dir().unwrap_or_else(std::env::temp_dir);
+    let path = dir.join(format!("cosmic-settings-gamma-{}", std::process::id()));
+
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)?;
+
+    _ = std::fs::remove_file(&path);
+
+    let bytes = ramps
+        .iter()
+        .flat_map(|value| value.to_ne_bytes())
+        .collect::<Vec<_>>();
+
+    // The compositor reads the ramps from the current offset of the file.
+    file.write_all(&bytes)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for State {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } if interface == "wl_output" => {
+                let output = registry.bind(name, version.min(4), qh, ());
+                state.outputs.push((name, output));
+            }
+
+            wl_registry::Event::GlobalRemove { name } => {
+                state.outputs.retain(|(output, _)| *output != name);
+
+                state.controls.retain(|control| {
+                    let keep = control.output != name;
+
+                    if !keep {
+                        control.control.destroy();
+                    }
+
+                    keep
+                });
+            }
+
+            _ => (),
+        }
+    }
+}
+
+impl Dispatch<WlOutput, ()> for State {
+    fn event(
+        _: &mut Self,
+        _: &WlOutput,
+        _: <WlOutput as cosmic::cctk::wayland_client::Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrGammaControlManagerV1, ()> for State {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrGammaControlManagerV1,
+        _: <ZwlrGammaControlManagerV1 as cosmic::cctk::wayland_client::Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrGammaControlV1, u32> for State {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrGammaControlV1,
+        event: zwlr_gamma_control_v1::Event,
+        output: &u32,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(control) = state.controls.iter_mut().find(|c| c.output == *output) else {
+            return;
+        };
+
+        match event {
+            zwlr_gamma_control_v1::Event::GammaSize { size } => control.size = Some(size),
+
+            // Another client controls the gamma of the output, or it can't be changed.
+            zwlr_gamma_control_v1::Event::Failed => {
+                tracing::warn!(output, "gamma control of output failed");
+                control.failed = true;
+            }
+
+            _ => (),
+        }
+    }
+}
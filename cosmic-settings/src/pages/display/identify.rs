@@ -0,0 +1,70 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Overlays which identify each output by its number in the display arrangement.
+
+use std::time::Duration;
+
+use cosmic::cctk::wayland_client::protocol::wl_output::WlOutput;
+use cosmic::iced::wayland::actions::layer_surface::{IcedOutput, SctkLayerSurfaceSettings};
+use cosmic::iced::{window, Alignment, Length};
+use cosmic::iced_sctk::commands::layer_surface::{
+    destroy_layer_surface, get_layer_surface, Anchor, KeyboardInteractivity, Layer,
+};
+use cosmic::widget::{column, container, text};
+use cosmic::{Apply, Command, Element};
+
+/// How long the overlays are shown for.
+pub const DURATION: Duration = Duration::from_secs(3);
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 220;
+
+pub struct Overlay {
+    pub id: window::Id,
+    pub number: usize,
+    pub name: String,
+}
+
+impl Overlay {
+    pub fn new(number: usize, name: String) -> Self {
+        Self {
+            id: window::Id::unique(),
+            number,
+            name,
+        }
+    }
+
+    /// Creates the overlay as a layer surface centered on the output.
+    pub fn show<Message>(&self, output: WlOutput) -> Command<Message> {
+        get_layer_surface(SctkLayerSurfaceSettings {
+            id: self.id,
+            layer: Layer::Overlay,
+            keyboard_interactivity: KeyboardInteractivity::None,
+            anchor: Anchor::empty(),
+            output: IcedOutput::Output(output),
+            namespace: String::from("cosmic-settings-identify"),
+            size: Some((Some(WIDTH), Some(HEIGHT))),
+            exclusive_zone: -1,
+            ..Default::default()
+        })
+    }
+
+    pub fn hide<Message>(&self) -> Command<Message> {
+        destroy_layer_surface(self.id)
+    }
+
+    pub fn view<Message: 'static>(&self) -> Element<'_, Message> {
+        column()
+            .align_items(Alignment::Center)
+            .push(text(self.number.to_string()).size(96))
+            .push(text::title3(&self.name))
+            .apply(container)
+            .center_x()
+            .center_y()
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(crate::theme::display_identify())
+            .into()
+    }
+}
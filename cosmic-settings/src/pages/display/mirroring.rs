@@ -0,0 +1,284 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Mirror groups, in which a source display is shown on any number of target displays.
+
+use cosmic::cosmic_config::{self, ConfigGet, ConfigSet};
+use cosmic::widget::{self, column, settings, toggler};
+use cosmic::{Apply, Command, Element};
+use cosmic_randr_shell::{List, OutputKey};
+use cosmic_settings_page::Section;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use slab::Slab;
+use slotmap::SecondaryMap;
+
+use super::{apply_randr, confirm_layout, profiles, randr, Message};
+use crate::{app, pages};
+
+const MIRROR_FIT: &str = "display-mirror-fit";
+
+static FIT_LABELS: Lazy<Vec<String>> =
+    Lazy::new(|| Fit::ALL.iter().map(|fit| fit.title()).collect());
+
+/// How the source is shown on targets whose resolution differs from it.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Fit {
+    /// Targets switch to the mode nearest to the source's resolution, filling the screen.
+    #[default]
+    Scale,
+    /// Targets keep their preferred mode, with bars where the aspect ratios differ.
+    Letterbox,
+}
+
+impl Fit {
+    pub const ALL: [Self; 2] = [Self::Scale, Self::Letterbox];
+
+    pub fn title(self) -> String {
+        match self {
+            Self::Scale => fl!("mirroring", "fit-scale"),
+            Self::Letterbox => fl!("mirroring", "fit-letterbox"),
+        }
+    }
+
+    pub fn position(self) -> usize {
+        Self::ALL.iter().position(|&fit| fit == self).unwrap_or(0)
+    }
+}
+
+pub fn load(config: &cosmic_config::Config) -> Fit {
+    config.get(MIRROR_FIT).unwrap_or_else(|why| {
+        if !matches!(why, cosmic_config::Error::NoConfigDirectory) {
+            tracing::error!(?why, "failed to read config '{MIRROR_FIT}'");
+        }
+
+        Fit::default()
+    })
+}
+
+pub fn save(config: &cosmic_config::Config, fit: Fit) {
+    if let Err(why) = config.set(MIRROR_FIT, fit) {
+        tracing::error!(?why, "failed to set config '{MIRROR_FIT}'");
+    }
+}
+
+/// The source of the mirror group that an output belongs to, which may be the output itself.
+pub fn source(mirror_map: &SecondaryMap<OutputKey, OutputKey>, output: OutputKey) -> OutputKey {
+    mirror_map.get(output).copied().unwrap_or(output)
+}
+
+/// The outputs which mirror `source`.
+pub fn targets(
+    mirror_map: &SecondaryMap<OutputKey, OutputKey>,
+    source: OutputKey,
+) -> Vec<OutputKey> {
+    mirror_map
+        .iter()
+        .filter(|&(_, &from)| from == source)
+        .map(|(target, _)| target)
+        .collect()
+}
+
+/// Creates a request which mirrors `source` onto each of the `targets`, and stops mirroring
+/// it onto the `released` outputs.
+pub fn request(
+    list: &List,
+    source: OutputKey,
+    targets: &[OutputKey],
+    released: &[OutputKey],
+    fit: Fit,
+) -> randr::Request {
+    let Some(source_output) = list.outputs.get(source) else {
+        return randr::Request::new();
+    };
+
+    let source_size = source_output
+        .current
+        .and_then(|mode| list.modes.get(mode))
+        .map(|mode| mode.size);
+
+    let mut request = randr::Request::new().enable(&source_output.name);
+
+    for &key in released {
+        if let Some(output) = list.outputs.get(key) {
+            request = request.enable(&output.name);
+        }
+    }
+
+    for &key in targets {
+        let Some(output) = list.outputs.get(key) else {
+            continue;
+        };
+
+        request = request.mirror(&output.name, &source_output.name);
+
+        if let Some(((width, height), refresh_rate)) =
+            source_size.and_then(|size| mode(list, key, size, fit))
+        {
+            request = request
+                .resolution(&output.name, width, height)
+                .refresh_rate(&output.name, refresh_rate);
+        }
+    }
+
+    request
+}
+
+/// The mode that a target shows the source with.
+fn mode(
+    list: &List,
+    target: OutputKey,
+    (width, height): (u32, u32),
+    fit: Fit,
+) -> Option<((u32, u32), u32)> {
+    let mut modes = list.outputs[target]
+        .modes
+        .iter()
+        .filter_map(|&mode| list.modes.get(mode));
+
+    let mode = match fit {
+        Fit::Letterbox => modes.find(|mode| mode.preferred),
+
+        // The source's own resolution if the target supports it, or else the largest mode
+        // with the same aspect ratio.
+        Fit::Scale => modes
+            .filter(|mode| {
+                u64::from(mode.size.0) * u64::from(height)
+                    == u64::from(mode.size.1) * u64::from(width)
+            })
+            .max_by_key(|mode| {
+                (
+                    mode.size == (width, height),
+                    mode.size.0 * mode.size.1,
+                    mode.refresh_rate,
+                )
+            }),
+    }?;
+
+    Some((mode.size, mode.refresh_rate))
+}
+
+/// View for the mirror group of the active display.
+pub fn section() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let source_label = descriptions.insert(fl!("mirroring", "source"));
+    let targets_label = descriptions.insert(fl!("mirroring", "targets"));
+    let fit_label = descriptions.insert(fl!("mirroring", "fit"));
+
+    Section::default()
+        .title(fl!("mirroring", "group"))
+        .descriptions(descriptions)
+        .show_while::<super::Page>(|page| {
+            let source = source(&page.mirror_map, page.active_display);
+            !targets(&page.mirror_map, source).is_empty()
+        })
+        .view::<super::Page>(move |_binder, page, section| {
+            let descriptions = &section.descriptions;
+
+            let source = source(&page.mirror_map, page.active_display);
+            let targets = targets(&page.mirror_map, source);
+
+            let display_name = |key: OutputKey| {
+                page.list
+                    .outputs
+                    .get(key)
+                    .map(|output| crate::utils::display_name(&output.name, output.physical))
+                    .unwrap_or_default()
+            };
+
+            let group = settings::view_section(&section.title)
+                .add(settings::item(
+                    &descriptions[source_label],
+                    widget::text::body(display_name(source)),
+                ))
+                .add(settings::item(
+                    &descriptions[fit_label],
+                    widget::dropdown(
+                        &FIT_LABELS,
+                        Some(page.mirror_fit.position()),
+                        Message::MirrorFit,
+                    ),
+                ));
+
+            let mut target_list = settings::view_section(&descriptions[targets_label]);
+
+            for key in page.list.outputs.keys().filter(|&key| key != source) {
+                target_list = target_list.add(settings::item(
+                    display_name(key),
+                    toggler(None, targets.contains(&key), move |mirror| {
+                        Message::MirrorTarget(key, mirror)
+                    }),
+                ));
+            }
+
+            column()
+                .spacing(24)
+                .push(group)
+                .push(target_list)
+                .apply(Element::from)
+                .map(pages::Message::Displays)
+        })
+}
+
+impl super::Page {
+    /// Mirrors `source` onto exactly the given `targets`, releasing any other outputs which
+    /// mirrored it. The previous layout is restored if the change is not confirmed.
+    pub fn set_mirror_group(
+        &mut self,
+        source: OutputKey,
+        targets: Vec<OutputKey>,
+    ) -> Command<app::Message> {
+        let released = self::targets(&self.mirror_map, source)
+            .into_iter()
+            .filter(|key| !targets.contains(key))
+            .collect::<Vec<_>>();
+
+        let request = request(&self.list, source, &targets, &released, self.mirror_fit);
+
+        if request.is_empty() {
+            return Command::none();
+        }
+
+        let revert = profiles::Profile::capture(String::new(), &self.list, &self.heads)
+            .request(&self.list, &self.heads);
+
+        Command::batch(vec![apply_randr(request), confirm_layout(revert)])
+    }
+
+    /// Adds or removes a target from the mirror group of the active display.
+    pub fn set_mirror_target(&mut self, target: OutputKey, mirror: bool) -> Command<app::Message> {
+        let source = source(&self.mirror_map, self.active_display);
+        let mut targets = targets(&self.mirror_map, source);
+
+        targets.retain(|&key| key != target);
+
+        if mirror {
+            targets.push(target);
+        }
+
+        self.set_mirror_group(source, targets)
+    }
+
+    /// Changes how mismatched resolutions are reconciled, and applies it to the active group.
+    pub fn set_mirror_fit(&mut self, option: usize) -> Command<app::Message> {
+        let Some(&fit) = Fit::ALL.get(option) else {
+            return Command::none();
+        };
+
+        self.mirror_fit = fit;
+
+        if let Some(config) = self.settings_config.as_ref() {
+            save(config, fit);
+        }
+
+        let source = source(&self.mirror_map, self.active_display);
+        let targets = targets(&self.mirror_map, source);
+
+        if targets.is_empty() {
+            return Command::none();
+        }
+
+        self.set_mirror_group(source, targets)
+    }
+}
@@ -1,12 +1,22 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
+pub mod adaptive_sync;
 pub mod arrangement;
-// pub mod night_light;
+pub mod brightness;
+pub mod gamma;
+pub mod identify;
+pub mod mirroring;
+pub mod night_light;
+pub mod profiles;
+pub mod randr;
 
 use crate::{app, pages};
+use adaptive_sync::AdaptiveSync;
 use arrangement::Arrangement;
-use cosmic::iced::{time, Alignment, Length};
+use cosmic::cctk::wayland_client::protocol::wl_output::WlOutput;
+use cosmic::cosmic_config;
+use cosmic::iced::{Alignment, Length};
 use cosmic::iced_widget::scrollable::{Direction, Properties, RelativeOffset};
 use cosmic::prelude::CollectionWidget;
 use cosmic::widget::{
@@ -18,73 +28,122 @@ use cosmic_settings_page::{self as page, section, Section};
 use once_cell::sync::Lazy;
 use slab::Slab;
 use slotmap::{Key, SecondaryMap, SlotMap};
-use std::{collections::BTreeMap, process::ExitStatus, sync::Arc};
+use std::{collections::BTreeMap, ops::RangeInclusive, sync::Arc};
 
 static DPI_SCALES: &[u32] = &[50, 75, 100, 125, 150, 175, 200, 225, 250, 275, 300];
-static DPI_SCALE_LABELS: Lazy<Vec<String>> =
-    Lazy::new(|| DPI_SCALES.iter().map(|scale| format!("{scale}%")).collect());
-
-/// Display color depth options
-#[derive(Clone, Copy, Debug)]
-pub struct ColorDepth(usize);
+/// Custom scales accepted by the compositor, in percent.
+const CUSTOM_SCALES: RangeInclusive<u32> = 50..=300;
+/// Custom scales are limited to steps of 5% to keep the choices manageable. The compositor
+/// rounds them to its own precision of 1/120.
+const CUSTOM_SCALE_STEP: u32 = 5;
+/// Modes below this refresh rate, in millihertz, are hidden unless all modes are shown.
+const MIN_REFRESH_RATE: u32 = 50_000;
+const CUSTOM_MODE_WIDTHS: RangeInclusive<u32> = 320..=16384;
+const CUSTOM_MODE_HEIGHTS: RangeInclusive<u32> = 200..=16384;
+/// Custom refresh rates, in hertz.
+const CUSTOM_MODE_RATES: RangeInclusive<f32> = 1.0..=1000.0;
+static ADAPTIVE_SYNC_LABELS: Lazy<Vec<String>> =
+    Lazy::new(|| AdaptiveSync::ALL.iter().map(|mode| mode.title()).collect());
 
 /// Identifies the content to display in the context drawer
-// pub enum ContextDrawer {
-//     NightLight,
-// }
+pub enum ContextDrawer {
+    NightLight,
+}
+
+/// Fields of a custom display mode.
+#[derive(Clone, Copy, Debug)]
+pub enum ModeField {
+    Width,
+    Height,
+    RefreshRate,
+}
 
 /// Display mirroring options
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Mirroring {
     Disable,
-    // ProjectToAll,
+    ProjectToAll,
     Project(OutputKey),
     Mirror(OutputKey),
 }
 
 /// Night light preferences
-// #[derive(Clone, Copy, Debug)]
-// pub enum NightLight {
-/// Toggles night light's automatic scheduling.
-//     AutoSchedule(bool),
-/// Sets the night light schedule.
-//     ManualSchedule,
-/// Changes the preferred night light temperature.
-//     Temperature(f32),
-/// Toggles night light mode
-//     Toggle(bool),
-// }
+#[derive(Clone, Debug)]
+pub enum NightLight {
+    /// Toggles night light's automatic scheduling.
+    AutoSchedule(bool),
+    /// The gamma control thread stopped, with the reason if it failed.
+    Exited(Option<String>),
+    /// Sets the start of the manual schedule.
+    ScheduleFrom(usize),
+    /// Sets the end of the manual schedule.
+    ScheduleTo(usize),
+    /// Previews a night light temperature while it is being dragged.
+    Temperature(u32),
+    /// Saves the previewed night light temperature.
+    TemperatureRelease,
+    /// Follows the schedule as time passes.
+    Tick,
+    /// Toggles night light mode
+    Toggle(bool),
+}
 
 #[derive(Clone, Debug)]
 pub enum Message {
+    /// Set the adaptive sync mode of a display.
+    AdaptiveSync(usize),
+    /// The backlight of the built-in panel was found, or its brightness changed.
+    Backlight(brightness::Backlight),
+    /// Set the brightness of the built-in panel, in percent.
+    Brightness(u32),
     /// Change placement of display
     Position(OutputKey, i32, i32),
     /// Changes the active display being configured.
     Display(segmented_button::Entity),
-    /// Set the color depth of a display.
-    ColorDepth(ColorDepth),
-    /// Set the color profile of a display.
-    ColorProfile(usize),
-    /// The dialog was cancelled, and will revert settings.
-    DialogCancel,
-    /// The dialog was completed.
-    DialogComplete,
-    /// How long until the dialog automatically cancelles, in seconds.
-    DialogCountdown,
+    /// Whether it is currently between sunrise and sunset.
+    Daytime(bool),
     /// Toggles display on or off.
     DisplayToggle(bool),
+    /// Show the number of every display on the display itself.
+    Identify,
+    /// Hide the overlays identifying each display.
+    IdentifyHide,
+    /// Apply a saved display profile.
+    ProfileApply(usize),
+    /// Delete a saved display profile.
+    ProfileDelete(usize),
+    /// Edit the name of a new or renamed profile.
+    ProfileNameInput(String),
+    /// Start renaming a saved display profile.
+    ProfileRename(usize),
+    /// Save the current configuration as a new profile, or finish renaming one.
+    ProfileSave,
     /// Configures mirroring status of a display.
     Mirroring(Mirroring),
+    /// Set how mirrored displays reconcile mismatched resolutions.
+    MirrorFit(usize),
+    /// Add or remove a display from the active mirror group.
+    MirrorTarget(OutputKey, bool),
+    /// A wayland output was added.
+    OutputAdded(String, WlOutput),
+    /// A wayland output was removed.
+    OutputRemoved(WlOutput),
+    /// Edit a field of the custom mode.
+    ModeCustom(ModeField, String),
+    /// Apply the custom mode to a display.
+    ModeCustomSubmit,
+    /// Show or hide the custom mode entry.
+    ModeCustomToggle,
     /// Handle night light preferences.
-    // NightLight(NightLight),
+    NightLight(NightLight),
     /// Show the night light mode context drawer.
-    // NightLightContext,
+    NightLightContext,
     /// Set the orientation of a display.
     Orientation(Transform),
     /// Pan the displays view
     Pan(arrangement::Pan),
     /// Status of an applied display change.
-    RandrResult(Arc<std::io::Result<ExitStatus>>),
+    RandrResult(Arc<Result<(), randr::Error>>),
     /// Request to reload the page.
     Refresh,
     /// Set the refresh rate of a display.
@@ -93,10 +152,18 @@ pub enum Message {
     Resolution(usize),
     /// Set the preferred scale for a display.
     Scale(usize),
+    /// Edit the custom scale of a display.
+    ScaleCustom(String),
+    /// Apply the custom scale of a display.
+    ScaleCustomSubmit,
+    /// Show modes which are usually hidden, such as low refresh rates.
+    ShowAllModes(bool),
     /// Refreshes display outputs.
     Update {
         /// Available outputs from cosmic-randr.
         randr: Arc<Result<List, cosmic_randr_shell::Error>>,
+        /// State of the outputs which their list leaves out, by connector name.
+        heads: BTreeMap<String, randr::HeadInfo>,
     },
 }
 
@@ -109,7 +176,8 @@ impl From<Message> for app::Message {
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Randr {
-    Mirror(OutputKey),
+    AdaptiveSync(AdaptiveSync),
+    Mode(u32, u32, u32),
     Position(i32, i32),
     RefreshRate(u32),
     Resolution(u32, u32),
@@ -121,40 +189,128 @@ enum Randr {
 /// The page struct for the display settings page.
 pub struct Page {
     list: List,
+    /// State of the outputs which their list leaves out, by connector name.
+    heads: BTreeMap<String, randr::HeadInfo>,
     display_tabs: segmented_button::SingleSelectModel,
     mirror_map: SecondaryMap<OutputKey, OutputKey>,
     mirror_menu: widget::dropdown::multi::Model<String, Mirroring>,
+    mirror_fit: mirroring::Fit,
     active_display: OutputKey,
     background_service: Option<tokio::task::JoinHandle<()>>,
     config: Config,
     cache: ViewCache,
-    // context: Option<ContextDrawer>,
+    context: Option<ContextDrawer>,
+    /// Whether it is daytime at the current location, if known.
+    daytime: Option<bool>,
     display_arrangement_scrollable: widget::Id,
     /// Tracks the last pan status.
     last_pan: f32,
-    /// The setting to revert to if the next dialog page is cancelled.
-    dialog: Option<Randr>,
-    /// the instant the setting was changed.
-    dialog_countdown: usize,
+    /// Error from the last display configuration that failed to apply.
+    randr_error: Option<randr::Error>,
+    night_light_config: Option<cosmic_config::Config>,
+    /// Applies the night light temperature to every output, while night light is shown.
+    gamma: Option<gamma::Gamma>,
+    /// Why the night light temperature couldn't be applied.
+    gamma_error: Option<String>,
+    settings_config: Option<cosmic_config::Config>,
+    custom_mode: Option<CustomMode>,
+    /// Wayland outputs by connector name, for showing surfaces on them.
+    wl_outputs: Vec<(String, WlOutput)>,
+    identify_overlays: Vec<identify::Overlay>,
+    /// Backlight of the built-in panel, if it has one.
+    backlight: Option<brightness::Backlight>,
+    profiles: Vec<profiles::Profile>,
+    /// Name entered for a new profile, or for the profile being renamed.
+    profile_name: String,
+    profile_renaming: Option<usize>,
+    /// Percentage entered as a custom scale, when a custom scale is being used.
+    scale_custom: Option<String>,
+    scale_custom_invalid: bool,
+    show_all_modes: bool,
     show_display_options: bool,
 }
 
+/// Custom mode entered by the user.
+#[derive(Default)]
+struct CustomMode {
+    width: String,
+    height: String,
+    refresh_rate: String,
+    invalid: bool,
+}
+
+impl CustomMode {
+    /// The size and refresh rate, in millihertz, if they are valid.
+    fn parse(&self) -> Option<(u32, u32, u32)> {
+        let width = self.width.trim().parse().ok()?;
+        let height = self.height.trim().parse().ok()?;
+        let refresh_rate: f32 = self.refresh_rate.trim().parse().ok()?;
+
+        (CUSTOM_MODE_WIDTHS.contains(&width)
+            && CUSTOM_MODE_HEIGHTS.contains(&height)
+            && CUSTOM_MODE_RATES.contains(&refresh_rate))
+        .then(|| (width, height, (refresh_rate * 1000.0).round() as u32))
+    }
+}
+
 impl Default for Page {
     fn default() -> Self {
+        let settings_config = cosmic_config::Config::new(crate::config::NAME, 1)
+            .inspect_err(|why| tracing::error!(?why, "failed to get settings config"))
+            .ok();
+
+        let profiles = settings_config
+            .as_ref()
+            .map(profiles::load)
+            .unwrap_or_default();
+
+        let mirror_fit = settings_config
+            .as_ref()
+            .map(mirroring::load)
+            .unwrap_or_default();
+
+        let night_light_config = cosmic_config::Config::new(night_light::NAME, 1)
+            .inspect_err(|why| tracing::error!(?why, "failed to get night light config"))
+            .ok();
+
+        let config = Config {
+            night_light: night_light_config
+                .as_ref()
+                .map(night_light::Settings::load)
+                .unwrap_or_default(),
+            ..Config::default()
+        };
+
         Self {
             list: List::default(),
+            heads: BTreeMap::new(),
             display_tabs: segmented_button::SingleSelectModel::default(),
             mirror_map: SecondaryMap::new(),
             mirror_menu: widget::dropdown::multi::model(),
+            mirror_fit,
             active_display: OutputKey::default(),
             background_service: None,
-            config: Config::default(),
+            config,
             cache: ViewCache::default(),
-            // context: None,
+            context: None,
+            daytime: None,
             display_arrangement_scrollable: widget::Id::unique(),
             last_pan: 0.5,
-            dialog: None,
-            dialog_countdown: 0,
+            randr_error: None,
+            night_light_config,
+            gamma: None,
+            gamma_error: None,
+            settings_config,
+            custom_mode: None,
+            wl_outputs: Vec::new(),
+            identify_overlays: Vec::new(),
+            backlight: None,
+            profiles,
+            profile_name: String::new(),
+            profile_renaming: None,
+            scale_custom: None,
+            scale_custom_invalid: false,
+            show_all_modes: false,
             show_display_options: true,
         }
     }
@@ -162,8 +318,7 @@ impl Default for Page {
 
 #[derive(Default)]
 struct Config {
-    /// Whether night light is enabled.
-    // night_light_enabled: bool,
+    night_light: night_light::Settings,
     refresh_rate: Option<u32>,
     resolution: Option<(u32, u32)>,
     scale: u32,
@@ -176,6 +331,8 @@ struct ViewCache {
     orientations: [String; 4],
     refresh_rates: Vec<String>,
     resolutions: Vec<String>,
+    scales: Vec<String>,
+    adaptive_sync_selected: Option<usize>,
     orientation_selected: Option<usize>,
     refresh_rate_selected: Option<usize>,
     resolution_selected: Option<usize>,
@@ -191,19 +348,15 @@ impl page::Page<crate::pages::Message> for Page {
     ) -> Option<page::Content> {
         Some(vec![
             // Night light
-            // sections.insert(
-            //     Section::default()
-            //         .descriptions(vec![
-            //             text::NIGHT_LIGHT.as_str().into(),
-            //             text::NIGHT_LIGHT_AUTO.as_str().into(),
-            //             text::NIGHT_LIGHT_DESCRIPTION.as_str().into(),
-            //         ])
-            //         .view::<Page>(move |_binder, page, _section| page.night_light_view()),
-            // ),
+            sections.insert(night_light::section()),
             // Display arrangement
             sections.insert(display_arrangement()),
             // Display configuration
             sections.insert(display_configuration()),
+            // Mirror group
+            sections.insert(mirroring::section()),
+            // Display profiles
+            sections.insert(display_profiles()),
         ])
     }
 
@@ -301,42 +454,17 @@ impl page::Page<crate::pages::Message> for Page {
 
             crate::pages::Message::Displays(Message::Update {
                 randr: Arc::new(Ok(randr)),
+                heads: BTreeMap::new(),
             })
         })
     }
 
-    // fn context_drawer(&self) -> Option<Element<pages::Message>> {
-    //     Some(match self.context {
-
-    //         Some(ContextDrawer::NightLight) => self.night_light_context_view(),
-
-    //         None => return None,
-    //     })
-    // }
-
-    /// Opens a dialog to confirm the display settings.
-    ///
-    /// This dialog has a 10 (arbitrary) second counter which will
-    /// automatically revert to the original display settings when depleted.
-    ///
-    /// To make a setting activate this dialog. Call the `set_dialog` method with
-    /// the Randr enum value which undos the current change. Makde sure the
-    /// return value is returned with the `exec_value` return value within a batch
-    /// command.
-    fn dialog(&self) -> Option<Element<pages::Message>> {
-        self.dialog?;
-        let element = widget::dialog(fl!("dialog", "title"))
-            .body(fl!("dialog", "change-prompt", time = self.dialog_countdown))
-            .primary_action(
-                widget::button::suggested(fl!("dialog", "keep-changes"))
-                    .on_press(pages::Message::Displays(Message::DialogComplete)),
-            )
-            .secondary_action(
-                widget::button::standard(fl!("dialog", "revert-settings"))
-                    .on_press(pages::Message::Displays(Message::DialogCancel)),
-            )
-            .into();
-        Some(element)
+    fn context_drawer(&self) -> Option<Element<pages::Message>> {
+        Some(match self.context {
+            Some(ContextDrawer::NightLight) => self.night_light_context_view(),
+
+            None => return None,
+        })
     }
 }
 
@@ -344,83 +472,205 @@ impl Page {
     pub fn update(&mut self, message: Message) -> Command<app::Message> {
         match message {
             Message::RandrResult(result) => {
-                if let Some(Err(why)) = Arc::into_inner(result) {
-                    tracing::error!(?why, "cosmic-randr error");
-                } else {
-                    // Reload display info
-                    return cosmic::command::future(async move {
-                        crate::Message::PageMessage(on_enter().await)
-                    });
-                }
+                self.randr_error = match Arc::into_inner(result) {
+                    Some(Err(why)) => {
+                        tracing::error!(?why, "failed to apply display configuration");
+                        Some(why)
+                    }
+                    _ => None,
+                };
+
+                // Reload display info to reflect what the compositor actually applied.
+                return cosmic::command::future(async move {
+                    crate::Message::PageMessage(on_enter().await)
+                });
             }
 
-            Message::DialogCancel => {
-                let Some(request) = self.dialog else {
+            Message::AdaptiveSync(option) => {
+                let Some(&mode) = AdaptiveSync::ALL.get(option) else {
                     return Command::none();
                 };
-                let Some(output) = self.list.outputs.get(self.active_display) else {
-                    return Command::none();
-                };
-                self.dialog = None;
-                self.dialog_countdown = 0;
-                return self.exec_randr(output, request);
+
+                return self.set_adaptive_sync(mode);
             }
 
-            Message::DialogComplete => {
-                self.dialog = None;
-                self.dialog_countdown = 0;
+            Message::Display(display) => self.set_display(display),
+
+            Message::Backlight(backlight) => {
+                self.backlight = Some(backlight);
+                return Command::none();
             }
 
-            Message::DialogCountdown => {
-                if self.dialog_countdown == 0 {
-                    if self.dialog.is_some() {
-                        return command::message(app::Message::from(Message::DialogCancel));
-                    }
-                } else {
-                    self.dialog_countdown -= 1;
-                    return command::future(async move {
-                        tokio::time::sleep(time::Duration::from_secs(1)).await;
-                        Message::DialogCountdown
-                    });
-                }
+            Message::Brightness(percent) => {
+                self.set_brightness(percent);
+                return Command::none();
             }
 
-            Message::Display(display) => self.set_display(display),
+            Message::Identify => return self.identify(),
 
-            Message::ColorDepth(color_depth) => return self.set_color_depth(color_depth),
+            Message::IdentifyHide => {
+                return Command::batch(
+                    self.identify_overlays
+                        .drain(..)
+                        .map(|overlay| overlay.hide())
+                        .collect::<Vec<_>>(),
+                );
+            }
 
-            Message::ColorProfile(profile) => return self.set_color_profile(profile),
+            Message::Daytime(daytime) => {
+                self.daytime = Some(daytime);
+                return self.apply_night_light();
+            }
 
             Message::DisplayToggle(enable) => return self.toggle_display(enable),
 
+            Message::ProfileApply(id) => {
+                if let Some(profile) = self.profiles.get(id) {
+                    return apply_randr(profile.request(&self.list, &self.heads));
+                }
+            }
+
+            Message::ProfileDelete(id) => {
+                if id < self.profiles.len() {
+                    self.profiles.remove(id);
+                    self.profile_renaming = None;
+                    self.save_profiles();
+                }
+            }
+
+            Message::ProfileNameInput(name) => self.profile_name = name,
+
+            Message::ProfileRename(id) => {
+                if let Some(profile) = self.profiles.get(id) {
+                    self.profile_name.clone_from(&profile.name);
+                    self.profile_renaming = Some(id);
+                }
+            }
+
+            Message::ProfileSave => {
+                let name = self.profile_name.trim();
+
+                if name.is_empty() {
+                    return Command::none();
+                }
+
+                match self.profile_renaming.take() {
+                    Some(id) => {
+                        if let Some(profile) = self.profiles.get_mut(id) {
+                            profile.name = name.to_owned();
+                        }
+                    }
+
+                    None => {
+                        let profile =
+                            profiles::Profile::capture(name.to_owned(), &self.list, &self.heads);
+
+                        // A profile with the same name for the same monitors is replaced.
+                        self.profiles.retain(|p| {
+                            p.name != profile.name || p.outputs.keys().ne(profile.outputs.keys())
+                        });
+                        self.profiles.push(profile);
+                    }
+                }
+
+                self.profile_name.clear();
+                self.save_profiles();
+            }
+
             Message::Mirroring(mirroring) => match mirroring {
-                Mirroring::Disable => return self.toggle_display(true),
+                Mirroring::Disable => {
+                    // A source stops being mirrored, whereas a target shows its own content.
+                    if !mirroring::targets(&self.mirror_map, self.active_display).is_empty() {
+                        return self.set_mirror_group(self.active_display, Vec::new());
+                    }
 
-                Mirroring::Mirror(from_display) => {
-                    let Some(output) = self.list.outputs.get(self.active_display) else {
-                        return Command::none();
-                    };
+                    return self.toggle_display(true);
+                }
 
-                    return self.exec_randr(output, Randr::Mirror(from_display));
+                Mirroring::Mirror(from_display) => {
+                    let mut targets = mirroring::targets(&self.mirror_map, from_display);
+                    targets.push(self.active_display);
+                    return self.set_mirror_group(from_display, targets);
                 }
 
                 Mirroring::Project(to_display) => {
-                    let Some(output) = self.list.outputs.get(to_display) else {
-                        return Command::none();
-                    };
+                    return self.set_mirror_group(self.active_display, vec![to_display]);
+                }
+
+                Mirroring::ProjectToAll => {
+                    let targets = self
+                        .list
+                        .outputs
+                        .keys()
+                        .filter(|&key| key != self.active_display)
+                        .collect();
 
-                    return self.exec_randr(output, Randr::Mirror(self.active_display));
-                } // Mirroring::ProjectToAll => (),
+                    return self.set_mirror_group(self.active_display, targets);
+                }
             },
 
-            // Message::NightLight(night_light) => {}
-            //
-            // Message::NightLightContext => {
-            //     self.context = Some(ContextDrawer::NightLight);
-            //     return cosmic::command::message(app::Message::OpenContextDrawer(
-            //         text::NIGHT_LIGHT.clone().into(),
-            //     ));
-            // }
+            Message::MirrorFit(option) => return self.set_mirror_fit(option),
+
+            Message::MirrorTarget(target, mirror) => {
+                return self.set_mirror_target(target, mirror);
+            }
+
+            Message::NightLight(night_light) => return self.update_night_light(night_light),
+
+            Message::NightLightContext => {
+                self.context = Some(ContextDrawer::NightLight);
+                return cosmic::command::message(app::Message::OpenContextDrawer(
+                    fl!("night-light").into(),
+                ));
+            }
+
+            Message::ModeCustom(field, value) => {
+                if let Some(mode) = self.custom_mode.as_mut() {
+                    mode.invalid = false;
+
+                    match field {
+                        ModeField::Width => mode.width = value,
+                        ModeField::Height => mode.height = value,
+                        ModeField::RefreshRate => mode.refresh_rate = value,
+                    }
+                }
+
+                return Command::none();
+            }
+
+            Message::ModeCustomSubmit => {
+                let Some(custom_mode) = self.custom_mode.as_mut() else {
+                    return Command::none();
+                };
+
+                let Some((width, height, refresh_rate)) = custom_mode.parse() else {
+                    custom_mode.invalid = true;
+                    return Command::none();
+                };
+
+                return self.set_mode(width, height, refresh_rate);
+            }
+
+            Message::ModeCustomToggle => {
+                self.custom_mode = match self.custom_mode.take() {
+                    Some(_) => None,
+                    None => Some(self.current_mode()),
+                };
+
+                return Command::none();
+            }
+
+            Message::OutputAdded(name, output) => {
+                self.wl_outputs.retain(|(n, _)| *n != name);
+                self.wl_outputs.push((name, output));
+                return Command::none();
+            }
+
+            Message::OutputRemoved(output) => {
+                self.wl_outputs.retain(|(_, o)| *o != output);
+                return Command::none();
+            }
+
             Message::Orientation(orientation) => return self.set_orientation(orientation),
 
             Message::Pan(pan) => {
@@ -452,7 +702,38 @@ impl Page {
 
             Message::Scale(scale) => return self.set_scale(scale),
 
-            Message::Update { randr } => {
+            Message::ShowAllModes(show) => {
+                self.show_all_modes = show;
+                self.set_display(self.display_tabs.active());
+                return Command::none();
+            }
+
+            Message::ScaleCustom(input) => {
+                self.scale_custom = Some(input);
+                self.scale_custom_invalid = false;
+                return Command::none();
+            }
+
+            Message::ScaleCustomSubmit => {
+                let scale = self
+                    .scale_custom
+                    .as_deref()
+                    .and_then(|input| input.trim().trim_end_matches('%').trim_end().parse().ok())
+                    .filter(|scale| {
+                        CUSTOM_SCALES.contains(scale) && scale % CUSTOM_SCALE_STEP == 0
+                    });
+
+                let Some(scale) = scale else {
+                    self.scale_custom_invalid = true;
+                    return Command::none();
+                };
+
+                return self.apply_scale(scale);
+            }
+
+            Message::Update { randr, heads } => {
+                self.heads = heads;
+
                 match Arc::into_inner(randr) {
                     Some(Ok(outputs)) => self.update_displays(outputs),
 
@@ -479,11 +760,6 @@ impl Page {
         )
     }
 
-    /// Displays the night light context drawer.
-    // pub fn night_light_context_view(&self) -> Element<pages::Message> {
-    //     column().into()
-    // }
-
     /// Reloads the display list, and all information relevant to the active display.
     pub fn update_displays(&mut self, list: List) {
         let active_display_name = self
@@ -518,7 +794,12 @@ impl Page {
                 }
             }
 
-            let text = crate::utils::display_name(&output.name, output.physical);
+            let text = fl!(
+                "display",
+                "numbered",
+                number = pos + 1,
+                name = crate::utils::display_name(&output.name, output.physical)
+            );
 
             if text == active_display_name {
                 active_tab_pos = pos as u16;
@@ -533,32 +814,70 @@ impl Page {
         self.set_display(self.display_tabs.active());
     }
 
-    /// Sets the dialog to be shown to the user. Will not show a dialog if the
-    /// current request does not change anything.
-    fn set_dialog(
-        &mut self,
-        revert_request: Randr,
-        current_request: &Randr,
-    ) -> Command<app::Message> {
-        if revert_request == *current_request {
+    /// Shows an overlay on every enabled display with its number from the arrangement.
+    fn identify(&mut self) -> Command<app::Message> {
+        if !self.identify_overlays.is_empty() {
             return Command::none();
         }
-        self.dialog = Some(revert_request);
-        self.dialog_countdown = 10;
-        command::future(async {
-            tokio::time::sleep(time::Duration::from_secs(1)).await;
-            app::Message::from(Message::DialogCountdown)
-        })
+
+        let mut commands = Vec::new();
+
+        for &key in self
+            .display_tabs
+            .iter()
+            .filter_map(|id| self.display_tabs.data::<OutputKey>(id))
+        {
+            let Some(output) = self.list.outputs.get(key).filter(|output| output.enabled) else {
+                continue;
+            };
+
+            let Some((_, wl_output)) = self.wl_outputs.iter().find(|(n, _)| *n == output.name)
+            else {
+                continue;
+            };
+
+            let Some(number) = arrangement::display_number(&self.display_tabs, key) else {
+                continue;
+            };
+
+            let overlay = identify::Overlay::new(number, output.name.clone());
+            commands.push(overlay.show(wl_output.clone()));
+            self.identify_overlays.push(overlay);
+        }
+
+        commands.push(command::future(async {
+            tokio::time::sleep(identify::DURATION).await;
+            app::Message::from(Message::IdentifyHide)
+        }));
+
+        Command::batch(commands)
     }
 
-    /// Changes the color depth of the active display.
-    pub fn set_color_depth(&mut self, _depth: ColorDepth) -> Command<app::Message> {
-        unimplemented!()
+    /// The view of an overlay identifying a display.
+    pub fn identify_view(&self, id: cosmic::iced::window::Id) -> Option<Element<app::Message>> {
+        self.identify_overlays
+            .iter()
+            .find(|overlay| overlay.id == id)
+            .map(identify::Overlay::view)
     }
 
-    /// Changes the color profile of the active display.
-    pub fn set_color_profile(&mut self, _profile: usize) -> Command<app::Message> {
-        unimplemented!()
+    /// Asks for a change of the active display to be confirmed, applying `revert_request`
+    /// if it is not. Will not ask if the current request does not change anything.
+    fn set_dialog(&self, revert_request: Randr, current_request: &Randr) -> Command<app::Message> {
+        if revert_request == *current_request {
+            return Command::none();
+        }
+
+        let Some(request) = self
+            .list
+            .outputs
+            .get(self.active_display)
+            .and_then(|output| self.randr_request(output, revert_request))
+        else {
+            return Command::none();
+        };
+
+        confirm_layout(request)
     }
 
     /// Changes the active display, and regenerates available options for it.
@@ -575,7 +894,7 @@ impl Page {
         self.active_display = output_id;
         self.config.refresh_rate = None;
         self.config.resolution = None;
-        self.config.scale = (output.scale * 100.0) as u32;
+        self.config.scale = (output.scale * 100.0).round() as u32;
 
         self.cache.modes.clear();
         self.cache.refresh_rates.clear();
@@ -590,19 +909,21 @@ impl Page {
         self.cache.resolution_selected = None;
         self.cache.refresh_rate_selected = None;
 
-        self.cache.scale_selected = Some(
-            DPI_SCALES
-                .iter()
-                .position(|scale| self.config.scale <= *scale)
-                .unwrap_or(DPI_SCALES.len() - 1),
-        );
-
         if let Some(current_mode_id) = output.current {
             for (mode_id, mode) in output
                 .modes
                 .iter()
                 .filter_map(|&id| self.list.modes.get(id).map(|m| (id, m)))
             {
+                // Interlaced modes are not distinguished by output management, so modes are
+                // only filtered by their refresh rate.
+                if !self.show_all_modes
+                    && mode.refresh_rate < MIN_REFRESH_RATE
+                    && mode_id != current_mode_id
+                {
+                    continue;
+                }
+
                 let refresh_rates = self.cache.modes.entry(mode.size).or_default();
 
                 refresh_rates.push(mode.refresh_rate);
@@ -621,7 +942,7 @@ impl Page {
                 .resolutions
                 .push(format!("{}x{}", resolution.0, resolution.1));
             if Some(resolution) == self.config.resolution {
-                cache_rates(&mut self.cache.refresh_rates, rates);
+                cache_refresh_rates(&mut self.cache.refresh_rates, rates, self.show_all_modes);
             }
         }
 
@@ -632,8 +953,13 @@ impl Page {
             vec![(fl!("mirroring", "dont"), Mirroring::Disable)],
         ));
 
-        self.mirror_menu.insert(widget::dropdown::multi::list(
-            None,
+        let mut project = Vec::with_capacity(self.list.outputs.len());
+
+        if self.list.outputs.len() > 2 {
+            project.push((fl!("mirroring", "project-all"), Mirroring::ProjectToAll));
+        }
+
+        project.extend(
             self.list
                 .outputs
                 .iter()
@@ -643,9 +969,11 @@ impl Page {
                         fl!("mirroring", "project", display = other_output.name.as_str()),
                         Mirroring::Project(other_id),
                     )
-                })
-                .collect::<Vec<_>>(),
-        ));
+                }),
+        );
+
+        self.mirror_menu
+            .insert(widget::dropdown::multi::list(None, project));
 
         self.mirror_menu.insert(widget::dropdown::multi::list(
             None,
@@ -670,17 +998,87 @@ impl Page {
         self.show_display_options = self.mirror_menu.selected.is_none();
 
         if self.mirror_menu.selected.is_none() {
-            self.mirror_menu.selected = self
-                .mirror_map
-                .iter()
-                .find(|&(_, mirrored_id)| *mirrored_id == output_id)
-                .map(|(projected_id, _)| Mirroring::Project(projected_id));
+            let targets = mirroring::targets(&self.mirror_map, output_id);
+
+            self.mirror_menu.selected = match targets.as_slice() {
+                [] => None,
+                [target] => Some(Mirroring::Project(*target)),
+                [target, ..] if targets.len() + 1 < self.list.outputs.len() => {
+                    Some(Mirroring::Project(*target))
+                }
+                _ => Some(Mirroring::ProjectToAll),
+            };
         }
 
         if self.mirror_menu.selected.is_none() {
             // If mirror menu is not set, set it to don't mirror.
             self.mirror_menu.selected = Some(Mirroring::Disable);
         }
+
+        self.cache_scale();
+
+        // Read from output management, which reflects what the compositor applied.
+        self.cache.adaptive_sync_selected = self
+            .list
+            .outputs
+            .get(output_id)
+            .and_then(|output| self.heads.get(&output.name))
+            .and_then(|head| head.adaptive_sync)
+            .map(|mode| mode.position());
+
+        if self.custom_mode.is_some() {
+            self.custom_mode = Some(self.current_mode());
+        }
+    }
+
+    /// The current mode of the active display, as a custom mode.
+    fn current_mode(&self) -> CustomMode {
+        let ((width, height), refresh_rate) = self
+            .config
+            .resolution
+            .zip(self.config.refresh_rate)
+            .unwrap_or_default();
+
+        CustomMode {
+            width: width.to_string(),
+            height: height.to_string(),
+            refresh_rate: format!("{}.{:03}", refresh_rate / 1000, refresh_rate % 1000),
+            invalid: false,
+        }
+    }
+
+    /// Changes the adaptive sync mode of the active display.
+    pub fn set_adaptive_sync(&mut self, mode: AdaptiveSync) -> Command<app::Message> {
+        let Some(output) = self.list.outputs.get(self.active_display) else {
+            return Command::none();
+        };
+
+        self.cache.adaptive_sync_selected = Some(mode.position());
+        self.exec_randr(output, Randr::AdaptiveSync(mode))
+    }
+
+    /// Sets a custom mode, which may not be one of the modes advertised by the display.
+    pub fn set_mode(
+        &mut self,
+        width: u32,
+        height: u32,
+        refresh_rate: u32,
+    ) -> Command<app::Message> {
+        let Some(output) = self.list.outputs.get(self.active_display) else {
+            return Command::none();
+        };
+
+        let request = Randr::Mode(width, height, refresh_rate);
+
+        let revert_request = match self.config.resolution.zip(self.config.refresh_rate) {
+            Some(((width, height), rate)) => Randr::Mode(width, height, rate),
+            None => request,
+        };
+
+        Command::batch(vec![
+            self.exec_randr(output, request),
+            self.set_dialog(revert_request, &request),
+        ])
     }
 
     /// Change display orientation.
@@ -723,7 +1121,13 @@ impl Page {
             return Command::none();
         };
 
-        output.position = (x, y);
+        let previous = std::mem::replace(&mut output.position, (x, y));
+
+        if let Err(why) = arrangement::validate(&self.list) {
+            tracing::error!(?why, "refusing to move display to {x},{y}");
+            self.list.outputs[display].position = previous;
+            return Command::none();
+        }
 
         if cfg!(feature = "test") {
             tracing::debug!("set position {x},{y}");
@@ -765,8 +1169,7 @@ impl Page {
             return Command::none();
         };
 
-        self.cache.refresh_rates.clear();
-        cache_rates(&mut self.cache.refresh_rates, rates);
+        cache_refresh_rates(&mut self.cache.refresh_rates, rates, self.show_all_modes);
 
         let Some(&rate) = rates.first() else {
             return Command::none();
@@ -788,26 +1191,91 @@ impl Page {
         Command::batch(commands)
     }
 
-    /// Set the scale of the active display.
+    /// Caches the scale options of the active display, marking the recommended scale.
+    fn cache_scale(&mut self) {
+        let recommended = self
+            .list
+            .outputs
+            .get(self.active_display)
+            .zip(self.config.resolution)
+            .and_then(|(output, resolution)| {
+                crate::utils::recommended_scale(&output.name, output.physical, resolution)
+            });
+
+        self.cache.scales = DPI_SCALES
+            .iter()
+            .map(|&scale| {
+                if Some(scale) == recommended {
+                    fl!("display", "scale-recommended", scale = scale)
+                } else {
+                    format!("{scale}%")
+                }
+            })
+            .chain(std::iter::once(fl!("display", "scale-custom")))
+            .collect();
+
+        let selected = DPI_SCALES
+            .iter()
+            .position(|&scale| scale == self.config.scale);
+
+        self.cache.scale_selected = Some(selected.unwrap_or(DPI_SCALES.len()));
+        self.scale_custom = selected.is_none().then(|| self.config.scale.to_string());
+        self.scale_custom_invalid = false;
+    }
+
+    /// Set the scale of the active display, where the last option is a custom scale.
     pub fn set_scale(&mut self, option: usize) -> Command<app::Message> {
+        match DPI_SCALES.get(option) {
+            Some(&scale) => self.apply_scale(scale),
+
+            None => {
+                self.cache.scale_selected = Some(DPI_SCALES.len());
+                self.scale_custom = Some(self.config.scale.to_string());
+                Command::none()
+            }
+        }
+    }
+
+    /// Applies a scale, in percent, to the active display.
+    fn apply_scale(&mut self, scale: u32) -> Command<app::Message> {
         let mut commands = Vec::with_capacity(2);
 
         let Some(output) = self.list.outputs.get(self.active_display) else {
             return Command::none();
         };
 
-        let scale = (option * 25 + 50) as u32;
-
         let request = Randr::Scale(scale);
         let revert_request = Randr::Scale(self.config.scale);
 
-        self.cache.scale_selected = Some(option);
         self.config.scale = scale;
         commands.push(self.exec_randr(output, Randr::Scale(scale)));
         commands.push(self.set_dialog(revert_request, &request));
+        self.cache_scale();
         Command::batch(commands)
     }
 
+    /// Changes the brightness of the built-in panel.
+    pub fn set_brightness(&mut self, percent: u32) {
+        let Some(backlight) = self.backlight.as_mut() else {
+            return;
+        };
+
+        let brightness = backlight.brightness_from_percent(percent);
+
+        if brightness == backlight.brightness {
+            return;
+        }
+
+        backlight.brightness = brightness;
+        let name = backlight.name.clone();
+
+        tokio::task::spawn(async move {
+            if let Err(why) = brightness::set_brightness(&name, brightness).await {
+                tracing::error!(?why, "failed to set brightness of backlight {name}");
+            }
+        });
+    }
+
     /// Enables or disables the active display.
     pub fn toggle_display(&mut self, enable: bool) -> Command<app::Message> {
         let mut commands = Vec::with_capacity(2);
@@ -828,133 +1296,94 @@ impl Page {
         Command::batch(commands)
     }
 
-    /// Applies a display configuration via `cosmic-randr`.
+    /// Applies a display configuration through the compositor's output management.
     fn exec_randr(&self, output: &Output, request: Randr) -> Command<app::Message> {
-        let mut commands = Vec::with_capacity(2);
-
-        // Removes the dialog if no change is being made
-        if Some(request) == self.dialog {
-            commands.push(command::message(app::Message::from(
-                Message::DialogComplete,
-            )));
+        match self.randr_request(output, request) {
+            Some(request) => apply_randr(request),
+            None => Command::none(),
         }
+    }
 
+    /// Converts a setting of an output into a request for the compositor.
+    fn randr_request(&self, output: &Output, request: Randr) -> Option<randr::Request> {
         let name = &*output.name;
-        let mut command = tokio::process::Command::new("cosmic-randr");
 
-        match request {
-            Randr::Mirror(from_id) => {
-                let Some(from_output) = self.list.outputs.get(from_id) else {
-                    return Command::none();
-                };
+        let request = match request {
+            Randr::AdaptiveSync(mode) => randr::Request::new().adaptive_sync(name, mode),
 
-                command
-                    .arg("mirror")
-                    .arg(&output.name)
-                    .arg(&from_output.name);
-            }
-
-            Randr::Position(x, y) => {
-                let Some(current) = output.current.and_then(|id| self.list.modes.get(id)) else {
-                    return Command::none();
-                };
+            Randr::Mode(width, height, rate) => randr::Request::new()
+                .resolution(name, width, height)
+                .refresh_rate(name, rate),
 
-                command
-                    .arg("mode")
-                    .arg("--pos-x")
-                    .arg(itoa::Buffer::new().format(x))
-                    .arg("--pos-y")
-                    .arg(itoa::Buffer::new().format(y))
-                    .arg(name)
-                    .arg(itoa::Buffer::new().format(current.size.0))
-                    .arg(itoa::Buffer::new().format(current.size.1));
-            }
+            Randr::Position(x, y) => randr::Request::new().position(name, x, y),
 
             Randr::RefreshRate(rate) => {
-                let Some(current) = output.current.and_then(|id| self.list.modes.get(id)) else {
-                    return Command::none();
-                };
+                let current = output.current.and_then(|id| self.list.modes.get(id))?;
 
-                command
-                    .arg("mode")
-                    .arg("--refresh")
-                    .arg(
-                        &[
-                            itoa::Buffer::new().format(rate / 1000),
-                            ".",
-                            itoa::Buffer::new().format(rate % 1000),
-                        ]
-                        .concat(),
-                    )
-                    .arg(name)
-                    .arg(itoa::Buffer::new().format(current.size.0))
-                    .arg(itoa::Buffer::new().format(current.size.1));
+                randr::Request::new()
+                    .resolution(name, current.size.0, current.size.1)
+                    .refresh_rate(name, rate)
             }
 
             Randr::Resolution(width, height) => {
-                command
-                    .arg("mode")
-                    .arg(name)
-                    .arg(itoa::Buffer::new().format(width))
-                    .arg(itoa::Buffer::new().format(height));
+                let request = randr::Request::new().resolution(name, width, height);
+
+                // Select the preferred refresh rate for the new resolution.
+                match self
+                    .cache
+                    .modes
+                    .get(&(width, height))
+                    .and_then(|r| r.first())
+                {
+                    Some(&rate) => request.refresh_rate(name, rate),
+                    None => request,
+                }
             }
 
-            Randr::Scale(scale) => {
-                let Some(current) = output.current.and_then(|id| self.list.modes.get(id)) else {
-                    return Command::none();
-                };
-
-                command
-                    .arg("mode")
-                    .arg("--scale")
-                    .arg(
-                        &[
-                            itoa::Buffer::new().format(scale / 100),
-                            ".",
-                            itoa::Buffer::new().format(scale % 100),
-                        ]
-                        .concat(),
-                    )
-                    .arg(name)
-                    .arg(itoa::Buffer::new().format(current.size.0))
-                    .arg(itoa::Buffer::new().format(current.size.1));
-            }
+            Randr::Scale(scale) => randr::Request::new().scale(name, f64::from(scale) / 100.0),
 
             Randr::Toggle(enable) => {
-                command
-                    .arg(if enable { "enable" } else { "disable" })
-                    .arg(name);
+                if enable {
+                    randr::Request::new().enable(name)
+                } else {
+                    randr::Request::new().disable(name)
+                }
             }
 
-            Randr::Transform(transform) => {
-                let Some(current) = output.current.and_then(|id| self.list.modes.get(id)) else {
-                    return Command::none();
-                };
+            Randr::Transform(transform) => randr::Request::new().transform(name, transform),
+        };
 
-                command
-                    .arg("mode")
-                    .arg("--transform")
-                    .arg(&*format!("{transform}"))
-                    .arg(name)
-                    .arg(itoa::Buffer::new().format(current.size.0))
-                    .arg(itoa::Buffer::new().format(current.size.1));
-            }
-        }
+        Some(request)
+    }
 
-        commands.push(cosmic::command::future(async move {
-            tracing::debug!(?command, "executing");
-            app::Message::from(Message::RandrResult(Arc::new(command.status().await)))
-        }));
-        Command::batch(commands)
+    fn save_profiles(&self) {
+        if let Some(config) = self.settings_config.as_ref() {
+            profiles::save(config, &self.profiles);
+        }
     }
 }
 
+/// Applies a batch of display changes in a single output configuration.
+fn apply_randr(request: randr::Request) -> Command<app::Message> {
+    cosmic::command::future(async move {
+        app::Message::from(Message::RandrResult(Arc::new(request.apply().await)))
+    })
+}
+
+/// Asks for a display change to be confirmed, applying `revert_request` if it is not.
+fn confirm_layout(revert_request: randr::Request) -> Command<app::Message> {
+    crate::revert::Revert::new(fl!("dialog", "title"))
+        .command(move || apply_randr(revert_request.clone()))
+        .confirm()
+}
+
 /// View for the display arrangement section.
 pub fn display_arrangement() -> Section<crate::pages::Message> {
     let mut descriptions = Slab::new();
 
     _ = descriptions.insert(fl!("display", "arrangement"));
     let display_arrangement_desc = descriptions.insert(fl!("display", "arrangement-desc"));
+    let identify_label = descriptions.insert(fl!("display", "identify"));
 
     Section::default()
         .title(fl!("display", "arrangement"))
@@ -971,7 +1400,16 @@ pub fn display_arrangement() -> Section<crate::pages::Message> {
                     theme.cosmic().space_m(),
                 ]))
                 .spacing(theme.cosmic().space_xs())
-                .push(widget::text::body(&descriptions[display_arrangement_desc]))
+                .push(
+                    widget::row()
+                        .align_items(Alignment::Center)
+                        .push(widget::text::body(&descriptions[display_arrangement_desc]))
+                        .push(widget::horizontal_space(Length::Fill))
+                        .push(
+                            widget::button::standard(&descriptions[identify_label])
+                                .on_press(pages::Message::Displays(Message::Identify)),
+                        ),
+                )
                 .push({
                     Arrangement::new(&page.list, &page.display_tabs)
                         .on_select(|id| pages::Message::Displays(Message::Display(id)))
@@ -998,8 +1436,20 @@ pub fn display_configuration() -> Section<crate::pages::Message> {
 
     let _display = descriptions.insert(fl!("display"));
     let refresh_rate = descriptions.insert(fl!("display", "refresh-rate"));
+    let brightness_label = descriptions.insert(fl!("display", "brightness"));
     let resolution = descriptions.insert(fl!("display", "resolution"));
     let scale = descriptions.insert(fl!("display", "scale"));
+    let scale_custom = descriptions.insert(fl!("display", "scale-custom-label"));
+    let scale_invalid = descriptions.insert(fl!("display", "scale-custom-invalid"));
+    let apply_label = descriptions.insert(fl!("display", "apply"));
+    let adaptive_sync_label = descriptions.insert(fl!("adaptive-sync"));
+    let show_all_modes = descriptions.insert(fl!("display", "show-all-modes"));
+    let custom_mode = descriptions.insert(fl!("custom-mode"));
+    let custom_mode_desc = descriptions.insert(fl!("custom-mode", "desc"));
+    let custom_mode_invalid = descriptions.insert(fl!("custom-mode", "invalid"));
+    let custom_mode_width = descriptions.insert(fl!("custom-mode", "width"));
+    let custom_mode_height = descriptions.insert(fl!("custom-mode", "height"));
+    let custom_mode_rate = descriptions.insert(fl!("custom-mode", "refresh-rate"));
     let orientation = descriptions.insert(fl!("orientation"));
     let enable_label = descriptions.insert(fl!("display", "enable"));
     let options_label = descriptions.insert(fl!("display", "options"));
@@ -1017,29 +1467,93 @@ pub fn display_configuration() -> Section<crate::pages::Message> {
 
             let active_output = &page.list.outputs[active_id];
 
-            let display_options = (page.show_display_options && active_output.enabled).then(|| {
-                list_column()
-                    .add(widget::settings::item(
-                        &descriptions[resolution],
-                        dropdown(
-                            &page.cache.resolutions,
-                            page.cache.resolution_selected,
-                            Message::Resolution,
-                        ),
-                    ))
-                    .add(widget::settings::item(
-                        &descriptions[refresh_rate],
-                        dropdown(
-                            &page.cache.refresh_rates,
-                            page.cache.refresh_rate_selected,
-                            Message::RefreshRate,
-                        ),
-                    ))
-                    .add(widget::settings::item(
-                        &descriptions[scale],
-                        dropdown(&DPI_SCALE_LABELS, page.cache.scale_selected, Message::Scale),
-                    ))
-                    .add(widget::settings::item(
+            let display_options =
+                (page.show_display_options && active_output.enabled).then(|| {
+                    let mut options = list_column();
+
+                    // Only the built-in panel is controlled by the backlight.
+                    if let Some(backlight) = page
+                        .backlight
+                        .as_ref()
+                        .filter(|_| crate::utils::is_laptop_display(&active_output.name))
+                    {
+                        let slider = widget::row()
+                            .align_items(Alignment::Center)
+                            .spacing(8)
+                            .push(widget::slider(
+                                brightness::MIN_PERCENT..=100,
+                                backlight.percent(),
+                                Message::Brightness,
+                            ))
+                            .push(widget::text::body(format!("{}%", backlight.percent())));
+
+                        options = options.add(widget::settings::item(
+                            &descriptions[brightness_label],
+                            slider,
+                        ));
+                    }
+
+                    options = options
+                        .add(widget::settings::item(
+                            &descriptions[resolution],
+                            dropdown(
+                                &page.cache.resolutions,
+                                page.cache.resolution_selected,
+                                Message::Resolution,
+                            ),
+                        ))
+                        .add(widget::settings::item(
+                            &descriptions[refresh_rate],
+                            dropdown(
+                                &page.cache.refresh_rates,
+                                page.cache.refresh_rate_selected,
+                                Message::RefreshRate,
+                            ),
+                        ))
+                        .add(widget::settings::item(
+                            &descriptions[adaptive_sync_label],
+                            dropdown(
+                                &ADAPTIVE_SYNC_LABELS,
+                                page.cache.adaptive_sync_selected,
+                                Message::AdaptiveSync,
+                            ),
+                        ))
+                        .add(widget::settings::item(
+                            &descriptions[scale],
+                            dropdown(
+                                &page.cache.scales,
+                                page.cache.scale_selected,
+                                Message::Scale,
+                            ),
+                        ));
+
+                    if let Some(input) = page.scale_custom.as_ref() {
+                        let control = widget::row()
+                            .align_items(Alignment::Center)
+                            .spacing(8)
+                            .push(
+                                widget::text_input("", input)
+                                    .on_input(Message::ScaleCustom)
+                                    .on_submit(Message::ScaleCustomSubmit)
+                                    .width(Length::Fixed(64.0)),
+                            )
+                            .push(widget::text::body("%"))
+                            .push(
+                                widget::button::standard(&descriptions[apply_label])
+                                    .on_press(Message::ScaleCustomSubmit),
+                            );
+
+                        let item = widget::settings::item::builder(&descriptions[scale_custom]);
+
+                        options = options.add(if page.scale_custom_invalid {
+                            item.description(&descriptions[scale_invalid])
+                                .control(control)
+                        } else {
+                            item.control(control)
+                        });
+                    }
+
+                    options = options.add(widget::settings::item(
                         &descriptions[orientation],
                         dropdown(
                             &page.cache.orientations,
@@ -1053,10 +1567,81 @@ pub fn display_configuration() -> Section<crate::pages::Message> {
                                 })
                             },
                         ),
-                    ))
-            });
+                    ));
+
+                    options = options
+                        .add(widget::settings::item(
+                            &descriptions[show_all_modes],
+                            toggler(None, page.show_all_modes, Message::ShowAllModes),
+                        ))
+                        .add(
+                            widget::settings::item::builder(&descriptions[custom_mode])
+                                .description(&descriptions[custom_mode_desc])
+                                .control(
+                                    widget::button::icon(widget::icon::from_name(
+                                        if page.custom_mode.is_some() {
+                                            "go-up-symbolic"
+                                        } else {
+                                            "go-down-symbolic"
+                                        },
+                                    ))
+                                    .on_press(Message::ModeCustomToggle),
+                                ),
+                        );
+
+                    if let Some(mode) = page.custom_mode.as_ref() {
+                        let input = |placeholder: &str, value: &str, field: ModeField| {
+                            widget::text_input(placeholder.to_owned(), value.to_owned())
+                                .on_input(move |value| Message::ModeCustom(field, value))
+                                .on_submit(Message::ModeCustomSubmit)
+                                .width(Length::Fixed(96.0))
+                        };
+
+                        let entry = widget::settings::item_row(vec![
+                            input(
+                                &descriptions[custom_mode_width],
+                                &mode.width,
+                                ModeField::Width,
+                            )
+                            .into(),
+                            widget::text::body("×").into(),
+                            input(
+                                &descriptions[custom_mode_height],
+                                &mode.height,
+                                ModeField::Height,
+                            )
+                            .into(),
+                            widget::text::body("@").into(),
+                            input(
+                                &descriptions[custom_mode_rate],
+                                &mode.refresh_rate,
+                                ModeField::RefreshRate,
+                            )
+                            .into(),
+                            widget::text::body("Hz").into(),
+                            widget::horizontal_space(Length::Fill).into(),
+                            widget::button::standard(&descriptions[apply_label])
+                                .on_press(Message::ModeCustomSubmit)
+                                .into(),
+                        ]);
+
+                        options = options.add(entry);
+
+                        if mode.invalid {
+                            options = options.add(widget::settings::item_row(vec![
+                                widget::text::caption(&descriptions[custom_mode_invalid]).into(),
+                            ]));
+                        }
+                    }
 
-            let mut content = column().spacing(theme.cosmic().space_m());
+                    options
+                });
+
+            let mut content = column().spacing(theme.cosmic().space_m()).push_maybe(
+                page.randr_error
+                    .as_ref()
+                    .map(|why| widget::text::body(why.to_string())),
+            );
 
             if page.list.outputs.len() > 1 {
                 let display_switcher = tab_bar::horizontal(&page.display_tabs)
@@ -1098,6 +1683,96 @@ pub fn display_configuration() -> Section<crate::pages::Message> {
         })
 }
 
+/// View for saving and restoring display profiles.
+pub fn display_profiles() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let profiles_desc = descriptions.insert(fl!("display-profiles", "desc"));
+    let name_placeholder = descriptions.insert(fl!("display-profiles", "name"));
+    let save_label = descriptions.insert(fl!("display-profiles", "save"));
+    let apply_label = descriptions.insert(fl!("display-profiles", "apply"));
+    let rename_label = descriptions.insert(fl!("display-profiles", "rename"));
+    let matches_label = descriptions.insert(fl!("display-profiles", "connected"));
+
+    Section::default()
+        .title(fl!("display-profiles"))
+        .descriptions(descriptions)
+        .view::<Page>(move |_binder, page, section| {
+            let descriptions = &section.descriptions;
+            let connected = profiles::monitor_set(&page.list, &page.heads);
+
+            let name_input = || {
+                widget::text_input(&descriptions[name_placeholder], &page.profile_name)
+                    .on_input(Message::ProfileNameInput)
+                    .on_submit(Message::ProfileSave)
+            };
+
+            let profiles = page.profiles.iter().enumerate().map(|(id, profile)| {
+                let matches = profile.matches(&connected);
+
+                if page.profile_renaming == Some(id) {
+                    return widget::settings::item_row(vec![
+                        name_input().into(),
+                        widget::button::standard(&descriptions[save_label])
+                            .on_press(Message::ProfileSave)
+                            .into(),
+                    ]);
+                }
+
+                let title = column().push(widget::text::body(&profile.name)).push_maybe(
+                    matches.then(|| widget::text::caption(&descriptions[matches_label])),
+                );
+
+                widget::settings::item_row(vec![
+                    title.into(),
+                    widget::horizontal_space(Length::Fill).into(),
+                    widget::button::standard(&descriptions[apply_label])
+                        .on_press_maybe(matches.then_some(Message::ProfileApply(id)))
+                        .into(),
+                    widget::button::standard(&descriptions[rename_label])
+                        .on_press(Message::ProfileRename(id))
+                        .into(),
+                    widget::button::icon(widget::icon::from_name("edit-delete-symbolic"))
+                        .on_press(Message::ProfileDelete(id))
+                        .into(),
+                ])
+            });
+
+            let mut section =
+                widget::settings::view_section(&section.title).add(widget::settings::item_row(
+                    vec![widget::text::caption(&descriptions[profiles_desc]).into()],
+                ));
+
+            section = profiles.fold(section, widget::settings::Section::add);
+
+            if page.profile_renaming.is_none() {
+                section = section.add(widget::settings::item_row(vec![
+                    name_input().into(),
+                    widget::button::standard(&descriptions[save_label])
+                        .on_press(Message::ProfileSave)
+                        .into(),
+                ]));
+            }
+
+            section.apply(Element::from).map(pages::Message::Displays)
+        })
+}
+
+/// Caches refresh rate labels, which are rounded unless `exact` rates are requested.
+fn cache_refresh_rates(cached_rates: &mut Vec<String>, rates: &[u32], exact: bool) {
+    if !exact {
+        cache_rates(cached_rates, rates);
+        return;
+    }
+
+    cached_rates.clear();
+    cached_rates.extend(
+        rates
+            .iter()
+            .map(|rate| format!("{}.{:03} Hz", rate / 1000, rate % 1000)),
+    );
+}
+
 fn cache_rates(cached_rates: &mut Vec<String>, rates: &[u32]) {
     cached_rates.clear();
 
@@ -1145,10 +1820,16 @@ fn cache_rates(cached_rates: &mut Vec<String>, rates: &[u32]) {
 }
 
 pub async fn on_enter() -> crate::pages::Message {
-    let randr_fut = cosmic_randr_shell::list();
+    let (list, heads) = futures::join!(cosmic_randr_shell::list(), randr::heads());
+
+    let heads = heads.unwrap_or_else(|why| {
+        tracing::error!(?why, "error fetching output state");
+        BTreeMap::new()
+    });
 
     crate::pages::Message::Displays(Message::Update {
-        randr: Arc::new(randr_fut.await),
+        randr: Arc::new(list),
+        heads,
     })
 }
 
@@ -1,73 +1,348 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
-use super::{Message, NightLight};
-use crate::pages;
-use cosmic::iced_core::{Alignment, Length, Padding};
+//! Night light preferences, and the schedule which warms the colors of every output.
+//!
+//! Settings applies the temperature itself through [`super::gamma`], so night light is only
+//! active while settings is running until a session service takes over the schedule. The gamma
+//! control thread is only started once night light is first shown.
+
+use std::any::TypeId;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use super::{gamma, Message, NightLight};
+use crate::{app, pages};
+use chrono::Timelike;
+use cosmic::cosmic_config::{self, ConfigGet, ConfigSet};
+use cosmic::iced::{self, Alignment};
 use cosmic::prelude::CollectionWidget;
-use cosmic::widget::{button, column, icon, list_column, row, toggler};
-use cosmic::{Apply, Command, Element};
-use std::sync::Arc;
-
-pub fn view(
-    mode: &'static str,
-    description: &'static str,
-    button: Option<(&'static str, Message)>,
-) -> Element<'static, Message> {
-    let theme = cosmic::theme::active();
-    let theme = theme.cosmic();
-    let has_checkmark = button.is_none();
-
-    let content = column::with_capacity(3)
-        .padding(Padding::from([theme.space_xxs(), theme.space_l()]))
-        .push(cosmic::widget::text::body(mode))
-        .push(cosmic::widget::text::caption(description))
-        .push(cosmic::widget::Space::new(Length::Fill, 12))
-        .push_maybe(button.map(|(text, message)| {
-            button::text(text)
-                .style(cosmic::theme::Button::Link)
-                .trailing_icon(icon::from_name("go-next-symbolic").size(16))
-                .padding(0)
-                .on_press(message)
-        }));
-
-    if has_checkmark {
-        row::with_capacity(2)
-            .align_items(Alignment::Center)
-            .push(content)
-            .push(icon::from_name("object-select-symbolic").size(24))
-            .apply(Element::from)
-            .apply(cosmic::widget::list::container)
-            .into()
-    } else {
-        cosmic::widget::list::container(content).into()
+use cosmic::widget::{self, button, column, icon, row, settings, toggler};
+use cosmic::{command, Apply, Command, Element};
+use cosmic_settings_page::Section;
+use once_cell::sync::Lazy;
+use serde::{de::DeserializeOwned, Serialize};
+use slab::Slab;
+
+pub const NAME: &str = "com.system76.CosmicNightLight";
+
+/// Supported color temperatures, in Kelvin.
+pub const TEMPERATURES: RangeInclusive<u32> = 1500..=6500;
+const TEMPERATURE_STEP: u32 = 100;
+
+/// Minutes between each time offered for the manual schedule.
+const SCHEDULE_STEP: u32 = 30;
+
+static SCHEDULE_LABELS: Lazy<Vec<String>> = Lazy::new(|| {
+    (0..24 * 60)
+        .step_by(SCHEDULE_STEP as usize)
+        .map(|minute| format!("{:02}:{:02}", minute / 60, minute % 60))
+        .collect()
+});
+
+const ENABLED: &str = "enabled";
+const TEMPERATURE: &str = "temperature";
+const AUTOMATIC: &str = "automatic";
+const SCHEDULE_FROM: &str = "schedule_from";
+const SCHEDULE_TO: &str = "schedule_to";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    pub enabled: bool,
+    /// Color temperature in Kelvin.
+    pub temperature: u32,
+    /// Follow the local sunset and sunrise instead of the manual schedule.
+    pub automatic: bool,
+    /// Start of the manual schedule, in minutes after midnight.
+    pub from: u32,
+    /// End of the manual schedule, in minutes after midnight.
+    pub to: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            temperature: 4000,
+            automatic: true,
+            from: 20 * 60,
+            to: 6 * 60,
+        }
     }
 }
 
+impl Settings {
+    pub fn load(config: &cosmic_config::Config) -> Self {
+        let default = Self::default();
+
+        Self {
+            enabled: get(config, ENABLED, default.enabled),
+            temperature: get(config, TEMPERATURE, default.temperature)
+                .clamp(*TEMPERATURES.start(), *TEMPERATURES.end()),
+            automatic: get(config, AUTOMATIC, default.automatic),
+            from: get(config, SCHEDULE_FROM, default.from) % (24 * 60),
+            to: get(config, SCHEDULE_TO, default.to) % (24 * 60),
+        }
+    }
+
+    pub fn save(&self, config: &cosmic_config::Config) {
+        set(config, ENABLED, self.enabled);
+        set(config, TEMPERATURE, self.temperature);
+        set(config, AUTOMATIC, self.automatic);
+        set(config, SCHEDULE_FROM, self.from);
+        set(config, SCHEDULE_TO, self.to);
+    }
+
+    /// Whether night light should currently be shown.
+    ///
+    /// `daytime` is unknown when the location is unavailable, in which case the
+    /// automatic schedule is never active.
+    pub fn is_active(&self, daytime: Option<bool>, minute: u32) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        if self.automatic {
+            return daytime == Some(false);
+        }
+
+        if self.from <= self.to {
+            (self.from..self.to).contains(&minute)
+        } else {
+            // The schedule runs past midnight.
+            minute >= self.from || minute < self.to
+        }
+    }
+}
+
+/// Emits once at startup and then every minute, so that the manual schedule starts and ends
+/// on time.
+pub fn subscription() -> iced::Subscription<()> {
+    struct NightLightSchedule;
+
+    iced::subscription::channel(
+        TypeId::of::<NightLightSchedule>(),
+        1,
+        |mut output| async move {
+            loop {
+                _ = output.send(()).await;
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+        },
+    )
+}
+
+fn get<T: DeserializeOwned>(config: &cosmic_config::Config, key: &str, default: T) -> T {
+    config.get(key).unwrap_or_else(|why| {
+        if !matches!(why, cosmic_config::Error::NoConfigDirectory) {
+            tracing::error!(?why, "failed to read night light config '{key}'");
+        }
+
+        default
+    })
+}
+
+fn set<T: Serialize>(config: &cosmic_config::Config, key: &str, value: T) {
+    if let Err(why) = config.set(key, value) {
+        tracing::error!(?why, "failed to set night light config '{key}'");
+    }
+}
+
+fn schedule_position(minute: u32) -> usize {
+    (minute / SCHEDULE_STEP) as usize
+}
+
+/// View for the night light section.
+pub fn section() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let night_light = descriptions.insert(fl!("night-light"));
+    let night_light_desc = descriptions.insert(fl!("night-light", "desc"));
+    _ = descriptions.insert(fl!("night-light", "auto"));
+
+    Section::default()
+        .descriptions(descriptions)
+        .view::<super::Page>(move |_binder, page, section| {
+            let descriptions = &section.descriptions;
+
+            let control = row()
+                .align_items(Alignment::Center)
+                .push(toggler(None, page.config.night_light.enabled, |enable| {
+                    Message::NightLight(NightLight::Toggle(enable))
+                }))
+                .push(
+                    button::icon(icon::from_name("go-next-symbolic"))
+                        .extra_small()
+                        .on_press(Message::NightLightContext),
+                );
+
+            settings::view_section("")
+                .add(
+                    settings::item::builder(&descriptions[night_light])
+                        .description(&descriptions[night_light_desc])
+                        .control(control),
+                )
+                .apply(Element::from)
+                .map(pages::Message::Displays)
+        })
+}
+
 impl super::Page {
-    pub fn night_light_view(&self) -> Element<pages::Message> {
-        let mut container = list_column();
-
-        // Displays the night light status, and a button for configuring it.
-        container = container.add(
-            cosmic::widget::settings::item::builder(&*super::text::NIGHT_LIGHT)
-                .description(&*super::text::NIGHT_LIGHT_DESCRIPTION)
-                .control(
-                    row()
-                        .align_items(Alignment::Center)
-                        .push(toggler(None, self.config.night_light_enabled, |enable| {
-                            Message::NightLight(NightLight::Toggle(enable))
-                        }))
-                        .push(
-                            button::icon(icon::from_name("go-next-symbolic"))
-                                .extra_small()
-                                .on_press(Message::NightLightContext),
-                        ),
-                ),
-        );
-
-        container
+    /// Displays the night light context drawer.
+    pub fn night_light_context_view(&self) -> Element<pages::Message> {
+        let night_light = &self.config.night_light;
+        let minute = chrono::Local::now().time().num_seconds_from_midnight() / 60;
+
+        let status = if self.gamma_error.is_some() {
+            fl!("night-light", "unavailable")
+        } else if night_light.is_active(self.daytime, minute) {
+            fl!("night-light", "active")
+        } else {
+            fl!("night-light", "inactive")
+        };
+
+        let temperature = row()
+            .align_items(Alignment::Center)
+            .spacing(8)
+            .push(
+                widget::slider(TEMPERATURES, night_light.temperature, |kelvin| {
+                    Message::NightLight(NightLight::Temperature(kelvin))
+                })
+                .step(TEMPERATURE_STEP)
+                .on_release(Message::NightLight(NightLight::TemperatureRelease)),
+            )
+            .push(widget::text::body(format!("{}K", night_light.temperature)));
+
+        let schedule = (!night_light.automatic).then(|| {
+            settings::view_section(fl!("scheduling", "manual"))
+                .add(settings::item(
+                    fl!("scheduling", "from"),
+                    widget::dropdown(
+                        &SCHEDULE_LABELS,
+                        Some(schedule_position(night_light.from)),
+                        |id| Message::NightLight(NightLight::ScheduleFrom(id)),
+                    ),
+                ))
+                .add(settings::item(
+                    fl!("scheduling", "to"),
+                    widget::dropdown(
+                        &SCHEDULE_LABELS,
+                        Some(schedule_position(night_light.to)),
+                        |id| Message::NightLight(NightLight::ScheduleTo(id)),
+                    ),
+                ))
+        });
+
+        let location_unavailable = (night_light.automatic && self.daytime.is_none())
+            .then(|| widget::text::caption(fl!("night-light", "location-unavailable")));
+
+        column()
+            .spacing(24)
+            .push(
+                settings::view_section("")
+                    .add(
+                        settings::item::builder(fl!("night-light"))
+                            .description(status)
+                            .toggler(night_light.enabled, |enable| {
+                                Message::NightLight(NightLight::Toggle(enable))
+                            }),
+                    )
+                    .add(settings::item(fl!("color", "temperature"), temperature))
+                    .add(
+                        settings::item::builder(fl!("night-light", "auto"))
+                            .toggler(night_light.automatic, |automatic| {
+                                Message::NightLight(NightLight::AutoSchedule(automatic))
+                            }),
+                    ),
+            )
+            .push_maybe(location_unavailable)
+            .push_maybe(schedule)
             .apply(Element::from)
-            .map(crate::pages::Message::Displays)
+            .map(pages::Message::Displays)
+    }
+
+    pub fn update_night_light(&mut self, message: NightLight) -> Command<app::Message> {
+        let night_light = &mut self.config.night_light;
+
+        match message {
+            NightLight::AutoSchedule(automatic) => night_light.automatic = automatic,
+
+            NightLight::Exited(why) => {
+                if let Some(why) = why {
+                    self.gamma = None;
+                    self.gamma_error = Some(why);
+                }
+
+                return Command::none();
+            }
+
+            NightLight::ScheduleFrom(id) => night_light.from = id as u32 * SCHEDULE_STEP,
+
+            NightLight::ScheduleTo(id) => night_light.to = id as u32 * SCHEDULE_STEP,
+
+            NightLight::Temperature(kelvin) => {
+                night_light.temperature = kelvin;
+
+                // Shown regardless of the schedule while it is being chosen, and saved when
+                // the slider is released.
+                return self.set_gamma(Some(kelvin));
+            }
+
+            NightLight::TemperatureRelease => (),
+
+            NightLight::Tick => return self.apply_night_light(),
+
+            NightLight::Toggle(enable) => {
+                night_light.enabled = enable;
+                // Toggling night light tries again after a failure.
+                self.gamma_error = None;
+            }
+        }
+
+        if let Some(config) = self.night_light_config.as_ref() {
+            night_light.save(config);
+        }
+
+        self.apply_night_light()
+    }
+
+    /// Applies the temperature which the schedule asks for at the current time.
+    pub fn apply_night_light(&mut self) -> Command<app::Message> {
+        let night_light = &self.config.night_light;
+        let minute = chrono::Local::now().time().num_seconds_from_midnight() / 60;
+
+        let temperature = night_light
+            .is_active(self.daytime, minute)
+            .then_some(night_light.temperature);
+
+        self.set_gamma(temperature)
+    }
+
+    /// Sets the color temperature of every output, or restores their colors if `None`.
+    ///
+    /// The gamma control thread is only started once a temperature is needed.
+    fn set_gamma(&mut self, temperature: Option<u32>) -> Command<app::Message> {
+        let Some(temperature) = temperature else {
+            // Dropping the handle restores the gamma of every output.
+            self.gamma = None;
+            return Command::none();
+        };
+
+        if let Some(gamma) = self.gamma.as_ref() {
+            gamma.set(temperature);
+            return Command::none();
+        }
+
+        if self.gamma_error.is_some() {
+            return Command::none();
+        }
+
+        let (gamma, failed) = gamma::Gamma::spawn();
+        gamma.set(temperature);
+        self.gamma = Some(gamma);
+
+        command::future(async move {
+            app::Message::from(Message::NightLight(NightLight::Exited(failed.await.ok())))
+        })
     }
 }
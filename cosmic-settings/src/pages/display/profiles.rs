@@ -0,0 +1,295 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Named display configurations which are restored when the same set of monitors is connected.
+
+use std::any::TypeId;
+use std::collections::{BTreeMap, BTreeSet};
+
+use cosmic::cosmic_config::{self, ConfigGet, ConfigSet};
+use cosmic::iced::{
+    self,
+    futures::{channel::mpsc::Sender, future, SinkExt},
+};
+use cosmic_randr_shell::{List, OutputKey, Transform};
+use serde::{Deserialize, Serialize};
+
+use super::randr;
+
+const PROFILES: &str = "display-profiles";
+
+/// Identifies a physical monitor independently of the connector it is plugged into.
+///
+/// Identical monitors which don't report a serial number can't be told apart.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct MonitorId {
+    pub make: String,
+    pub model: String,
+    #[serde(default)]
+    pub serial: String,
+}
+
+/// The monitors in a list, with the serial numbers reported by output management.
+pub fn monitors(
+    list: &List,
+    heads: &BTreeMap<String, randr::HeadInfo>,
+) -> Vec<(MonitorId, OutputKey)> {
+    list.outputs
+        .iter()
+        .map(|(key, output)| {
+            let id = MonitorId {
+                make: output.make.clone().unwrap_or_default(),
+                model: output.model.clone(),
+                serial: heads
+                    .get(&output.name)
+                    .map(|head| head.serial_number.clone())
+                    .unwrap_or_default(),
+            };
+
+            (id, key)
+        })
+        .collect()
+}
+
+pub fn monitor_set(list: &List, heads: &BTreeMap<String, randr::HeadInfo>) -> BTreeSet<MonitorId> {
+    monitors(list, heads)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect()
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct OutputState {
+    pub enabled: bool,
+    pub size: Option<(u32, u32)>,
+    /// Refresh rate in millihertz.
+    pub refresh: Option<u32>,
+    pub position: (i32, i32),
+    pub scale: f64,
+    #[serde(with = "transform")]
+    pub transform: Option<Transform>,
+    pub mirroring: Option<MonitorId>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Profile {
+    pub name: String,
+    pub outputs: BTreeMap<MonitorId, OutputState>,
+}
+
+impl Profile {
+    /// Captures the current configuration of every output.
+    pub fn capture(name: String, list: &List, heads: &BTreeMap<String, randr::HeadInfo>) -> Self {
+        let monitors = monitors(list, heads);
+
+        let outputs = monitors
+            .iter()
+            .map(|(id, key)| {
+                let output = &list.outputs[*key];
+                let mode = output.current.and_then(|mode| list.modes.get(mode));
+
+                let mirroring = output.mirroring.as_deref().and_then(|from| {
+                    monitors
+                        .iter()
+                        .find(|(_, key)| list.outputs[*key].name == from)
+                        .map(|(id, _)| id.clone())
+                });
+
+                let state = OutputState {
+                    enabled: output.enabled,
+                    size: mode.map(|mode| mode.size),
+                    refresh: mode.map(|mode| mode.refresh_rate),
+                    position: output.position,
+                    scale: output.scale,
+                    transform: output.transform,
+                    mirroring,
+                };
+
+                (id.clone(), state)
+            })
+            .collect();
+
+        Self { name, outputs }
+    }
+
+    /// Whether the profile was saved for exactly this set of monitors.
+    pub fn matches(&self, monitors: &BTreeSet<MonitorId>) -> bool {
+        self.outputs.len() == monitors.len() && self.outputs.keys().all(|id| monitors.contains(id))
+    }
+
+    /// Creates a request which restores the profile onto the currently connected outputs.
+    pub fn request(
+        &self,
+        list: &List,
+        heads: &BTreeMap<String, randr::HeadInfo>,
+    ) -> randr::Request {
+        let monitors = monitors(list, heads)
+            .into_iter()
+            .map(|(id, key)| (id, &*list.outputs[key].name))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut request = randr::Request::new();
+
+        for (id, state) in &self.outputs {
+            let Some(&name) = monitors.get(id) else {
+                continue;
+            };
+
+            if !state.enabled {
+                request = request.disable(name);
+                continue;
+            }
+
+            if let Some(from) = state.mirroring.as_ref().and_then(|id| monitors.get(id)) {
+                request = request.mirror(name, from);
+            }
+
+            if let Some((width, height)) = state.size {
+                request = request.resolution(name, width, height);
+            }
+
+            if let Some(refresh) = state.refresh {
+                request = request.refresh_rate(name, refresh);
+            }
+
+            if let Some(transform) = state.transform {
+                request = request.transform(name, transform);
+            }
+
+            request = request
+                .position(name, state.position.0, state.position.1)
+                .scale(name, state.scale);
+        }
+
+        request
+    }
+}
+
+pub fn load(config: &cosmic_config::Config) -> Vec<Profile> {
+    config.get(PROFILES).unwrap_or_else(|why| {
+        if !matches!(why, cosmic_config::Error::NoConfigDirectory) {
+            tracing::error!(?why, "failed to read config '{PROFILES}'");
+        }
+
+        Vec::new()
+    })
+}
+
+pub fn save(config: &cosmic_config::Config, profiles: &[Profile]) {
+    if let Err(why) = config.set(PROFILES, profiles) {
+        tracing::error!(?why, "failed to set config '{PROFILES}'");
+    }
+}
+
+/// Restores the matching profile whenever the set of connected monitors changes.
+///
+/// Emits the result of every profile that was applied.
+pub fn subscription() -> iced::Subscription<Result<(), randr::Error>> {
+    struct DisplayProfiles;
+
+    iced::subscription::channel(TypeId::of::<DisplayProfiles>(), 1, |tx| async move {
+        // The wayland connection is not `Send`, so it must be driven from its own thread.
+        let runtime = tokio::runtime::Handle::current();
+        _ = tokio::task::spawn_blocking(move || runtime.block_on(watch(tx))).await;
+
+        future::pending().await
+    })
+}
+
+async fn watch(mut tx: Sender<Result<(), randr::Error>>) {
+    let Ok(config) = cosmic_config::Config::new(crate::config::NAME, 1) else {
+        return;
+    };
+
+    let (randr_tx, mut randr_rx) = tachyonix::channel(5);
+    let Ok((mut context, mut event_queue)) = cosmic_randr::connect(randr_tx) else {
+        return;
+    };
+
+    let mut connected = None;
+
+    while context.dispatch(&mut event_queue).await.is_ok() {
+        let mut changed = false;
+
+        while let Ok(message) = randr_rx.try_recv() {
+            changed |= matches!(message, cosmic_randr::Message::ManagerDone);
+        }
+
+        if !changed {
+            continue;
+        }
+
+        let Ok(list) = cosmic_randr_shell::list().await else {
+            continue;
+        };
+
+        let heads = randr::head_info(&context);
+
+        let monitors = monitor_set(&list, &heads);
+
+        if connected.as_ref() == Some(&monitors) {
+            continue;
+        }
+
+        // Don't reconfigure the displays which were connected when settings was started.
+        if connected.replace(monitors.clone()).is_none() {
+            continue;
+        }
+
+        if let Some(profile) = load(&config).into_iter().find(|p| p.matches(&monitors)) {
+            tracing::info!(profile = profile.name, "restoring display profile");
+
+            if tx
+                .send(profile.request(&list, &heads).apply().await)
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+/// Serializes transforms by their cosmic-randr names.
+mod transform {
+    use cosmic_randr_shell::Transform;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    const TRANSFORMS: [(Transform, &str); 8] = [
+        (Transform::Normal, "normal"),
+        (Transform::Rotate90, "rotate90"),
+        (Transform::Rotate180, "rotate180"),
+        (Transform::Rotate270, "rotate270"),
+        (Transform::Flipped, "flipped"),
+        (Transform::Flipped90, "flipped90"),
+        (Transform::Flipped180, "flipped180"),
+        (Transform::Flipped270, "flipped270"),
+    ];
+
+    pub fn serialize<S: Serializer>(
+        transform: &Option<Transform>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let name = transform.and_then(|transform| {
+            TRANSFORMS
+                .iter()
+                .find(|(t, _)| *t == transform)
+                .map(|(_, name)| *name)
+        });
+
+        name.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Transform>, D::Error> {
+        let name = Option::<String>::deserialize(deserializer)?;
+
+        Ok(name.and_then(|name| {
+            TRANSFORMS
+                .iter()
+                .find(|(_, n)| *n == name)
+                .map(|(transform, _)| *transform)
+        }))
+    }
+}
@@ -0,0 +1,322 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Typed output configuration requests, applied through the compositor's output management.
+
+use std::{collections::BTreeMap, fmt};
+
+use cosmic::cctk::cosmic_protocols::output_management::v1::client::zcosmic_output_head_v1::AdaptiveSyncStateExt;
+use cosmic::cctk::wayland_client::protocol::wl_output;
+use cosmic_randr::context::{Context, HeadConfiguration};
+use cosmic_randr_shell::Transform;
+
+use super::adaptive_sync::AdaptiveSync;
+
+/// A batch of output changes which the compositor applies atomically.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Request {
+    heads: BTreeMap<String, Head>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Head {
+    state: HeadState,
+    changes: HeadChanges,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+enum HeadState {
+    #[default]
+    Enabled,
+    Disabled,
+    Mirroring(String),
+}
+
+/// Changes to a single output. Fields left as `None` are kept as they are.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeadChanges {
+    pub size: Option<(u32, u32)>,
+    /// Refresh rate in millihertz.
+    pub refresh: Option<u32>,
+    pub position: Option<(i32, i32)>,
+    pub scale: Option<f64>,
+    pub transform: Option<Transform>,
+    pub adaptive_sync: Option<AdaptiveSync>,
+}
+
+impl Request {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heads.is_empty()
+    }
+
+    /// Changes to be applied to an output, enabling it if it was to be disabled.
+    fn changes(&mut self, output: &str) -> &mut HeadChanges {
+        let head = self.heads.entry(output.to_owned()).or_default();
+
+        if head.state == HeadState::Disabled {
+            head.state = HeadState::Enabled;
+        }
+
+        &mut head.changes
+    }
+
+    pub fn enable(mut self, output: &str) -> Self {
+        self.changes(output);
+        self
+    }
+
+    pub fn disable(mut self, output: &str) -> Self {
+        self.heads.entry(output.to_owned()).or_default().state = HeadState::Disabled;
+        self
+    }
+
+    /// Mirrors the contents of the `from` output onto `output`.
+    pub fn mirror(mut self, output: &str, from: &str) -> Self {
+        self.heads.entry(output.to_owned()).or_default().state =
+            HeadState::Mirroring(from.to_owned());
+        self
+    }
+
+    pub fn resolution(mut self, output: &str, width: u32, height: u32) -> Self {
+        self.changes(output).size = Some((width, height));
+        self
+    }
+
+    pub fn refresh_rate(mut self, output: &str, millihertz: u32) -> Self {
+        self.changes(output).refresh = Some(millihertz);
+        self
+    }
+
+    pub fn position(mut self, output: &str, x: i32, y: i32) -> Self {
+        self.changes(output).position = Some((x, y));
+        self
+    }
+
+    pub fn scale(mut self, output: &str, scale: f64) -> Self {
+        self.changes(output).scale = Some(scale);
+        self
+    }
+
+    pub fn transform(mut self, output: &str, transform: Transform) -> Self {
+        self.changes(output).transform = Some(transform);
+        self
+    }
+
+    pub fn adaptive_sync(mut self, output: &str, adaptive_sync: AdaptiveSync) -> Self {
+        self.changes(output).adaptive_sync = Some(adaptive_sync);
+        self
+    }
+
+    /// Merges the changes of another request into this one, with `other` taking precedence.
+    pub fn merge(mut self, other: Request) -> Self {
+        for (name, head) in other.heads {
+            let current = self.heads.entry(name).or_default();
+            current.state = head.state;
+            current.changes.merge(head.changes);
+        }
+
+        self
+    }
+
+    /// Applies every change in the request as a single output configuration.
+    pub async fn apply(self) -> Result<(), Error> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        tracing::debug!(request = ?self, "applying output configuration");
+
+        // The wayland connection is not `Send`, so it must be driven from its own thread.
+        let runtime = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking(move || runtime.block_on(apply_heads(self.heads)))
+            .await
+            .map_err(|why| Error::Connection(why.to_string()))?
+    }
+}
+
+/// State of an output which is not included in cosmic-randr's output list.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeadInfo {
+    /// Serial number from the EDID, which is empty if the monitor doesn't report one.
+    pub serial_number: String,
+    /// Adaptive sync mode, if the compositor reports it.
+    pub adaptive_sync: Option<AdaptiveSync>,
+}
+
+/// Reads the state of every output from output management, by connector name.
+pub async fn heads() -> Result<BTreeMap<String, HeadInfo>, Error> {
+    // The wayland connection is not `Send`, so it must be driven from its own thread.
+    let runtime = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || runtime.block_on(read_heads()))
+        .await
+        .map_err(|why| Error::Connection(why.to_string()))?
+}
+
+async fn read_heads() -> Result<BTreeMap<String, HeadInfo>, Error> {
+    let (tx, mut rx) = tachyonix::channel(5);
+
+    let (mut context, mut event_queue) =
+        cosmic_randr::connect(tx).map_err(|why| Error::Connection(why.to_string()))?;
+
+    loop {
+        context
+            .dispatch(&mut event_queue)
+            .await
+            .map_err(|why| Error::Connection(why.to_string()))?;
+
+        while let Ok(message) = rx.try_recv() {
+            if let cosmic_randr::Message::ManagerDone = message {
+                return Ok(head_info(&context));
+            }
+        }
+    }
+}
+
+/// The state of every output known to an output management connection, by connector name.
+pub fn head_info(context: &Context) -> BTreeMap<String, HeadInfo> {
+    context
+        .output_heads
+        .values()
+        .map(|head| {
+            let info = HeadInfo {
+                serial_number: head.serial_number.clone(),
+                adaptive_sync: head.adaptive_sync.and_then(|state| match state {
+                    AdaptiveSyncStateExt::Disabled => Some(AdaptiveSync::Disabled),
+                    AdaptiveSyncStateExt::Automatic => Some(AdaptiveSync::Automatic),
+                    AdaptiveSyncStateExt::Always => Some(AdaptiveSync::Always),
+                    _ => None,
+                }),
+            };
+
+            (head.name.clone(), info)
+        })
+        .collect()
+}
+
+/// Applies the head changes as a single output configuration.
+async fn apply_heads(heads: BTreeMap<String, Head>) -> Result<(), Error> {
+    let (tx, mut rx) = tachyonix::channel(5);
+
+    let (mut context, mut event_queue) =
+        cosmic_randr::connect(tx).map_err(|why| Error::Connection(why.to_string()))?;
+
+    // Wait for the current state of every output before configuring them.
+    'init: loop {
+        context
+            .dispatch(&mut event_queue)
+            .await
+            .map_err(|why| Error::Connection(why.to_string()))?;
+
+        while let Ok(message) = rx.try_recv() {
+            if let cosmic_randr::Message::ManagerDone = message {
+                break 'init;
+            }
+        }
+    }
+
+    let mut config = context.create_output_config();
+
+    for (name, head) in heads {
+        let result = match head.state {
+            HeadState::Enabled => config.enable_head(&name, Some(head.changes.into())),
+            HeadState::Disabled => config.disable_head(&name),
+            HeadState::Mirroring(from) => {
+                config.mirror_head(&name, &from, Some(head.changes.into()))
+            }
+        };
+
+        result.map_err(|why| Error::Configuration(why.to_string()))?;
+    }
+
+    config.apply();
+
+    loop {
+        context
+            .dispatch(&mut event_queue)
+            .await
+            .map_err(|why| Error::Connection(why.to_string()))?;
+
+        while let Ok(message) = rx.try_recv() {
+            match message {
+                cosmic_randr::Message::ConfigurationSucceeded => return Ok(()),
+                cosmic_randr::Message::ConfigurationFailed => return Err(Error::Failed),
+                cosmic_randr::Message::ConfigurationCancelled => return Err(Error::Cancelled),
+                _ => (),
+            }
+        }
+    }
+}
+
+impl HeadChanges {
+    fn merge(&mut self, other: HeadChanges) {
+        self.size = other.size.or(self.size);
+        self.refresh = other.refresh.or(self.refresh);
+        self.position = other.position.or(self.position);
+        self.scale = other.scale.or(self.scale);
+        self.transform = other.transform.or(self.transform);
+        self.adaptive_sync = other.adaptive_sync.or(self.adaptive_sync);
+    }
+}
+
+impl From<HeadChanges> for HeadConfiguration {
+    fn from(changes: HeadChanges) -> Self {
+        HeadConfiguration {
+            size: changes.size,
+            refresh: changes.refresh.map(|rate| rate as f32 / 1000.0),
+            pos: changes.position,
+            scale: changes.scale,
+            transform: changes.transform.map(|transform| match transform {
+                Transform::Normal => wl_output::Transform::Normal,
+                Transform::Rotate90 => wl_output::Transform::_90,
+                Transform::Rotate180 => wl_output::Transform::_180,
+                Transform::Rotate270 => wl_output::Transform::_270,
+                Transform::Flipped => wl_output::Transform::Flipped,
+                Transform::Flipped90 => wl_output::Transform::Flipped90,
+                Transform::Flipped180 => wl_output::Transform::Flipped180,
+                Transform::Flipped270 => wl_output::Transform::Flipped270,
+            }),
+            adaptive_sync: changes
+                .adaptive_sync
+                .map(|adaptive_sync| match adaptive_sync {
+                    AdaptiveSync::Disabled => AdaptiveSyncStateExt::Disabled,
+                    AdaptiveSync::Automatic => AdaptiveSyncStateExt::Automatic,
+                    AdaptiveSync::Always => AdaptiveSyncStateExt::Always,
+                }),
+            ..HeadConfiguration::default()
+        }
+    }
+}
+
+/// Why an output configuration could not be applied.
+#[derive(Clone, Debug)]
+pub enum Error {
+    /// Output management is unavailable.
+    Connection(String),
+    /// The request could not be expressed as an output configuration.
+    Configuration(String),
+    /// The compositor rejected the configuration.
+    Failed,
+    /// The configuration was superseded by another change before it was applied.
+    Cancelled,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connection(why) => {
+                f.write_str(&fl!("display-error", "connection", why = why.as_str()))
+            }
+            Self::Configuration(why) => {
+                f.write_str(&fl!("display-error", "configuration", why = why.as_str()))
+            }
+            Self::Failed => f.write_str(&fl!("display-error", "failed")),
+            Self::Cancelled => f.write_str(&fl!("display-error", "cancelled")),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -46,6 +46,8 @@ const STR_ORDER: &str = "`str` is always comparable";
 pub enum Message {
     ExpandInputSourcePopover(Option<DefaultKey>),
     InputSourceSearch(String),
+    /// Reloads the xkb config after it was reverted.
+    Reload,
     OpenSpecialCharacterContext(SpecialKey),
     ShowInputSourcesContext,
     SourceAdd(DefaultKey),
@@ -322,28 +324,7 @@ impl page::Page<crate::pages::Message> for Page {
                     }
                 }
 
-                // Xkb layouts currently enabled.
-                let layouts = if self.xkb.layout.is_empty() {
-                    "us"
-                } else {
-                    &self.xkb.layout
-                }
-                .split_terminator(',');
-
-                // Xkb variants for each layout. Repeat empty strings in case there's more layouts than variants.
-                let variants = self
-                    .xkb
-                    .variant
-                    .split_terminator(',')
-                    .chain(std::iter::repeat(""));
-
-                for (layout, variant) in layouts.zip(variants) {
-                    for (id, (xkb_layout, xkb_variant, _desc)) in &self.keyboard_layouts {
-                        if layout == xkb_layout && variant == xkb_variant {
-                            self.active_layouts.push(id);
-                        }
-                    }
-                }
+                self.load_active_layouts();
             }
 
             Err(why) => {
@@ -362,6 +343,11 @@ impl Page {
                 self.input_source_search = search;
             }
 
+            Message::Reload => {
+                self.xkb = super::get_config(&self.config, "xkb_config");
+                self.load_active_layouts();
+            }
+
             Message::SourceAdd(id) => {
                 self.context = None;
 
@@ -401,8 +387,21 @@ impl Page {
                         if let Some(pos) =
                             self.active_layouts.iter().position(|&active| active == id)
                         {
+                            // Removing a layout may leave none that can type the confirmation
+                            // of the change, so it is reverted unless kept.
+                            let revert =
+                                crate::revert::Revert::new(fl!("dialog", "title-settings"))
+                                    .config(&self.config, "xkb_config", self.xkb.clone())
+                                    .command(|| {
+                                        cosmic::command::message(crate::app::Message::PageMessage(
+                                            crate::pages::Message::Keyboard(Message::Reload),
+                                        ))
+                                    });
+
                             let _removed = self.active_layouts.remove(pos);
                             self.update_xkb_config();
+
+                            return revert.confirm();
                         }
                     }
 
@@ -538,6 +537,34 @@ impl Page {
         cosmic::widget::container(list).padding(24).into()
     }
 
+    /// Finds the layouts enabled in the xkb config.
+    fn load_active_layouts(&mut self) {
+        self.active_layouts.clear();
+
+        // Xkb layouts currently enabled.
+        let layouts = if self.xkb.layout.is_empty() {
+            "us"
+        } else {
+            &self.xkb.layout
+        }
+        .split_terminator(',');
+
+        // Xkb variants for each layout. Repeat empty strings in case there's more layouts than variants.
+        let variants = self
+            .xkb
+            .variant
+            .split_terminator(',')
+            .chain(std::iter::repeat(""));
+
+        for (layout, variant) in layouts.zip(variants) {
+            for (id, (xkb_layout, xkb_variant, _desc)) in &self.keyboard_layouts {
+                if layout == xkb_layout && variant == xkb_variant {
+                    self.active_layouts.push(id);
+                }
+            }
+        }
+    }
+
     fn update_xkb_config(&mut self) {
         let mut new_layout = String::new();
         let mut new_variant = String::new();
@@ -19,6 +19,8 @@ pub enum ShortcutMessage {
     DeleteShortcut(usize),
     EditBinding(usize, bool),
     InputBinding(usize, String),
+    /// Reloads the shortcuts from the config, if they are shown.
+    Reload,
     ResetBindings,
     ShowShortcut(usize, String),
     SubmitBinding(usize),
@@ -224,6 +226,14 @@ impl Model {
         shortcuts
     }
 
+    /// Captures the custom shortcuts before they are changed, to restore them unless the
+    /// change is kept.
+    pub(super) fn revert(&self) -> crate::revert::Revert {
+        crate::revert::Revert::new(fl!("dialog", "title-settings"))
+            .capture::<Shortcuts>(&self.config, "custom")
+            .command(reload)
+    }
+
     /// Writes a new configuration to the keyboard shortcuts config file.
     pub(super) fn shortcuts_config_set(&self, shortcuts: Shortcuts) {
         if let Err(why) = self.config.set("custom", shortcuts) {
@@ -268,6 +278,10 @@ impl Model {
             ShortcutMessage::ApplyReplace => {
                 if let Some((id, new_binding, ..)) = self.replace_dialog.take() {
                     if let Some(short_id) = self.shortcut_context {
+                        // Taking the binding of another shortcut may take the only way to
+                        // reach an action, so it is reverted unless kept.
+                        let revert = self.revert();
+
                         // Remove conflicting bindings that are saved on disk.
                         self.config_remove(&new_binding);
 
@@ -300,6 +314,7 @@ impl Model {
                         }
 
                         self.on_enter();
+                        return revert.confirm();
                     }
                 }
             }
@@ -353,6 +368,13 @@ impl Model {
                 }
             }
 
+            ShortcutMessage::Reload => {
+                // Shortcuts are only loaded while their page is shown.
+                if !self.shortcut_models.is_empty() {
+                    self.on_enter();
+                }
+            }
+
             // Removes all bindings from the active shortcut context, and reloads the shortcuts model.
             ShortcutMessage::ResetBindings => {
                 if let Some(short_id) = self.shortcut_context {
@@ -586,3 +608,23 @@ fn shortcut_item(custom: bool, id: usize, data: &ShortcutModel) -> Element<Short
             LocalMessage::Remove => ShortcutMessage::DeleteShortcut(id),
         })
 }
+
+/// Reloads every shortcuts page, since a change may have moved a binding between them.
+fn reload() -> Command<crate::app::Message> {
+    use crate::pages::Message;
+
+    let pages: [fn(ShortcutMessage) -> Message; 6] = [
+        |message| Message::CustomShortcuts(super::custom::Message::Shortcut(message)),
+        Message::ManageWindowShortcuts,
+        Message::MoveWindowShortcuts,
+        Message::NavShortcuts,
+        Message::SystemShortcuts,
+        Message::TilingShortcuts,
+    ];
+
+    Command::batch(pages.into_iter().map(|page| {
+        command::message(crate::app::Message::PageMessage(page(
+            ShortcutMessage::Reload,
+        )))
+    }))
+}
@@ -183,12 +183,18 @@ impl Page {
 
             Message::ReplaceApply => {
                 if let Some((binding, ..)) = self.replace_dialog.pop() {
+                    // Taking the binding of another shortcut may take the only way to reach
+                    // an action, so it is reverted unless kept.
+                    let revert = self.model.revert();
+
                     self.model.config_remove(&binding);
                     self.add_shortcut(binding);
 
                     if self.replace_dialog.is_empty() {
                         self.model.on_enter();
                     }
+
+                    return revert.confirm();
                 }
             }
 
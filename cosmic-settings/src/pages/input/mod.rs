@@ -19,6 +19,8 @@ pub enum Message {
     // seperate close message, to make sure another isn't closed?
     DisableWhileTyping(bool, bool),
     PrimaryButtonSelected(cosmic::widget::segmented_button::Entity, bool),
+    /// Reloads the input configs after they were reverted.
+    Reload,
     SetAcceleration(bool, bool),
     SetMouseSpeed(f64, bool),
     SetNaturalScroll(bool, bool),
@@ -31,7 +33,6 @@ pub enum Message {
 pub struct Page {
     config: cosmic_config::Config,
     input_default: InputConfig,
-    #[allow(dead_code)]
     input_touchpad: InputConfig,
 
     // Mouse
@@ -57,32 +58,36 @@ fn get_config<T: Default + serde::de::DeserializeOwned>(
 impl Default for Page {
     fn default() -> Self {
         let config = cosmic_config::Config::new("com.system76.CosmicComp", 1).unwrap();
-        let input_default: InputConfig = get_config(&config, "input_default");
-        let input_touchpad: InputConfig = get_config(&config, "input_touchpad");
 
-        let mut primary_button = mouse::default_primary_button();
-        let idx = input_default.left_handed.unwrap_or(false) as u16;
-        primary_button.activate_position(idx);
-
-        let mut touchpad_primary_button = mouse::default_primary_button();
-        let idx = input_touchpad.left_handed.unwrap_or(false) as u16;
-        touchpad_primary_button.activate_position(idx);
-
-        Self {
+        let mut page = Self {
             config,
-            input_default,
-            input_touchpad,
+            input_default: InputConfig::default(),
+            input_touchpad: InputConfig::default(),
 
             // Mouse
-            primary_button,
+            primary_button: mouse::default_primary_button(),
 
             // Touchpad
-            touchpad_primary_button,
-        }
+            touchpad_primary_button: mouse::default_primary_button(),
+        };
+
+        page.load();
+        page
     }
 }
 
 impl Page {
+    fn load(&mut self) {
+        self.input_default = get_config(&self.config, "input_default");
+        self.input_touchpad = get_config(&self.config, "input_touchpad");
+
+        let idx = self.input_default.left_handed.unwrap_or(false) as u16;
+        self.primary_button.activate_position(idx);
+
+        let idx = self.input_touchpad.left_handed.unwrap_or(false) as u16;
+        self.touchpad_primary_button.activate_position(idx);
+    }
+
     fn update_input<F: Fn(&mut InputConfig)>(&mut self, touchpad: bool, f: F) {
         let (name, input_config) = if touchpad {
             ("input_touchpad", &mut self.input_touchpad)
@@ -159,9 +164,30 @@ impl Page {
                 };
 
                 let left_handed = select_model.active() == left_entity;
+
+                // Swapping the buttons of the only pointing device can leave it unable to
+                // confirm the change, so it is reverted unless kept.
+                let key = if touchpad {
+                    "input_touchpad"
+                } else {
+                    "input_default"
+                };
+
+                let revert = crate::revert::Revert::new(fl!("dialog", "title-settings"))
+                    .capture::<InputConfig>(&self.config, key)
+                    .command(|| {
+                        cosmic::command::message(app::Message::PageMessage(
+                            crate::pages::Message::Input(Message::Reload),
+                        ))
+                    });
+
                 self.update_input(touchpad, |x| x.left_handed = Some(left_handed));
+
+                return revert.confirm();
             }
 
+            Message::Reload => self.load(),
+
             Message::TapToClick(enabled) => {
                 self.update_input(true, |conf| {
                     conf.tap_config
@@ -0,0 +1,152 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! In-process fakes of system76-power, power-profiles-daemon and UPower,
+//! served to a private peer-to-peer connection.
+
+use zbus::{
+    connection::Builder,
+    interface,
+    zvariant::{ObjectPath, OwnedObjectPath},
+    Connection, Guid,
+};
+
+const DISPLAY_DEVICE: &str = "/org/freedesktop/UPower/devices/DisplayDevice";
+
+/// The services exported by the fake bus. `None` leaves a service unavailable.
+#[derive(Default)]
+pub struct Services {
+    pub s76_profile: Option<&'static str>,
+    pub ppd_profile: Option<&'static str>,
+    pub battery: Option<Device>,
+}
+
+pub struct PowerDaemon {
+    profile: String,
+}
+
+#[interface(name = "com.system76.PowerDaemon")]
+impl PowerDaemon {
+    fn get_profile(&self) -> String {
+        self.profile.clone()
+    }
+
+    fn battery(&mut self) {
+        self.profile = String::from("Battery");
+    }
+
+    fn balanced(&mut self) {
+        self.profile = String::from("Balanced");
+    }
+
+    fn performance(&mut self) {
+        self.profile = String::from("Performance");
+    }
+}
+
+pub struct PowerProfiles {
+    active_profile: String,
+}
+
+#[interface(name = "org.freedesktop.UPower.PowerProfiles")]
+impl PowerProfiles {
+    #[zbus(property)]
+    fn active_profile(&self) -> String {
+        self.active_profile.clone()
+    }
+
+    #[zbus(property)]
+    fn set_active_profile(&mut self, profile: String) {
+        self.active_profile = profile;
+    }
+}
+
+pub struct UPower {
+    on_battery: bool,
+}
+
+#[interface(name = "org.freedesktop.UPower")]
+impl UPower {
+    fn get_display_device(&self) -> OwnedObjectPath {
+        ObjectPath::from_static_str_unchecked(DISPLAY_DEVICE).into()
+    }
+
+    #[zbus(property)]
+    fn on_battery(&self) -> bool {
+        self.on_battery
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Device {
+    pub on_battery: bool,
+    pub is_present: bool,
+    pub percentage: f64,
+    pub time_to_empty: i64,
+    pub time_to_full: i64,
+}
+
+#[interface(name = "org.freedesktop.UPower.Device")]
+impl Device {
+    #[zbus(property)]
+    fn is_present(&self) -> bool {
+        self.is_present
+    }
+
+    #[zbus(property)]
+    fn percentage(&self) -> f64 {
+        self.percentage
+    }
+
+    #[zbus(property)]
+    fn time_to_empty(&self) -> i64 {
+        self.time_to_empty
+    }
+
+    #[zbus(property)]
+    fn time_to_full(&self) -> i64 {
+        self.time_to_full
+    }
+}
+
+/// Serves the fake services, returning the server and client ends of the connection.
+///
+/// The server connection must be kept alive for as long as the client is used.
+pub async fn connect(services: Services) -> zbus::Result<(Connection, Connection)> {
+    let (server, client) = tokio::net::UnixStream::pair()?;
+
+    let mut server = Builder::unix_stream(server).server(Guid::generate())?.p2p();
+
+    if let Some(profile) = services.s76_profile {
+        server = server.serve_at(
+            "/com/system76/PowerDaemon",
+            PowerDaemon {
+                profile: profile.to_owned(),
+            },
+        )?;
+    }
+
+    if let Some(profile) = services.ppd_profile {
+        server = server.serve_at(
+            "/org/freedesktop/UPower/PowerProfiles",
+            PowerProfiles {
+                active_profile: profile.to_owned(),
+            },
+        )?;
+    }
+
+    if let Some(device) = services.battery {
+        server = server
+            .serve_at(
+                "/org/freedesktop/UPower",
+                UPower {
+                    on_battery: device.on_battery,
+                },
+            )?
+            .serve_at(DISPLAY_DEVICE, device)?;
+    }
+
+    let client = Builder::unix_stream(client).p2p().build();
+
+    futures::try_join!(server.build(), client)
+}
@@ -1,10 +1,16 @@
 use chrono::Duration;
 use futures::FutureExt;
+use serde::{Deserialize, Serialize};
 use zbus::Connection;
 
 mod ppdaemon;
 mod s76powerdaemon;
 
+#[cfg(test)]
+mod fake;
+#[cfg(test)]
+mod tests;
+
 pub trait SetPowerProfile {
     async fn set_power_profile(&self, profile: PowerProfile);
 }
@@ -39,22 +45,43 @@ impl GetCurrentPowerProfile for PowerBackendEnum {
 pub trait PowerBackend: SetPowerProfile + GetCurrentPowerProfile {}
 
 pub async fn get_backend() -> Option<PowerBackendEnum> {
-    match get_s76power_daemon_proxy().await {
-        Ok(p) => match p.get_profile().await {
-            Ok(_) => Some(PowerBackendEnum::S76(S76Backend {})),
-            Err(_) => match get_power_profiles_proxy().await {
-                Ok(pr) => match pr.active_profile().await {
-                    Ok(_) => Some(PowerBackendEnum::PP(PPBackend {})),
-                    Err(_) => None,
-                },
-                Err(()) => None,
-            },
-        },
-        Err(()) => None,
+    detect_backend(&system_connection().await?).await
+}
+
+/// Detects the power profile daemon available on the given bus.
+///
+/// system76-power is preferred over power-profiles-daemon when both respond.
+pub async fn detect_backend(connection: &Connection) -> Option<PowerBackendEnum> {
+    if let Ok(daemon) = get_s76power_daemon_proxy(connection).await {
+        if daemon.get_profile().await.is_ok() {
+            return Some(PowerBackendEnum::S76(S76Backend {
+                connection: connection.clone(),
+            }));
+        }
+    }
+
+    if let Ok(daemon) = get_power_profiles_proxy(connection).await {
+        if daemon.active_profile().await.is_ok() {
+            return Some(PowerBackendEnum::PP(PPBackend {
+                connection: connection.clone(),
+            }));
+        }
     }
+
+    None
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+async fn system_connection() -> Option<Connection> {
+    match Connection::system().await {
+        Ok(c) => Some(c),
+        Err(e) => {
+            tracing::error!("zbus connection failed. {e}");
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum PowerProfile {
     Battery,
     Balanced,
@@ -95,13 +122,15 @@ pub fn get_power_profiles() -> Vec<PowerProfile> {
     ]
 }
 
-pub struct S76Backend {}
+pub struct S76Backend {
+    connection: Connection,
+}
 
 impl PowerBackend for S76Backend {}
 
 impl SetPowerProfile for S76Backend {
     async fn set_power_profile(&self, profile: PowerProfile) {
-        let Ok(daemon) = get_s76power_daemon_proxy().await else {
+        let Ok(daemon) = get_s76power_daemon_proxy(&self.connection).await else {
             tracing::error!("Problem while setting power profile.");
             return;
         };
@@ -125,7 +154,7 @@ impl SetPowerProfile for S76Backend {
 
 impl GetCurrentPowerProfile for S76Backend {
     async fn get_current_power_profile(&self) -> PowerProfile {
-        let Ok(daemon) = get_s76power_daemon_proxy().await else {
+        let Ok(daemon) = get_s76power_daemon_proxy(&self.connection).await else {
             tracing::error!("Problem while getting power profile.");
             return PowerProfile::Balanced;
         };
@@ -142,16 +171,10 @@ impl GetCurrentPowerProfile for S76Backend {
     }
 }
 
-async fn get_s76power_daemon_proxy<'a>() -> Result<s76powerdaemon::PowerDaemonProxy<'a>, ()> {
-    let connection = match Connection::system().await {
-        Ok(c) => c,
-        Err(e) => {
-            tracing::error!("zbus connection failed. {e}");
-            return Err(());
-        }
-    };
-
-    match s76powerdaemon::PowerDaemonProxy::new(&connection).await {
+async fn get_s76power_daemon_proxy<'a>(
+    connection: &Connection,
+) -> Result<s76powerdaemon::PowerDaemonProxy<'a>, ()> {
+    match s76powerdaemon::PowerDaemonProxy::new(connection).await {
         Ok(d) => Ok(d),
         Err(e) => {
             tracing::error!("Power daemon proxy can't be created. Is it installed? {e}");
@@ -160,13 +183,139 @@ async fn get_s76power_daemon_proxy<'a>() -> Result<s76powerdaemon::PowerDaemonPr
     }
 }
 
-pub struct PPBackend {}
+/// Graphics modes supported by system76-power on switchable graphics hardware.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GraphicsMode {
+    Integrated,
+    Hybrid,
+    Nvidia,
+    Compute,
+}
+
+impl GraphicsMode {
+    pub const ALL: [Self; 4] = [Self::Integrated, Self::Hybrid, Self::Nvidia, Self::Compute];
+
+    fn from_daemon(s: &str) -> Option<GraphicsMode> {
+        Some(match s {
+            "integrated" => Self::Integrated,
+            "hybrid" => Self::Hybrid,
+            "nvidia" => Self::Nvidia,
+            "compute" => Self::Compute,
+            _ => return None,
+        })
+    }
+
+    const fn as_daemon(self) -> &'static str {
+        match self {
+            Self::Integrated => "integrated",
+            Self::Hybrid => "hybrid",
+            Self::Nvidia => "nvidia",
+            Self::Compute => "compute",
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            Self::Integrated => fl!("graphics-mode", "integrated"),
+            Self::Hybrid => fl!("graphics-mode", "hybrid"),
+            Self::Nvidia => fl!("graphics-mode", "nvidia"),
+            Self::Compute => fl!("graphics-mode", "compute"),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Self::Integrated => fl!("graphics-mode", "integrated-desc"),
+            Self::Hybrid => fl!("graphics-mode", "hybrid-desc"),
+            Self::Nvidia => fl!("graphics-mode", "nvidia-desc"),
+            Self::Compute => fl!("graphics-mode", "compute-desc"),
+        }
+    }
+
+    /// Whether the discrete GPU may be powered off while in this mode.
+    pub fn has_discrete_power_control(self) -> bool {
+        matches!(self, Self::Hybrid | Self::Compute)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Graphics {
+    /// The mode configured in system76-power, which is used after the next restart.
+    pub mode: GraphicsMode,
+    /// Whether the discrete GPU is powered on.
+    pub discrete_power: bool,
+}
+
+impl Graphics {
+    /// Fetches the graphics state, if system76-power supports switching on this system.
+    pub async fn get() -> Option<Self> {
+        Self::from_connection(&system_connection().await?).await
+    }
+
+    pub async fn from_connection(connection: &Connection) -> Option<Self> {
+        let daemon = get_s76power_daemon_proxy(connection).await.ok()?;
+
+        if !daemon.get_switchable().await.unwrap_or(false) {
+            return None;
+        }
+
+        let mode = match daemon.get_graphics().await {
+            Ok(mode) => GraphicsMode::from_daemon(&mode)?,
+            Err(why) => {
+                tracing::error!(?why, "Problem while getting graphics mode.");
+                return None;
+            }
+        };
+
+        let discrete_power = daemon.get_graphics_power().await.unwrap_or(true);
+
+        Some(Self {
+            mode,
+            discrete_power,
+        })
+    }
+
+    /// Switches the graphics mode, which takes effect after a restart.
+    pub async fn set_mode(mode: GraphicsMode) -> Result<(), String> {
+        let connection = system_connection()
+            .await
+            .ok_or_else(|| String::from("system bus is not available"))?;
+
+        let daemon = get_s76power_daemon_proxy(&connection)
+            .await
+            .map_err(|()| String::from("system76-power is not available"))?;
+
+        daemon
+            .set_graphics(mode.as_daemon())
+            .await
+            .map_err(|why| why.to_string())
+    }
+
+    pub async fn set_discrete_power(power: bool) -> Result<(), String> {
+        let connection = system_connection()
+            .await
+            .ok_or_else(|| String::from("system bus is not available"))?;
+
+        let daemon = get_s76power_daemon_proxy(&connection)
+            .await
+            .map_err(|()| String::from("system76-power is not available"))?;
+
+        daemon
+            .set_graphics_power(power)
+            .await
+            .map_err(|why| why.to_string())
+    }
+}
+
+pub struct PPBackend {
+    connection: Connection,
+}
 
 impl PowerBackend for PPBackend {}
 
 impl SetPowerProfile for PPBackend {
     async fn set_power_profile(&self, profile: PowerProfile) {
-        let daemon = match get_power_profiles_proxy().await {
+        let daemon = match get_power_profiles_proxy(&self.connection).await {
             Ok(c) => c,
             Err(()) => {
                 tracing::error!("Problem while setting power profile.");
@@ -193,7 +342,7 @@ impl SetPowerProfile for PPBackend {
 
 impl GetCurrentPowerProfile for PPBackend {
     async fn get_current_power_profile(&self) -> PowerProfile {
-        let Ok(daemon) = get_power_profiles_proxy().await else {
+        let Ok(daemon) = get_power_profiles_proxy(&self.connection).await else {
             tracing::error!("Problem while getting power profile.");
             return PowerProfile::Balanced;
         };
@@ -211,16 +360,10 @@ impl GetCurrentPowerProfile for PPBackend {
     }
 }
 
-async fn get_power_profiles_proxy<'a>() -> Result<ppdaemon::PowerProfilesProxy<'a>, ()> {
-    let connection = match Connection::system().await {
-        Ok(c) => c,
-        Err(e) => {
-            tracing::error!("zbus connection failed. {e}");
-            return Err(());
-        }
-    };
-
-    match ppdaemon::PowerProfilesProxy::new(&connection).await {
+async fn get_power_profiles_proxy<'a>(
+    connection: &Connection,
+) -> Result<ppdaemon::PowerProfilesProxy<'a>, ()> {
+    match ppdaemon::PowerProfilesProxy::new(connection).await {
         Ok(d) => Ok(d),
         Err(e) => {
             tracing::error!("Power daemon proxy can't be created. Is it installed? {e}");
@@ -239,39 +382,32 @@ pub struct Battery {
     pub remaining_time: String,
 }
 
-async fn get_device_proxy<'a>() -> Result<upower_dbus::DeviceProxy<'a>, zbus::Error> {
-    let connection = match Connection::system().await {
-        Ok(c) => c,
-        Err(e) => {
-            tracing::error!("zbus connection failed. {e}");
-            return Err(e);
-        }
-    };
-
-    match upower_dbus::UPowerProxy::new(&connection).await {
-        Ok(p) => p.get_display_device().await,
-        Err(e) => Err(e),
-    }
+async fn get_device_proxy<'a>(
+    connection: &Connection,
+) -> Result<upower_dbus::DeviceProxy<'a>, zbus::Error> {
+    upower_dbus::UPowerProxy::new(connection)
+        .await?
+        .get_display_device()
+        .await
 }
 
-async fn get_on_battery_status() -> Result<bool, zbus::Error> {
-    let connection = match Connection::system().await {
-        Ok(c) => c,
-        Err(e) => {
-            tracing::error!("zbus connection failed. {e}");
-            return Err(e);
-        }
-    };
-
-    match upower_dbus::UPowerProxy::new(&connection).await {
-        Ok(p) => p.on_battery().await,
-        Err(e) => Err(e),
-    }
+async fn get_on_battery_status(connection: &Connection) -> Result<bool, zbus::Error> {
+    upower_dbus::UPowerProxy::new(connection)
+        .await?
+        .on_battery()
+        .await
 }
 
 impl Battery {
     pub async fn update_battery() -> Self {
-        let proxy = get_device_proxy().await;
+        match system_connection().await {
+            Some(connection) => Self::from_connection(&connection).await,
+            None => Battery::default(),
+        }
+    }
+
+    pub async fn from_connection(connection: &Connection) -> Self {
+        let proxy = get_device_proxy(connection).await;
 
         if let Ok(proxy) = proxy {
             let mut remaining_duration: Duration = Duration::default();
@@ -279,7 +415,7 @@ impl Battery {
             let (is_present, percentage, on_battery) = futures::join!(
                 proxy.is_present().map(Result::unwrap_or_default),
                 proxy.percentage().map(Result::unwrap_or_default),
-                get_on_battery_status().map(Result::unwrap_or_default)
+                get_on_battery_status(connection).map(Result::unwrap_or_default)
             );
 
             let percent = percentage.clamp(0.0, 100.0);
@@ -297,29 +433,7 @@ impl Battery {
                 }
             }
 
-            let battery_percent = if percent > 95.0 {
-                100
-            } else if percent > 80.0 {
-                90
-            } else if percent > 65.0 {
-                80
-            } else if percent > 35.0 {
-                50
-            } else if percent > 20.0 {
-                35
-            } else if percent > 14.0 {
-                20
-            } else if percent > 9.0 {
-                10
-            } else if percent > 5.0 {
-                5
-            } else {
-                0
-            };
-            let charging = if on_battery { "" } else { "charging-" };
-
-            let icon_name =
-                format!("cosmic-applet-battery-level-{battery_percent}-{charging}symbolic",);
+            let icon_name = Self::icon_name(percent, on_battery);
 
             let remaining_time = |duration: Duration| {
                 let total_seconds = duration.num_seconds();
@@ -347,4 +461,30 @@ impl Battery {
 
         Battery::default()
     }
+
+    /// Selects the battery icon for the charge level, bucketed to the levels the icon theme provides.
+    pub fn icon_name(percent: f64, on_battery: bool) -> String {
+        let battery_percent = if percent > 95.0 {
+            100
+        } else if percent > 80.0 {
+            90
+        } else if percent > 65.0 {
+            80
+        } else if percent > 35.0 {
+            50
+        } else if percent > 20.0 {
+            35
+        } else if percent > 14.0 {
+            20
+        } else if percent > 9.0 {
+            10
+        } else if percent > 5.0 {
+            5
+        } else {
+            0
+        };
+        let charging = if on_battery { "" } else { "charging-" };
+
+        format!("cosmic-applet-battery-level-{battery_percent}-{charging}symbolic")
+    }
 }
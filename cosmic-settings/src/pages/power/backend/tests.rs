@@ -0,0 +1,119 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::fake::{self, Device, Services};
+use super::*;
+
+#[tokio::test]
+async fn prefers_system76_power() {
+    let (_server, client) = fake::connect(Services {
+        s76_profile: Some("Performance"),
+        ppd_profile: Some("balanced"),
+        ..Services::default()
+    })
+    .await
+    .unwrap();
+
+    let backend = detect_backend(&client).await;
+
+    assert!(matches!(backend, Some(PowerBackendEnum::S76(_))));
+    assert_eq!(
+        backend.unwrap().get_current_power_profile().await,
+        PowerProfile::Performance
+    );
+}
+
+#[tokio::test]
+async fn falls_back_to_power_profiles_daemon() {
+    let (_server, client) = fake::connect(Services {
+        ppd_profile: Some("power-saver"),
+        ..Services::default()
+    })
+    .await
+    .unwrap();
+
+    let backend = detect_backend(&client).await;
+
+    assert!(matches!(backend, Some(PowerBackendEnum::PP(_))));
+
+    let backend = backend.unwrap();
+    assert_eq!(
+        backend.get_current_power_profile().await,
+        PowerProfile::Battery
+    );
+
+    backend.set_power_profile(PowerProfile::Performance).await;
+    assert_eq!(
+        backend.get_current_power_profile().await,
+        PowerProfile::Performance
+    );
+}
+
+#[tokio::test]
+async fn no_backend_without_daemons() {
+    let (_server, client) = fake::connect(Services::default()).await.unwrap();
+
+    assert!(detect_backend(&client).await.is_none());
+}
+
+#[tokio::test]
+async fn parses_discharging_battery() {
+    let (_server, client) = fake::connect(Services {
+        battery: Some(Device {
+            on_battery: true,
+            is_present: true,
+            percentage: 42.0,
+            time_to_empty: 3600,
+            time_to_full: 0,
+        }),
+        ..Services::default()
+    })
+    .await
+    .unwrap();
+
+    let battery = Battery::from_connection(&client).await;
+
+    assert!(battery.is_present);
+    assert!(battery.on_battery);
+    assert_eq!(battery.percent, 42.0);
+    assert_eq!(battery.remaining_duration, Duration::hours(1));
+    assert_eq!(battery.icon_name, "cosmic-applet-battery-level-50-symbolic");
+}
+
+#[tokio::test]
+async fn missing_upower_is_no_battery() {
+    let (_server, client) = fake::connect(Services::default()).await.unwrap();
+
+    assert!(!Battery::from_connection(&client).await.is_present);
+}
+
+#[test]
+fn battery_icon_levels() {
+    let levels = [
+        (100.0, 100),
+        (96.0, 100),
+        (95.0, 90),
+        (81.0, 90),
+        (66.0, 80),
+        (36.0, 50),
+        (21.0, 35),
+        (15.0, 20),
+        (10.0, 10),
+        (6.0, 5),
+        (5.0, 0),
+        (0.0, 0),
+    ];
+
+    for (percent, level) in levels {
+        assert_eq!(
+            Battery::icon_name(percent, true),
+            format!("cosmic-applet-battery-level-{level}-symbolic"),
+            "{percent}%"
+        );
+    }
+
+    assert_eq!(
+        Battery::icon_name(100.0, false),
+        "cosmic-applet-battery-level-100-charging-symbolic"
+    );
+}
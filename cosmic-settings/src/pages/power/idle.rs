@@ -0,0 +1,126 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::cosmic_config::{self, ConfigGet, ConfigSet};
+
+/// Config consumed by cosmic-idle to blank the screen and suspend the system.
+///
+/// cosmic-idle turns off the screen after the same delay on AC and on battery, and only
+/// distinguishes them for suspend.
+pub const NAME: &str = "com.system76.CosmicIdle";
+
+/// Delays offered in the dropdowns, in milliseconds. `None` disables the action.
+pub const DELAYS: &[Option<u32>] = &[
+    Some(60_000),
+    Some(2 * 60_000),
+    Some(5 * 60_000),
+    Some(10 * 60_000),
+    Some(15 * 60_000),
+    Some(20 * 60_000),
+    Some(30 * 60_000),
+    Some(45 * 60_000),
+    Some(60 * 60_000),
+    Some(90 * 60_000),
+    Some(120 * 60_000),
+    None,
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Delay {
+    ScreenOff,
+    SuspendOnAc,
+    SuspendOnBattery,
+}
+
+impl Delay {
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::ScreenOff => "screen_off_time",
+            Self::SuspendOnAc => "suspend_on_ac_time",
+            Self::SuspendOnBattery => "suspend_on_battery_time",
+        }
+    }
+
+    const fn default_value(self) -> Option<u32> {
+        match self {
+            Self::ScreenOff => Some(15 * 60_000),
+            Self::SuspendOnAc => None,
+            Self::SuspendOnBattery => Some(15 * 60_000),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IdleConfig {
+    pub screen_off: Option<u32>,
+    pub suspend_on_ac: Option<u32>,
+    pub suspend_on_battery: Option<u32>,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            screen_off: Delay::ScreenOff.default_value(),
+            suspend_on_ac: Delay::SuspendOnAc.default_value(),
+            suspend_on_battery: Delay::SuspendOnBattery.default_value(),
+        }
+    }
+}
+
+impl IdleConfig {
+    pub fn load(config: &cosmic_config::Config) -> Self {
+        let get = |delay: Delay| {
+            config
+                .get::<Option<u32>>(delay.key())
+                .unwrap_or_else(|why| {
+                    if !matches!(why, cosmic_config::Error::NoConfigDirectory) {
+                        tracing::error!(?why, "failed to read config '{}'", delay.key());
+                    }
+
+                    delay.default_value()
+                })
+        };
+
+        Self {
+            screen_off: get(Delay::ScreenOff),
+            suspend_on_ac: get(Delay::SuspendOnAc),
+            suspend_on_battery: get(Delay::SuspendOnBattery),
+        }
+    }
+
+    pub fn get(&self, delay: Delay) -> Option<u32> {
+        match delay {
+            Delay::ScreenOff => self.screen_off,
+            Delay::SuspendOnAc => self.suspend_on_ac,
+            Delay::SuspendOnBattery => self.suspend_on_battery,
+        }
+    }
+
+    pub fn set(&mut self, config: &cosmic_config::Config, delay: Delay, value: Option<u32>) {
+        match delay {
+            Delay::ScreenOff => self.screen_off = value,
+            Delay::SuspendOnAc => self.suspend_on_ac = value,
+            Delay::SuspendOnBattery => self.suspend_on_battery = value,
+        }
+
+        if let Err(why) = config.set(delay.key(), value) {
+            tracing::error!(?why, "failed to set config '{}'", delay.key());
+        }
+    }
+}
+
+/// Index of the dropdown option matching a delay, if it is one of the presets.
+pub fn delay_position(value: Option<u32>) -> Option<usize> {
+    DELAYS.iter().position(|delay| *delay == value)
+}
+
+pub fn delay_labels() -> Vec<String> {
+    DELAYS
+        .iter()
+        .map(|delay| match delay {
+            Some(ms) if ms % (60 * 60_000) == 0 => fl!("x-hours", number = ms / (60 * 60_000)),
+            Some(ms) => fl!("x-minutes", number = ms / 60_000),
+            None => fl!("never"),
+        })
+        .collect()
+}
@@ -0,0 +1,217 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use zbus::{proxy, Connection};
+
+/// Writes the drop-in which overrides the distribution's logind.conf, and reloads logind.
+///
+/// Its polkit action, `com.system76.CosmicSettings.logind`, only allows running this helper,
+/// which only accepts the settings offered here.
+const HELPER: &str = "/usr/libexec/cosmic-settings-logind-helper";
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Manager {
+    fn can_hibernate(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn handle_lid_switch(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn handle_lid_switch_docked(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn handle_lid_switch_external_power(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn handle_power_key(&self) -> zbus::Result<String>;
+}
+
+/// Actions that logind can take when the lid is closed or the power button is pressed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HandleAction {
+    Ignore,
+    Lock,
+    Suspend,
+    Hibernate,
+    PowerOff,
+}
+
+impl HandleAction {
+    pub const ALL: [Self; 5] = [
+        Self::Suspend,
+        Self::Hibernate,
+        Self::Lock,
+        Self::PowerOff,
+        Self::Ignore,
+    ];
+
+    fn from_logind(value: &str) -> Option<Self> {
+        Some(match value {
+            "ignore" => Self::Ignore,
+            "lock" => Self::Lock,
+            "suspend" => Self::Suspend,
+            "hibernate" => Self::Hibernate,
+            "poweroff" => Self::PowerOff,
+            _ => return None,
+        })
+    }
+
+    const fn as_logind(self) -> &'static str {
+        match self {
+            Self::Ignore => "ignore",
+            Self::Lock => "lock",
+            Self::Suspend => "suspend",
+            Self::Hibernate => "hibernate",
+            Self::PowerOff => "poweroff",
+        }
+    }
+
+    pub fn title(self) -> String {
+        match self {
+            Self::Ignore => fl!("power-action", "nothing"),
+            Self::Lock => fl!("power-action", "lock"),
+            Self::Suspend => fl!("power-action", "suspend"),
+            Self::Hibernate => fl!("power-action", "hibernate"),
+            Self::PowerOff => fl!("power-action", "power-off"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Switch {
+    LidOnBattery,
+    LidOnAc,
+    LidDocked,
+    PowerKey,
+}
+
+impl Switch {
+    const fn key(self) -> &'static str {
+        match self {
+            Self::LidOnBattery => "HandleLidSwitch",
+            Self::LidOnAc => "HandleLidSwitchExternalPower",
+            Self::LidDocked => "HandleLidSwitchDocked",
+            Self::PowerKey => "HandlePowerKey",
+        }
+    }
+}
+
+/// The actions logind currently takes for each switch.
+///
+/// `None` means logind is configured with an action not offered by settings.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Actions {
+    pub lid_on_battery: Option<HandleAction>,
+    pub lid_on_ac: Option<HandleAction>,
+    pub lid_docked: Option<HandleAction>,
+    pub power_key: Option<HandleAction>,
+    /// Whether logind is able to hibernate the system.
+    pub can_hibernate: bool,
+}
+
+impl Actions {
+    pub async fn load() -> Option<Self> {
+        let connection = match Connection::system().await {
+            Ok(c) => c,
+            Err(why) => {
+                tracing::error!(?why, "zbus connection failed");
+                return None;
+            }
+        };
+
+        let manager = match ManagerProxy::new(&connection).await {
+            Ok(m) => m,
+            Err(why) => {
+                tracing::error!(?why, "logind manager proxy can't be created");
+                return None;
+            }
+        };
+
+        let (lid_on_battery, lid_on_ac, lid_docked, power_key, can_hibernate) = futures::join!(
+            manager.handle_lid_switch(),
+            manager.handle_lid_switch_external_power(),
+            manager.handle_lid_switch_docked(),
+            manager.handle_power_key(),
+            manager.can_hibernate(),
+        );
+
+        let parse = |value: zbus::Result<String>| {
+            value
+                .ok()
+                .and_then(|value| HandleAction::from_logind(&value))
+        };
+
+        Some(Self {
+            lid_on_battery: parse(lid_on_battery),
+            lid_on_ac: parse(lid_on_ac),
+            lid_docked: parse(lid_docked),
+            power_key: parse(power_key),
+            // "challenge" means that hibernating requires authentication, which is still possible.
+            can_hibernate: can_hibernate.is_ok_and(|value| value == "yes" || value == "challenge"),
+        })
+    }
+
+    /// Actions which may be chosen for a switch.
+    pub fn offered(&self) -> Vec<HandleAction> {
+        HandleAction::ALL
+            .into_iter()
+            .filter(|action| *action != HandleAction::Hibernate || self.can_hibernate)
+            .collect()
+    }
+
+    pub fn get(&self, switch: Switch) -> Option<HandleAction> {
+        match switch {
+            Switch::LidOnBattery => self.lid_on_battery,
+            Switch::LidOnAc => self.lid_on_ac,
+            Switch::LidDocked => self.lid_docked,
+            Switch::PowerKey => self.power_key,
+        }
+    }
+
+    pub fn set(&mut self, switch: Switch, action: HandleAction) {
+        let field = match switch {
+            Switch::LidOnBattery => &mut self.lid_on_battery,
+            Switch::LidOnAc => &mut self.lid_on_ac,
+            Switch::LidDocked => &mut self.lid_docked,
+            Switch::PowerKey => &mut self.power_key,
+        };
+
+        *field = Some(action);
+    }
+
+    /// Settings of the drop-in, as `key=action` arguments for the helper.
+    fn drop_in(&self) -> Vec<String> {
+        [
+            Switch::LidOnBattery,
+            Switch::LidOnAc,
+            Switch::LidDocked,
+            Switch::PowerKey,
+        ]
+        .into_iter()
+        .filter_map(|switch| {
+            self.get(switch)
+                .map(|action| format!("{}={}", switch.key(), action.as_logind()))
+        })
+        .collect()
+    }
+
+    /// Writes the logind drop-in through the helper, which asks logind to reload it.
+    pub async fn apply(self) -> Result<(), String> {
+        let status = tokio::process::Command::new("pkexec")
+            .arg(HELPER)
+            .args(self.drop_in())
+            .status()
+            .await
+            .map_err(|why| why.to_string())?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("logind helper exited with {status}"))
+        }
+    }
+}
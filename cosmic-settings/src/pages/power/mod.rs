@@ -1,19 +1,85 @@
 mod backend;
+mod idle;
+mod logind;
+pub mod rules;
 
 use self::backend::{GetCurrentPowerProfile, SetPowerProfile};
-use backend::{Battery, PowerProfile};
+use self::idle::{Delay, IdleConfig};
+use self::logind::{HandleAction, Switch};
+use self::rules::{LowBattery, ProfileRules};
+use backend::{Battery, Graphics, GraphicsMode, PowerProfile};
 
 use chrono::TimeDelta;
+use cosmic::cosmic_config;
 use cosmic::iced_widget::row;
 use cosmic::widget::{self, column, radio, settings, text};
-use cosmic::Apply;
+use cosmic::{Apply, Command};
 use cosmic_settings_page::{self as page, section, Section};
 use slab::Slab;
 use slotmap::SlotMap;
 
-#[derive(Default)]
 pub struct Page {
     battery: Battery,
+    /// Graphics switching state, or `None` if system76-power can't switch graphics.
+    graphics: Option<Graphics>,
+    /// Mode the system is running with, which is the one configured when settings started.
+    graphics_running: Option<GraphicsMode>,
+    idle_config: Option<cosmic_config::Config>,
+    idle: IdleConfig,
+    /// Current logind switch actions, or `None` if logind is unreachable.
+    logind: Option<logind::Actions>,
+    settings_config: Option<cosmic_config::Config>,
+    pub rules: ProfileRules,
+    delay_labels: Vec<String>,
+    /// Actions offered for the lid and power button, in the order of their labels.
+    actions: Vec<HandleAction>,
+    action_labels: Vec<String>,
+    rule_profile_labels: Vec<String>,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        let idle_config = cosmic_config::Config::new(idle::NAME, 1)
+            .inspect_err(|why| tracing::error!(?why, "failed to get idle config"))
+            .ok();
+
+        let idle = idle_config
+            .as_ref()
+            .map(IdleConfig::load)
+            .unwrap_or_default();
+
+        let settings_config = cosmic_config::Config::new(crate::config::NAME, 1)
+            .inspect_err(|why| tracing::error!(?why, "failed to get settings config"))
+            .ok();
+
+        let rules = settings_config
+            .as_ref()
+            .map(ProfileRules::load)
+            .unwrap_or_default();
+
+        let rule_profile_labels = std::iter::once(fl!("power-rules", "unchanged"))
+            .chain(
+                backend::get_power_profiles()
+                    .iter()
+                    .map(PowerProfile::title),
+            )
+            .collect();
+
+        Self {
+            battery: Battery::default(),
+            graphics: None,
+            graphics_running: None,
+            idle_config,
+            idle,
+            logind: None,
+            settings_config,
+            rules,
+            delay_labels: idle::delay_labels(),
+            actions: Vec::new(),
+            action_labels: Vec::new(),
+            rule_profile_labels,
+        }
+    }
 }
 
 impl page::Page<crate::pages::Message> for Page {
@@ -30,6 +96,10 @@ impl page::Page<crate::pages::Message> for Page {
         Some(vec![
             sections.insert(battery_info()),
             sections.insert(profiles()),
+            sections.insert(profile_rules()),
+            sections.insert(graphics()),
+            sections.insert(screen_and_suspend()),
+            sections.insert(lid_and_power_button()),
         ])
     }
 
@@ -38,34 +108,194 @@ impl page::Page<crate::pages::Message> for Page {
         _page: cosmic_settings_page::Entity,
         _sender: tokio::sync::mpsc::Sender<crate::pages::Message>,
     ) -> cosmic::Command<crate::pages::Message> {
-        cosmic::command::future(async move {
-            let battery = Battery::update_battery().await;
-            Message::UpdateBattery(battery)
-        })
+        Command::batch(vec![
+            cosmic::command::future(async move {
+                let battery = Battery::update_battery().await;
+                Message::UpdateBattery(battery)
+            }),
+            cosmic::command::future(
+                async move { Message::UpdateLogind(logind::Actions::load().await) },
+            ),
+            cosmic::command::future(async move { Message::UpdateGraphics(Graphics::get().await) }),
+        ])
         .map(crate::pages::Message::Power)
     }
 }
 
 #[derive(Clone, Debug)]
 pub enum Message {
+    GraphicsApplied(Result<(), String>),
+    GraphicsModeApplied(Result<(), String>),
+    LogindApplied(Result<(), String>),
     PowerProfileChange(PowerProfile),
+    RuleLowBatteryProfile(usize),
+    RuleLowBatteryThreshold(u8),
+    RuleOnAc(usize),
+    RuleOnBattery(usize),
+    SetDelay(Delay, usize),
+    SetDiscreteGraphicsPower(bool),
+    SetGraphicsMode(GraphicsMode),
+    SetSwitchAction(Switch, usize),
     UpdateBattery(Battery),
+    UpdateGraphics(Option<Graphics>),
+    UpdateLogind(Option<logind::Actions>),
 }
 
 impl Page {
-    pub fn update(&mut self, message: Message) {
-        let runtime = tokio::runtime::Runtime::new().unwrap();
-
-        let backend = runtime.block_on(backend::get_backend());
-
+    pub fn update(&mut self, message: Message) -> Command<crate::app::Message> {
         match message {
             Message::PowerProfileChange(p) => {
-                if let Some(b) = backend {
+                let runtime = tokio::runtime::Runtime::new().unwrap();
+
+                if let Some(b) = runtime.block_on(backend::get_backend()) {
                     runtime.block_on(b.set_power_profile(p));
                 }
             }
+
             Message::UpdateBattery(battery) => self.battery = battery,
+
+            Message::RuleOnBattery(position) => {
+                self.rules.on_battery = rule_profile(position);
+                self.save_rules();
+            }
+
+            Message::RuleOnAc(position) => {
+                self.rules.on_ac = rule_profile(position);
+                self.save_rules();
+            }
+
+            Message::RuleLowBatteryProfile(position) => {
+                self.rules.low_battery = rule_profile(position).map(|profile| LowBattery {
+                    profile,
+                    ..self.rules.low_battery.unwrap_or_default()
+                });
+                self.save_rules();
+            }
+
+            Message::RuleLowBatteryThreshold(threshold) => {
+                if let Some(low_battery) = self.rules.low_battery.as_mut() {
+                    low_battery.threshold = threshold;
+                    self.save_rules();
+                }
+            }
+
+            Message::SetDelay(delay, position) => {
+                if let (Some(config), Some(value)) =
+                    (self.idle_config.as_ref(), idle::DELAYS.get(position))
+                {
+                    self.idle.set(config, delay, *value);
+                }
+            }
+
+            Message::SetSwitchAction(switch, position) => {
+                let (Some(actions), Some(action)) =
+                    (self.logind.as_mut(), self.actions.get(position))
+                else {
+                    return Command::none();
+                };
+
+                actions.set(switch, *action);
+                let actions = *actions;
+
+                return cosmic::command::future(async move {
+                    crate::pages::Message::Power(Message::LogindApplied(actions.apply().await))
+                });
+            }
+
+            Message::LogindApplied(result) => {
+                if let Err(why) = result {
+                    tracing::error!(why, "failed to apply logind settings");
+                }
+
+                // Reload so that the page reflects what logind actually uses.
+                return cosmic::command::future(async move {
+                    crate::pages::Message::Power(Message::UpdateLogind(
+                        logind::Actions::load().await,
+                    ))
+                });
+            }
+
+            Message::UpdateLogind(actions) => {
+                self.actions = actions.map(|a| a.offered()).unwrap_or_default();
+                self.action_labels = self.actions.iter().map(|a| a.title()).collect();
+                self.logind = actions;
+            }
+
+            Message::SetGraphicsMode(mode) => {
+                if self.graphics.as_ref().is_some_and(|g| g.mode == mode) {
+                    return Command::none();
+                }
+
+                return cosmic::command::future(async move {
+                    crate::pages::Message::Power(Message::GraphicsModeApplied(
+                        Graphics::set_mode(mode).await,
+                    ))
+                });
+            }
+
+            Message::GraphicsModeApplied(result) => {
+                if let Err(why) = result {
+                    tracing::error!(why, "failed to switch graphics mode");
+                }
+
+                return self.reload_graphics();
+            }
+
+            Message::SetDiscreteGraphicsPower(power) => {
+                return cosmic::command::future(async move {
+                    crate::pages::Message::Power(Message::GraphicsApplied(
+                        Graphics::set_discrete_power(power).await,
+                    ))
+                });
+            }
+
+            Message::GraphicsApplied(result) => {
+                if let Err(why) = result {
+                    tracing::error!(why, "failed to set discrete graphics power");
+                }
+
+                return self.reload_graphics();
+            }
+
+            Message::UpdateGraphics(graphics) => {
+                if self.graphics_running.is_none() {
+                    self.graphics_running = graphics.map(|g| g.mode);
+                }
+
+                self.graphics = graphics;
+            }
         };
+
+        Command::none()
+    }
+
+    fn reload_graphics(&self) -> Command<crate::app::Message> {
+        cosmic::command::future(async move {
+            crate::pages::Message::Power(Message::UpdateGraphics(Graphics::get().await))
+        })
+    }
+
+    fn save_rules(&self) {
+        if let Some(config) = self.settings_config.as_ref() {
+            self.rules.save(config);
+        }
+    }
+}
+
+/// Maps a rule dropdown position to a profile, where the first option leaves the profile unchanged.
+fn rule_profile(position: usize) -> Option<PowerProfile> {
+    position
+        .checked_sub(1)
+        .and_then(|position| backend::get_power_profiles().get(position).copied())
+}
+
+fn rule_position(profile: Option<PowerProfile>) -> Option<usize> {
+    match profile {
+        Some(profile) => backend::get_power_profiles()
+            .iter()
+            .position(|p| *p == profile)
+            .map(|position| position + 1),
+        None => Some(0),
     }
 }
 
@@ -139,4 +369,203 @@ fn profiles() -> Section<crate::pages::Message> {
         })
 }
 
+fn profile_rules() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let on_battery = descriptions.insert(fl!("power-rules", "on-battery"));
+    let low_battery = descriptions.insert(fl!("power-rules", "low-battery"));
+    let threshold = descriptions.insert(fl!("power-rules", "threshold"));
+    let on_ac = descriptions.insert(fl!("power-rules", "on-ac"));
+    let rules_desc = descriptions.insert(fl!("power-rules", "desc"));
+
+    Section::default()
+        .title(fl!("power-rules"))
+        .descriptions(descriptions)
+        .show_while::<Page>(|page| page.battery.is_present)
+        .view::<Page>(move |_binder, page, section| {
+            let descriptions = &section.descriptions;
+
+            let rule = |description: usize,
+                        profile: Option<PowerProfile>,
+                        on_select: fn(usize) -> Message| {
+                settings::item::builder(&descriptions[description]).control(widget::dropdown(
+                    &page.rule_profile_labels,
+                    rule_position(profile),
+                    on_select,
+                ))
+            };
+
+            let mut section = settings::view_section(&section.title)
+                .add(rule(
+                    on_battery,
+                    page.rules.on_battery,
+                    Message::RuleOnBattery,
+                ))
+                .add(rule(
+                    low_battery,
+                    page.rules.low_battery.map(|low| low.profile),
+                    Message::RuleLowBatteryProfile,
+                ));
+
+            if let Some(low) = page.rules.low_battery {
+                section = section.add(
+                    settings::item::builder(&descriptions[threshold]).control(
+                        row!(
+                            widget::slider(5..=50, low.threshold, Message::RuleLowBatteryThreshold)
+                                .step(5u8),
+                            text::body(format!("{}%", low.threshold)),
+                        )
+                        .spacing(8),
+                    ),
+                );
+            }
+
+            let section = section.add(rule(on_ac, page.rules.on_ac, Message::RuleOnAc));
+
+            column::with_capacity(2)
+                .spacing(8)
+                .push(section)
+                .push(text::caption(&*descriptions[rules_desc]))
+                .apply(cosmic::Element::from)
+                .map(crate::pages::Message::Power)
+        })
+}
+
+fn graphics() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let discrete_power = descriptions.insert(fl!("graphics-mode", "discrete-power"));
+    let restart_required = descriptions.insert(fl!("graphics-mode", "restart-required"));
+
+    Section::default()
+        .title(fl!("graphics-mode"))
+        .descriptions(descriptions)
+        .show_while::<Page>(|page| page.graphics.is_some())
+        .view::<Page>(move |_binder, page, section| {
+            let descriptions = &section.descriptions;
+            let mut section = settings::view_section(&section.title);
+
+            let (Some(graphics), Some(running)) = (page.graphics, page.graphics_running) else {
+                return section
+                    .apply(cosmic::Element::from)
+                    .map(crate::pages::Message::Power);
+            };
+
+            if graphics.mode != running {
+                section = section.add(settings::item_row(vec![text::body(
+                    &descriptions[restart_required],
+                )
+                .into()]));
+            }
+
+            section = GraphicsMode::ALL
+                .into_iter()
+                .map(|mode| {
+                    settings::item_row(vec![radio(
+                        column::with_capacity(2)
+                            .push(text::body(mode.title()))
+                            .push(text::caption(mode.description())),
+                        mode,
+                        Some(graphics.mode),
+                        Message::SetGraphicsMode,
+                    )
+                    .into()])
+                })
+                .fold(section, settings::Section::add);
+
+            // The discrete GPU is only powered on demand by the mode that is running.
+            if running.has_discrete_power_control() {
+                section = section.add(settings::item(
+                    &descriptions[discrete_power],
+                    widget::toggler(
+                        None,
+                        graphics.discrete_power,
+                        Message::SetDiscreteGraphicsPower,
+                    ),
+                ));
+            }
+
+            section
+                .apply(cosmic::Element::from)
+                .map(crate::pages::Message::Power)
+        })
+}
+
+fn screen_and_suspend() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let screen_off = descriptions.insert(fl!("power-idle", "screen-off"));
+    let suspend_ac = descriptions.insert(fl!("power-idle", "suspend-ac"));
+    let suspend_battery = descriptions.insert(fl!("power-idle", "suspend-battery"));
+
+    Section::default()
+        .title(fl!("power-idle"))
+        .descriptions(descriptions)
+        .view::<Page>(move |_binder, page, section| {
+            let descriptions = &section.descriptions;
+
+            let delay = |description: usize, delay: Delay| {
+                settings::item::builder(&descriptions[description]).control(widget::dropdown(
+                    &page.delay_labels,
+                    idle::delay_position(page.idle.get(delay)),
+                    move |position| Message::SetDelay(delay, position),
+                ))
+            };
+
+            let mut section = settings::view_section(&section.title)
+                .add(delay(screen_off, Delay::ScreenOff))
+                .add(delay(suspend_ac, Delay::SuspendOnAc));
+
+            if page.battery.is_present {
+                section = section.add(delay(suspend_battery, Delay::SuspendOnBattery));
+            }
+
+            section
+                .apply(cosmic::Element::from)
+                .map(crate::pages::Message::Power)
+        })
+}
+
+fn lid_and_power_button() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let lid_battery = descriptions.insert(fl!("power-buttons", "lid-battery"));
+    let lid_ac = descriptions.insert(fl!("power-buttons", "lid-ac"));
+    let lid_docked = descriptions.insert(fl!("power-buttons", "lid-docked"));
+    let power_key = descriptions.insert(fl!("power-buttons", "power-button"));
+
+    Section::default()
+        .title(fl!("power-buttons"))
+        .descriptions(descriptions)
+        .show_while::<Page>(|page| page.logind.is_some())
+        .view::<Page>(move |_binder, page, section| {
+            let descriptions = &section.descriptions;
+            let actions = page.logind.unwrap_or_default();
+
+            let action = |description: usize, switch: Switch| {
+                settings::item::builder(&descriptions[description]).control(widget::dropdown(
+                    &page.action_labels,
+                    actions
+                        .get(switch)
+                        .and_then(|action| page.actions.iter().position(|a| *a == action)),
+                    move |position| Message::SetSwitchAction(switch, position),
+                ))
+            };
+
+            let mut section = settings::view_section(&section.title);
+
+            if page.battery.is_present {
+                section = section
+                    .add(action(lid_battery, Switch::LidOnBattery))
+                    .add(action(lid_ac, Switch::LidOnAc))
+                    .add(action(lid_docked, Switch::LidDocked));
+            }
+
+            section
+                .add(action(power_key, Switch::PowerKey))
+                .apply(cosmic::Element::from)
+                .map(crate::pages::Message::Power)
+        })
+}
+
 impl page::AutoBind<crate::pages::Message> for Page {}
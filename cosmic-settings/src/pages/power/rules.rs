@@ -0,0 +1,142 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::any::TypeId;
+
+use cosmic::cosmic_config::{self, ConfigGet, ConfigSet};
+use cosmic::iced::{
+    self,
+    futures::{channel::mpsc::Sender, future, SinkExt, StreamExt},
+};
+use serde::{Deserialize, Serialize};
+use zbus::Connection;
+
+use super::backend::{self, Battery, PowerProfile, SetPowerProfile};
+
+const RULES: &str = "power-profile-rules";
+
+/// Rules for automatically switching the power profile on power source changes.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ProfileRules {
+    /// Profile to switch to when unplugged.
+    pub on_battery: Option<PowerProfile>,
+    /// Profile to switch to when the battery is at or below a threshold.
+    pub low_battery: Option<LowBattery>,
+    /// Profile to switch to when plugged in.
+    pub on_ac: Option<PowerProfile>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct LowBattery {
+    pub threshold: u8,
+    pub profile: PowerProfile,
+}
+
+impl Default for LowBattery {
+    fn default() -> Self {
+        Self {
+            threshold: 20,
+            profile: PowerProfile::Battery,
+        }
+    }
+}
+
+impl ProfileRules {
+    pub fn load(config: &cosmic_config::Config) -> Self {
+        config.get(RULES).unwrap_or_else(|why| {
+            if !matches!(why, cosmic_config::Error::NoConfigDirectory) {
+                tracing::error!(?why, "failed to read config '{RULES}'");
+            }
+
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, config: &cosmic_config::Config) {
+        if let Err(why) = config.set(RULES, self) {
+            tracing::error!(?why, "failed to set config '{RULES}'");
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.on_battery.is_none() && self.low_battery.is_none() && self.on_ac.is_none()
+    }
+
+    /// The profile that the rules require for the given power state, if any.
+    pub fn profile_for(&self, on_battery: bool, percent: f64) -> Option<PowerProfile> {
+        if !on_battery {
+            return self.on_ac;
+        }
+
+        match self.low_battery {
+            Some(low) if percent <= f64::from(low.threshold) => Some(low.profile),
+            _ => self.on_battery,
+        }
+    }
+}
+
+/// Applies the rules whenever the power source or battery level changes.
+///
+/// Settings evaluates the rules itself, so they are only followed while it is running. The
+/// profile which the rules require when the subscription starts is not applied, so that
+/// editing the rules doesn't override a profile which was chosen by hand.
+///
+/// Emits the refreshed battery status after every change.
+pub fn subscription(rules: ProfileRules) -> iced::Subscription<Battery> {
+    struct ProfileRulesSubscription;
+
+    iced::subscription::channel(
+        (TypeId::of::<ProfileRulesSubscription>(), rules.clone()),
+        1,
+        |tx| async move {
+            if !rules.is_empty() {
+                if let Err(why) = watch(rules, tx).await {
+                    tracing::error!(?why, "power profile rules subscription error");
+                }
+            }
+
+            future::pending().await
+        },
+    )
+}
+
+async fn watch(rules: ProfileRules, mut tx: Sender<Battery>) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let upower = upower_dbus::UPowerProxy::new(&connection).await?;
+    let device = upower.get_display_device().await?;
+
+    let mut on_battery_changed = upower.receive_on_battery_changed().await;
+    let mut percentage_changed = device.receive_percentage_changed().await;
+
+    let mut applied = rules.profile_for(upower.on_battery().await?, device.percentage().await?);
+
+    loop {
+        let on_battery = upower.on_battery().await?;
+        let percent = device.percentage().await?;
+
+        let profile = rules.profile_for(on_battery, percent);
+
+        if profile != applied {
+            if let (Some(profile), Some(backend)) =
+                (profile, backend::detect_backend(&connection).await)
+            {
+                backend.set_power_profile(profile).await;
+            }
+
+            applied = profile;
+        }
+
+        if tx
+            .send(Battery::from_connection(&connection).await)
+            .await
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        tokio::select! {
+            _ = on_battery_changed.next() => (),
+            _ = percentage_changed.next() => (),
+        }
+    }
+}
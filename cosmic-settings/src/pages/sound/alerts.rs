@@ -0,0 +1,536 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Alert volume and the freedesktop sound theme used for event sounds.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use cosmic::cosmic_config;
+use cosmic::widget::{self, settings};
+use cosmic::{Apply, Command, Element};
+use cosmic_settings_page::Section;
+use libpulse_binding::{
+    proplist::{properties, Proplist},
+    sample::{Format, Spec},
+    stream::{self, Stream},
+    volume::ChannelVolumes,
+};
+use slab::Slab;
+
+use super::playback::{self, failed};
+use super::server::{self, Client, Connection, Error};
+use super::{get, set};
+use crate::app;
+
+/// Name of the sound theme used for event sounds.
+pub const SOUND_THEME: &str = "sound_theme";
+/// Whether sounds are played as feedback for interacting with the desktop, such as clicks.
+pub const UI_SOUNDS: &str = "ui_sounds";
+
+/// The theme which every sound theme falls back to.
+const FALLBACK_THEME: &str = "freedesktop";
+
+const EXTENSIONS: &[&str] = &["oga", "ogg", "wav"];
+
+/// Events which can be previewed.
+const EVENTS: &[&str] = &[
+    "bell",
+    "message-new-instant",
+    "dialog-warning",
+    "complete",
+    "device-added",
+    "device-removed",
+    "power-plug",
+    "power-unplug",
+    "audio-volume-change",
+    "screen-capture",
+    "trash-empty",
+];
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    /// Change the alert volume shown while dragging.
+    Volume(u32),
+    /// Apply the alert volume once the slider is released.
+    VolumeApply,
+    /// The alert volume known to the sound server, if it was ever set.
+    VolumeLoaded(Option<u32>),
+    /// Select a sound theme.
+    Theme(usize),
+    /// Turn sounds for interacting with the desktop on or off.
+    UiSounds(bool),
+    /// Play the sound of an event.
+    Preview(&'static str),
+    /// An event sound finished playing, or failed to.
+    Previewed(Result<(), String>),
+}
+
+/// A sound theme, from an `index.theme` in a `sounds` data directory.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub id: String,
+    pub name: String,
+    inherits: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct Alerts {
+    themes: Vec<Theme>,
+    theme_names: Vec<String>,
+    active_theme: Option<usize>,
+    ui_sounds: bool,
+    volume: u32,
+    volume_text: String,
+    /// Events with a sound in the active theme which can be previewed, and their labels.
+    events: Vec<(&'static str, String)>,
+    /// Why the last preview failed to play.
+    error: Option<String>,
+}
+
+impl Alerts {
+    /// Reads the sound themes and the alert settings.
+    pub fn load(&mut self, config: Option<&cosmic_config::Config>) {
+        self.themes = themes();
+        self.theme_names = self.themes.iter().map(|theme| theme.name.clone()).collect();
+        self.set_volume(100);
+
+        let theme = config
+            .map(|config| get(config, SOUND_THEME, FALLBACK_THEME.to_owned()))
+            .unwrap_or_else(|| FALLBACK_THEME.to_owned());
+
+        self.ui_sounds = config.map_or(true, |config| get(config, UI_SOUNDS, true));
+        self.set_theme(&theme);
+    }
+
+    fn set_theme(&mut self, theme: &str) {
+        self.active_theme = self.themes.iter().position(|t| t.id == theme);
+
+        self.events = EVENTS
+            .iter()
+            .filter(|event| {
+                event_file(&self.themes, theme, event).is_some_and(|path| is_wav(&path))
+            })
+            .map(|&event| (event, event_label(event)))
+            .collect();
+    }
+
+    fn set_volume(&mut self, volume: u32) {
+        self.volume = volume;
+        self.volume_text = volume.to_string();
+    }
+
+    fn theme_id(&self) -> &str {
+        self.active_theme
+            .and_then(|pos| self.themes.get(pos))
+            .map_or(FALLBACK_THEME, |theme| theme.id.as_str())
+    }
+}
+
+impl super::Page {
+    pub fn update_alerts(&mut self, message: Message) -> Command<app::Message> {
+        match message {
+            Message::Volume(volume) => self.alerts.set_volume(volume),
+
+            Message::VolumeApply => {
+                return self.server_request(server::Request::AlertVolume(self.alerts.volume));
+            }
+
+            Message::VolumeLoaded(volume) => self.alerts.set_volume(volume.unwrap_or(100)),
+
+            Message::Theme(pos) => {
+                let Some(theme) = self.alerts.themes.get(pos).map(|theme| theme.id.clone()) else {
+                    return Command::none();
+                };
+
+                self.alerts.set_theme(&theme);
+
+                if let Some(config) = self.audio_config.as_ref() {
+                    set(config, SOUND_THEME, theme);
+                }
+            }
+
+            Message::UiSounds(enable) => {
+                self.alerts.ui_sounds = enable;
+
+                if let Some(config) = self.audio_config.as_ref() {
+                    set(config, UI_SOUNDS, enable);
+                }
+            }
+
+            Message::Preview(event) => {
+                let Some(client) = self.server.clone() else {
+                    return Command::none();
+                };
+
+                let Some(path) = event_file(&self.alerts.themes, self.alerts.theme_id(), event)
+                else {
+                    return Command::none();
+                };
+
+                let volume = self.alerts.volume;
+
+                return cosmic::command::future(async move {
+                    super::Message::Alerts(Message::Previewed(play(&client, &path, volume).await))
+                })
+                .map(crate::pages::Message::Sound)
+                .map(app::Message::PageMessage);
+            }
+
+            Message::Previewed(result) => {
+                self.alerts.error = result.err();
+
+                if let Some(why) = self.alerts.error.as_ref() {
+                    tracing::error!(?why, "failed to play event sound");
+                }
+            }
+        }
+
+        Command::none()
+    }
+}
+
+/// View for the alert volume, sound theme and event sounds.
+pub fn section() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let volume = descriptions.insert(fl!("sound-alerts", "volume"));
+    let theme = descriptions.insert(fl!("sound-alerts", "theme"));
+    let ui_sounds = descriptions.insert(fl!("sound-alerts", "ui-sounds"));
+    let events = descriptions.insert(fl!("sound-alerts", "events"));
+
+    Section::default()
+        .title(fl!("sound-alerts"))
+        .descriptions(descriptions)
+        .view::<super::Page>(move |_binder, page, section| {
+            let descriptions = &section.descriptions;
+            let alerts = &page.alerts;
+
+            let volume_control = widget::row::with_capacity(3)
+                .align_items(cosmic::iced::Alignment::Center)
+                .spacing(4)
+                .push(
+                    widget::icon::from_name("preferences-sound-symbolic")
+                        .size(16)
+                        .icon(),
+                )
+                .push(widget::text::body(&alerts.volume_text))
+                .push(
+                    widget::slider(0..=100, alerts.volume, Message::Volume)
+                        .on_release(Message::VolumeApply),
+                );
+
+            let controls = settings::view_section(&section.title)
+                .add(settings::flex_item(&*descriptions[volume], volume_control))
+                .add(settings::item(
+                    &*descriptions[theme],
+                    widget::dropdown(&alerts.theme_names, alerts.active_theme, Message::Theme),
+                ))
+                .add(settings::item(
+                    &*descriptions[ui_sounds],
+                    widget::toggler(None, alerts.ui_sounds, Message::UiSounds),
+                ));
+
+            let mut event_list = settings::view_section(&*descriptions[events]);
+
+            if let Some(why) = alerts.error.as_ref() {
+                event_list = event_list
+                    .add(settings::item_row(vec![
+                        widget::text::body(why.as_str()).into()
+                    ]));
+            }
+
+            for (event, label) in &alerts.events {
+                event_list = event_list.add(settings::item(
+                    label.as_str(),
+                    widget::button::icon(widget::icon::from_name("media-playback-start-symbolic"))
+                        .on_press(Message::Preview(*event)),
+                ));
+            }
+
+            widget::column::with_capacity(2)
+                .spacing(24)
+                .push(controls)
+                .push_maybe((!alerts.events.is_empty()).then_some(event_list))
+                .apply(Element::from)
+                .map(|message| crate::pages::Message::Sound(super::Message::Alerts(message)))
+        })
+}
+
+fn event_label(event: &str) -> String {
+    match event {
+        "bell" => fl!("sound-alerts", "event-bell"),
+        "message-new-instant" => fl!("sound-alerts", "event-message"),
+        "dialog-warning" => fl!("sound-alerts", "event-warning"),
+        "complete" => fl!("sound-alerts", "event-complete"),
+        "device-added" => fl!("sound-alerts", "event-device-added"),
+        "device-removed" => fl!("sound-alerts", "event-device-removed"),
+        "power-plug" => fl!("sound-alerts", "event-power-plug"),
+        "power-unplug" => fl!("sound-alerts", "event-power-unplug"),
+        "audio-volume-change" => fl!("sound-alerts", "event-volume-change"),
+        "screen-capture" => fl!("sound-alerts", "event-screen-capture"),
+        "trash-empty" => fl!("sound-alerts", "event-trash-empty"),
+        _ => event.to_owned(),
+    }
+}
+
+/// Plays a WAV file as an event sound, at a volume percentage.
+async fn play(client: &Client, path: &Path, volume: u32) -> Result<(), String> {
+    let preview_error = |why: String| fl!("sound-alerts", "preview-error", why = why);
+
+    let data = tokio::fs::read(path)
+        .await
+        .map_err(|why| preview_error(why.to_string()))?;
+
+    let (spec, samples) =
+        decode_wav(&data).ok_or_else(|| preview_error(String::from("unsupported WAV file")))?;
+
+    let samples = samples.to_vec();
+
+    playback::play(client, samples, move |connection| {
+        connect(connection, &spec, volume)
+    })
+    .await
+    .map_err(|why| preview_error(why.to_string()))
+}
+
+/// Creates a stream with the event role on the default sink, which the server treats like
+/// any other event sound.
+fn connect(connection: &mut Connection, spec: &Spec, volume: u32) -> Result<Stream, Error> {
+    let mut proplist = Proplist::new()
+        .ok_or_else(|| Error::Failed(String::from("failed to create property list")))?;
+
+    _ = proplist.set_str(properties::MEDIA_ROLE, "event");
+
+    let mut stream = Stream::new_with_proplist(
+        &mut connection.context,
+        "Event sound",
+        spec,
+        None,
+        &mut proplist,
+    )
+    .ok_or_else(|| Error::Failed(String::from("failed to create stream")))?;
+
+    let mut volumes = ChannelVolumes::default();
+    volumes.set(spec.channels, server::to_volume(volume));
+
+    stream
+        .connect_playback(None, None, stream::FlagSet::NOFLAGS, Some(&volumes), None)
+        .map_err(failed)?;
+
+    Ok(stream)
+}
+
+/// Whether a sound file is a WAV file, which is the only format that can be previewed without
+/// a decoder.
+fn is_wav(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "wav")
+}
+
+/// Finds the sample spec and the samples of an uncompressed WAV file.
+pub(super) fn decode_wav(data: &[u8]) -> Option<(Spec, &[u8])> {
+    if data.get(..4)? != b"RIFF" || data.get(8..12)? != b"WAVE" {
+        return None;
+    }
+
+    let mut chunks = &data[12..];
+    let mut spec = None;
+
+    while chunks.len() >= 8 {
+        let (id, rest) = chunks.split_at(4);
+        let (size, rest) = rest.split_at(4);
+        let size = u32::from_le_bytes(size.try_into().ok()?) as usize;
+        let body = &rest[..size.min(rest.len())];
+
+        match id {
+            b"fmt " => spec = Some(wav_spec(body)?),
+            b"data" => {
+                let spec = spec?;
+                // Leave out an incomplete frame at the end of a truncated file.
+                let len = body.len() - body.len() % spec.frame_size();
+                return Some((spec, &body[..len]));
+            }
+            _ => (),
+        }
+
+        // Chunks are padded to an even size.
+        chunks = rest.get(size + size % 2..).unwrap_or_default();
+    }
+
+    None
+}
+
+/// Reads the sample spec from the `fmt ` chunk of a WAV file.
+fn wav_spec(fmt: &[u8]) -> Option<Spec> {
+    let u16_at = |offset: usize| {
+        fmt.get(offset..offset + 2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+    };
+
+    let mut tag = u16_at(0)?;
+    let channels = u16_at(2)?;
+    let rate = u32::from_le_bytes(fmt.get(4..8)?.try_into().ok()?);
+    let bits = u16_at(14)?;
+
+    // WAVE_FORMAT_EXTENSIBLE stores the actual format tag at the start of its sub-format.
+    if tag == 0xFFFE {
+        tag = u16_at(24)?;
+    }
+
+    let format = match (tag, bits) {
+        (1, 8) => Format::U8,
+        (1, 16) => Format::S16le,
+        (1, 24) => Format::S24le,
+        (1, 32) => Format::S32le,
+        (3, 32) => Format::F32le,
+        _ => return None,
+    };
+
+    let spec = Spec {
+        format,
+        channels: u8::try_from(channels).ok()?,
+        rate,
+    };
+
+    spec.is_valid().then_some(spec)
+}
+
+/// Directories which contain sound themes, from the most to the least preferred.
+fn sound_dirs() -> Vec<PathBuf> {
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| String::from("/usr/local/share:/usr/share"));
+
+    dirs::data_dir()
+        .into_iter()
+        .chain(std::env::split_paths(&data_dirs))
+        .map(|dir| dir.join("sounds"))
+        .collect()
+}
+
+/// Sound themes which are not hidden, sorted by name.
+pub fn themes() -> Vec<Theme> {
+    let mut seen = BTreeSet::new();
+    let mut themes = Vec::new();
+
+    for dir in sound_dirs() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let Some(id) = entry.file_name().to_str().map(str::to_owned) else {
+                continue;
+            };
+
+            // A theme in a more preferred directory overrides those of the same ID.
+            if seen.contains(&id) {
+                continue;
+            }
+
+            let Ok(index) = std::fs::read_to_string(entry.path().join("index.theme")) else {
+                continue;
+            };
+
+            seen.insert(id.clone());
+
+            if let Some(theme) = parse_index(id, &index) {
+                themes.push(theme);
+            }
+        }
+    }
+
+    themes.sort_by(|a, b| a.name.cmp(&b.name));
+    themes
+}
+
+/// Parses the `[Sound Theme]` group of an `index.theme`, skipping hidden themes.
+pub(super) fn parse_index(id: String, index: &str) -> Option<Theme> {
+    let mut in_group = false;
+    let mut name = None;
+    let mut inherits = Vec::new();
+
+    for line in index.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_group = line == "[Sound Theme]";
+            continue;
+        }
+
+        if !in_group {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        match key.trim() {
+            "Name" => name = Some(value.trim().to_owned()),
+            "Inherits" => {
+                inherits = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|theme| !theme.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+            }
+            "Hidden" if value.trim() == "true" => return None,
+            _ => (),
+        }
+    }
+
+    Some(Theme {
+        name: name.unwrap_or_else(|| id.clone()),
+        id,
+        inherits,
+    })
+}
+
+/// Finds the sound file of an event in a theme, or else in the themes it inherits from.
+pub fn event_file(themes: &[Theme], theme: &str, event: &str) -> Option<PathBuf> {
+    find_event_file(&sound_dirs(), themes, theme, event)
+}
+
+/// Finds the sound file of an event in the sound theme directories `dirs`.
+pub(super) fn find_event_file(
+    dirs: &[PathBuf],
+    themes: &[Theme],
+    theme: &str,
+    event: &str,
+) -> Option<PathBuf> {
+    let mut visited = BTreeSet::new();
+    let mut queue = vec![theme.to_owned()];
+
+    while let Some(theme) = queue.pop() {
+        if !visited.insert(theme.clone()) {
+            continue;
+        }
+
+        for dir in dirs {
+            for subdir in ["stereo", ""] {
+                for extension in EXTENSIONS {
+                    let path = dir
+                        .join(&theme)
+                        .join(subdir)
+                        .join(format!("{event}.{extension}"));
+
+                    if path.is_file() {
+                        return Some(path);
+                    }
+                }
+            }
+        }
+
+        if let Some(parent) = themes.iter().find(|t| t.id == theme) {
+            // Searched in order, so pushed in reverse.
+            queue.extend(parent.inherits.iter().rev().cloned());
+        }
+
+        if queue.is_empty() && !visited.contains(FALLBACK_THEME) {
+            queue.push(FALLBACK_THEME.to_owned());
+        }
+    }
+
+    None
+}
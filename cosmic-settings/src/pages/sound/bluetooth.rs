@@ -0,0 +1,367 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Modes and codecs of Bluetooth audio devices, which are chosen through card profiles.
+//!
+//! PipeWire gives Bluetooth cards a profile for each mode, such as `a2dp-sink`, and one for
+//! each codec that the mode supports on the device, such as `a2dp-sink-ldac`.
+
+use cosmic::widget::{self, settings};
+use cosmic::{Apply, Command, Element};
+use cosmic_settings_page::Section;
+use cosmic_settings_subscriptions::pipewire;
+use slab::Slab;
+
+use super::{server, DeviceId};
+use crate::app;
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    /// Switch a device to another mode.
+    Mode(DeviceId, usize),
+    /// Switch a device to another codec of its mode.
+    Codec(DeviceId, usize),
+    /// The codec which a device currently uses.
+    CodecLoaded(DeviceId, Option<String>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// A2DP, which plays in high quality with the microphone turned off.
+    HighFidelity,
+    /// HFP or HSP, which plays in low quality with the microphone turned on.
+    Headset,
+}
+
+impl Mode {
+    const ALL: [Self; 2] = [Self::HighFidelity, Self::Headset];
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::HighFidelity => "a2dp-sink",
+            Self::Headset => "headset-head-unit",
+        }
+    }
+
+    fn label(self) -> String {
+        match self {
+            Self::HighFidelity => fl!("sound-bluetooth", "high-fidelity"),
+            Self::Headset => fl!("sound-bluetooth", "headset"),
+        }
+    }
+}
+
+/// Modes and codecs of a Bluetooth device.
+#[derive(Debug)]
+pub struct Card {
+    pub device_id: DeviceId,
+    pub name: String,
+    /// Profiles which switch to each mode, and the labels of the modes.
+    mode_profiles: Vec<String>,
+    mode_labels: Vec<String>,
+    active_mode: Option<usize>,
+    /// Profiles which switch to each codec of the active mode, and the labels of the codecs.
+    codec_profiles: Vec<String>,
+    codec_labels: Vec<String>,
+    active_codec: Option<usize>,
+}
+
+/// The mode and codec of a profile, such as `a2dp-sink-ldac`.
+pub(super) fn parse_profile(profile: &str) -> Option<(Mode, Option<&str>)> {
+    Mode::ALL.into_iter().find_map(|mode| {
+        let rest = profile.strip_prefix(mode.prefix())?;
+
+        if rest.is_empty() {
+            Some((mode, None))
+        } else {
+            rest.strip_prefix('-').map(|codec| (mode, Some(codec)))
+        }
+    })
+}
+
+/// The name by which a codec is known, such as `aptX HD` for `aptx_hd`.
+pub(super) fn codec_label(codec: &str) -> String {
+    match codec {
+        "sbc" => "SBC",
+        "sbc_xq" => "SBC XQ",
+        "aac" => "AAC",
+        "ldac" => "LDAC",
+        "aptx" => "aptX",
+        "aptx_hd" => "aptX HD",
+        "aptx_ll" => "aptX Low Latency",
+        "faststream" => "FastStream",
+        "lc3" => "LC3",
+        "lc3plus_h3" => "LC3plus",
+        "opus_05" => "Opus",
+        "msbc" => "mSBC",
+        "cvsd" => "CVSD",
+        codec => return codec.to_uppercase(),
+    }
+    .to_owned()
+}
+
+impl super::Page {
+    pub fn update_bluetooth(&mut self, message: Message) -> Command<app::Message> {
+        match message {
+            Message::Mode(device_id, pos) => {
+                let profile = self
+                    .bluetooth
+                    .iter()
+                    .find(|card| card.device_id == device_id)
+                    .and_then(|card| card.mode_profiles.get(pos).cloned());
+
+                if let Some(profile) = profile {
+                    return self.set_bluetooth_profile(device_id, profile);
+                }
+            }
+
+            Message::Codec(device_id, pos) => {
+                let profile = self
+                    .bluetooth
+                    .iter()
+                    .find(|card| card.device_id == device_id)
+                    .and_then(|card| card.codec_profiles.get(pos).cloned());
+
+                if let Some(profile) = profile {
+                    return self.set_bluetooth_profile(device_id, profile);
+                }
+            }
+
+            Message::CodecLoaded(device_id, codec) => {
+                match codec {
+                    Some(codec) => self.bluetooth_codecs.insert(device_id, codec),
+                    None => self.bluetooth_codecs.remove(&device_id),
+                };
+
+                self.update_bluetooth_cards();
+            }
+        }
+
+        Command::none()
+    }
+
+    fn set_bluetooth_profile(
+        &mut self,
+        device_id: DeviceId,
+        profile: String,
+    ) -> Command<app::Message> {
+        let Some(card) = self.card_names.get(&device_id).cloned() else {
+            return Command::none();
+        };
+
+        self.active_profiles
+            .insert(device_id.clone(), Some(profile.clone()));
+        self.bluetooth_codecs.remove(&device_id);
+        self.update_bluetooth_cards();
+
+        // The output is recreated for the new profile, so it is selected again afterwards.
+        if self.active_sink_device.as_ref() == Some(&device_id) {
+            self.set_sink_profiles(&device_id);
+            self.changing_sink_profile = true;
+
+            return self.set_card_profile(
+                card,
+                profile,
+                device_id,
+                super::Message::SinkProfileSelect,
+            );
+        }
+
+        self.server_request(server::Request::CardProfile { card, profile })
+    }
+
+    /// Fetches the codec of a Bluetooth device from its output, if it has one.
+    pub(super) fn refresh_bluetooth_codec(&self, device_id: &DeviceId) -> Command<app::Message> {
+        let (Some(client), DeviceId::Bluez5(_)) = (self.server.clone(), device_id) else {
+            return Command::none();
+        };
+
+        let sink = self.devices.get(device_id).and_then(|card| {
+            card.devices
+                .values()
+                .find(|device| matches!(device.class, pipewire::MediaClass::Sink))
+                .map(|device| device.identifier.clone())
+        });
+
+        let Some(sink) = sink else {
+            return Command::none();
+        };
+
+        let device_id = device_id.clone();
+
+        cosmic::command::future(async move {
+            let codec = client.bluetooth_codec(sink).await.unwrap_or_else(|why| {
+                tracing::error!(?why, "failed to get bluetooth codec");
+                None
+            });
+
+            super::Message::Bluetooth(Message::CodecLoaded(device_id, codec))
+        })
+        .map(crate::pages::Message::Sound)
+        .map(crate::app::Message::PageMessage)
+    }
+
+    /// Gathers the modes and codecs of Bluetooth devices from their card profiles.
+    pub(super) fn update_bluetooth_cards(&mut self) {
+        self.bluetooth = self
+            .card_profiles
+            .iter()
+            .filter(|(device_id, _)| matches!(device_id, DeviceId::Bluez5(_)))
+            .map(|(device_id, profiles)| {
+                let active = self
+                    .active_profiles
+                    .get(device_id)
+                    .and_then(|profile| profile.as_deref())
+                    .and_then(parse_profile);
+
+                let available = profiles
+                    .iter()
+                    .filter(|profile| profile.available)
+                    .filter_map(|profile| {
+                        parse_profile(&profile.name).map(|parsed| (profile.name.as_str(), parsed))
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut card = Card {
+                    device_id: device_id.clone(),
+                    name: self.card_description(device_id),
+                    mode_profiles: Vec::new(),
+                    mode_labels: Vec::new(),
+                    active_mode: None,
+                    codec_profiles: Vec::new(),
+                    codec_labels: Vec::new(),
+                    active_codec: None,
+                };
+
+                for mode in Mode::ALL {
+                    // Switching modes keeps the codec which the device prefers for the mode.
+                    let profile = available
+                        .iter()
+                        .filter(|(_, (m, _))| *m == mode)
+                        .min_by_key(|(_, (_, codec))| codec.is_some())
+                        .map(|(name, _)| (*name).to_owned());
+
+                    if let Some(profile) = profile {
+                        if active.map(|(m, _)| m) == Some(mode) {
+                            card.active_mode = Some(card.mode_profiles.len());
+                        }
+
+                        card.mode_profiles.push(profile);
+                        card.mode_labels.push(mode.label());
+                    }
+                }
+
+                if let Some((mode, codec)) = active {
+                    let codec = codec
+                        .map(str::to_owned)
+                        .or_else(|| self.bluetooth_codecs.get(device_id).cloned());
+
+                    for (name, (_, profile_codec)) in
+                        available.iter().filter(|(_, (m, _))| *m == mode)
+                    {
+                        let Some(profile_codec) = profile_codec else {
+                            continue;
+                        };
+
+                        if codec.as_deref() == Some(*profile_codec) {
+                            card.active_codec = Some(card.codec_profiles.len());
+                        }
+
+                        card.codec_profiles.push((*name).to_owned());
+                        card.codec_labels.push(codec_label(profile_codec));
+                    }
+
+                    // Without a profile for each codec, the codec in use is only shown.
+                    if card.codec_profiles.is_empty() {
+                        if let Some(codec) = codec {
+                            card.active_codec = Some(0);
+                            card.codec_profiles.push(String::new());
+                            card.codec_labels.push(codec_label(&codec));
+                        }
+                    }
+                }
+
+                card
+            })
+            .collect();
+    }
+
+    /// The name of a card, from the description of one of its devices.
+    fn card_description(&self, device_id: &DeviceId) -> String {
+        self.devices
+            .get(device_id)
+            .and_then(|card| card.devices.values().next())
+            .map(|device| {
+                self.device_options
+                    .name(&device.identifier, &device.description)
+                    .to_owned()
+            })
+            .or_else(|| self.card_names.get(device_id).cloned())
+            .unwrap_or_default()
+    }
+}
+
+pub fn section() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let mode = descriptions.insert(fl!("sound-bluetooth", "mode"));
+    let mode_desc = descriptions.insert(fl!("sound-bluetooth", "mode-desc"));
+    let codec = descriptions.insert(fl!("sound-bluetooth", "codec"));
+
+    Section::default()
+        .title(fl!("sound-bluetooth"))
+        .descriptions(descriptions)
+        .show_while::<super::Page>(|page| !page.bluetooth.is_empty())
+        .view::<super::Page>(move |_binder, page, section| {
+            let descriptions = &section.descriptions;
+
+            let mut column = widget::column::with_capacity(page.bluetooth.len() + 1)
+                .spacing(24)
+                .push(widget::text::body(&*descriptions[mode_desc]));
+
+            for card in &page.bluetooth {
+                let mut controls = settings::view_section(&card.name);
+
+                if !card.mode_labels.is_empty() {
+                    let device_id = card.device_id.clone();
+
+                    controls = controls.add(settings::item(
+                        &*descriptions[mode],
+                        widget::dropdown(&card.mode_labels, card.active_mode, move |pos| {
+                            Message::Mode(device_id.clone(), pos)
+                        }),
+                    ));
+                }
+
+                // A codec can only be chosen when the device has a profile for each.
+                if card
+                    .codec_profiles
+                    .iter()
+                    .any(|profile| !profile.is_empty())
+                {
+                    let device_id = card.device_id.clone();
+
+                    controls = controls.add(settings::item(
+                        &*descriptions[codec],
+                        widget::dropdown(&card.codec_labels, card.active_codec, move |pos| {
+                            Message::Codec(device_id.clone(), pos)
+                        }),
+                    ));
+                } else if let Some(label) =
+                    card.active_codec.and_then(|pos| card.codec_labels.get(pos))
+                {
+                    controls = controls.add(settings::item(
+                        &*descriptions[codec],
+                        widget::text::body(label.as_str()),
+                    ));
+                }
+
+                column = column.push(controls);
+            }
+
+            column
+                .apply(Element::from)
+                .map(super::Message::Bluetooth)
+                .map(crate::pages::Message::Sound)
+        })
+}
@@ -0,0 +1,343 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Options which users give to each device, such as hiding the HDMI outputs of a dock.
+//!
+//! Devices are known by their node names, which stay the same across reboots and reconnects.
+
+use std::collections::BTreeMap;
+
+use cosmic::cosmic_config;
+use cosmic::widget::{self, settings};
+use cosmic::{Apply, Command, Element};
+use cosmic_settings_page::Section;
+use cosmic_settings_subscriptions::pipewire;
+use serde::{Deserialize, Serialize};
+use slab::Slab;
+
+use super::{get, set, NodeId};
+use crate::app;
+
+/// Options of each device, by node name.
+pub const DEVICE_OPTIONS: &str = "device_options";
+/// Node names of devices, from the most to the least preferred.
+pub const DEVICE_PRIORITY: &str = "device_priority";
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    /// Give a device a name of its own, or restore its own with an empty name.
+    Rename(String, String),
+    /// Hide a device from the device lists.
+    Hide(String, bool),
+    /// Prefer a device over the one above it.
+    Raise(String),
+    /// Prefer the device below a device over it.
+    Lower(String),
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Options {
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Devices {
+    pub options: BTreeMap<String, Options>,
+    pub priority: Vec<String>,
+}
+
+impl Devices {
+    /// Reads the device options.
+    pub fn load(&mut self, config: Option<&cosmic_config::Config>) {
+        self.options = config.map_or_else(BTreeMap::new, |config| {
+            get(config, DEVICE_OPTIONS, BTreeMap::new())
+        });
+
+        self.priority =
+            config.map_or_else(Vec::new, |config| get(config, DEVICE_PRIORITY, Vec::new()));
+    }
+
+    /// The name shown for a device, which is its description unless it was renamed.
+    pub fn name<'a>(&'a self, node_name: &str, description: &'a str) -> &'a str {
+        self.options
+            .get(node_name)
+            .and_then(|options| options.name.as_deref())
+            .unwrap_or(description)
+    }
+
+    pub fn is_hidden(&self, node_name: &str) -> bool {
+        self.options
+            .get(node_name)
+            .map_or(false, |options| options.hidden)
+    }
+
+    /// Position of a device in the priority order, if it has one.
+    pub fn rank(&self, node_name: &str) -> Option<usize> {
+        self.priority.iter().position(|name| name == node_name)
+    }
+
+    /// Whether a device is preferred over another.
+    pub fn outranks(&self, node_name: &str, other: &str) -> bool {
+        match (self.rank(node_name), self.rank(other)) {
+            (Some(rank), Some(other)) => rank < other,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    fn save(&self, config: Option<&cosmic_config::Config>) {
+        if let Some(config) = config {
+            set(config, DEVICE_OPTIONS, &self.options);
+            set(config, DEVICE_PRIORITY, &self.priority);
+        }
+    }
+}
+
+impl super::Page {
+    pub fn update_devices(&mut self, message: Message) -> Command<app::Message> {
+        match message {
+            Message::Rename(node_name, name) => {
+                let options = self.device_options.options.entry(node_name).or_default();
+                options.name = Some(name).filter(|name| !name.trim().is_empty());
+            }
+
+            Message::Hide(node_name, hidden) => {
+                self.device_options
+                    .options
+                    .entry(node_name)
+                    .or_default()
+                    .hidden = hidden;
+            }
+
+            Message::Raise(node_name) => self.move_device(&node_name, true),
+
+            Message::Lower(node_name) => self.move_device(&node_name, false),
+        }
+
+        self.device_options
+            .options
+            .retain(|_, options| *options != Options::default());
+
+        self.device_options.save(self.audio_config.as_ref());
+        self.update_device_lists();
+
+        Command::none()
+    }
+
+    /// Swaps a device with its neighbor in the order of its class, and makes that order the
+    /// priority order of the class.
+    fn move_device(&mut self, node_name: &str, up: bool) {
+        let Some(device) = self
+            .devices
+            .values()
+            .flat_map(|card| card.devices.values())
+            .find(|device| device.identifier == node_name)
+        else {
+            return;
+        };
+
+        let mut order = self
+            .ordered_devices(&device.class)
+            .into_iter()
+            .map(|(_, device)| device.identifier.clone())
+            .collect::<Vec<_>>();
+
+        let Some(pos) = order.iter().position(|name| name == node_name) else {
+            return;
+        };
+
+        let other = if up {
+            pos.checked_sub(1)
+        } else {
+            Some(pos + 1).filter(|&other| other < order.len())
+        };
+
+        let Some(other) = other else {
+            return;
+        };
+
+        order.swap(pos, other);
+
+        // Devices which aren't connected keep their priority after the connected ones.
+        let priority = &mut self.device_options.priority;
+        priority.retain(|name| !order.contains(name));
+        order.append(priority);
+        *priority = order;
+    }
+
+    /// Devices of a class, from the most to the least preferred, and then by name.
+    pub(super) fn ordered_devices(
+        &self,
+        class: &pipewire::MediaClass,
+    ) -> Vec<(NodeId, &super::Device)> {
+        let mut devices = self
+            .devices
+            .values()
+            .flat_map(|card| card.devices.iter())
+            .filter(|(_, device)| {
+                matches!(
+                    (&device.class, class),
+                    (pipewire::MediaClass::Sink, pipewire::MediaClass::Sink)
+                        | (pipewire::MediaClass::Source, pipewire::MediaClass::Source)
+                )
+            })
+            .map(|(&node_id, device)| (node_id, device))
+            .collect::<Vec<_>>();
+
+        let options = &self.device_options;
+
+        devices.sort_by(|(_, a), (_, b)| {
+            let a_rank = options.rank(&a.identifier).unwrap_or(usize::MAX);
+            let b_rank = options.rank(&b.identifier).unwrap_or(usize::MAX);
+
+            a_rank.cmp(&b_rank).then_with(|| {
+                options
+                    .name(&a.identifier, &a.description)
+                    .cmp(options.name(&b.identifier, &b.description))
+            })
+        });
+
+        devices
+    }
+
+    /// Rebuilds the output and input lists from the connected devices, leaving out hidden
+    /// devices unless they are in use.
+    pub(super) fn update_device_lists(&mut self) {
+        let list = |page: &Self, class: &pipewire::MediaClass, default: &str| {
+            page.ordered_devices(class)
+                .into_iter()
+                .filter(|(_, device)| {
+                    device.identifier == default
+                        || !page.device_options.is_hidden(&device.identifier)
+                })
+                .map(|(node_id, device)| {
+                    let name = page
+                        .device_options
+                        .name(&device.identifier, &device.description);
+
+                    (name.to_owned(), node_id)
+                })
+                .unzip::<_, _, Vec<_>, Vec<_>>()
+        };
+
+        (self.sinks, self.sink_ids) = list(self, &pipewire::MediaClass::Sink, &self.default_sink);
+        (self.sources, self.source_ids) =
+            list(self, &pipewire::MediaClass::Source, &self.default_source);
+
+        self.active_sink = self.active_device(&self.sink_ids, &self.default_sink);
+        self.active_source = self.active_device(&self.source_ids, &self.default_source);
+    }
+
+    fn active_device(&self, node_ids: &[NodeId], node_name: &str) -> Option<usize> {
+        node_ids.iter().position(|&node_id| {
+            self.node_name(node_id)
+                .map_or(false, |identifier| identifier == node_name)
+        })
+    }
+
+    /// Makes a newly connected output the default if it is preferred over the current one.
+    pub(super) fn prefer_sink(&mut self, node_name: String) -> Command<app::Message> {
+        if !self.device_options.outranks(&node_name, &self.default_sink) {
+            return Command::none();
+        }
+
+        self.select_sink(node_name)
+    }
+
+    /// Makes a newly connected input the default if it is preferred over the current one.
+    pub(super) fn prefer_source(&mut self, node_name: String) -> Command<app::Message> {
+        if !self
+            .device_options
+            .outranks(&node_name, &self.default_source)
+        {
+            return Command::none();
+        }
+
+        self.select_source(node_name)
+    }
+}
+
+pub fn section() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let outputs = descriptions.insert(fl!("sound-devices", "outputs"));
+    let inputs = descriptions.insert(fl!("sound-devices", "inputs"));
+    let name = descriptions.insert(fl!("sound-devices", "name"));
+    let show = descriptions.insert(fl!("sound-devices", "show"));
+
+    Section::default()
+        .title(fl!("sound-devices"))
+        .descriptions(descriptions)
+        .view::<super::Page>(move |_binder, page, section| {
+            let descriptions = &section.descriptions;
+
+            let mut column = widget::column::with_capacity(3)
+                .spacing(24)
+                .push(widget::text::body(fl!("sound-devices", "desc")));
+
+            for (class, title) in [
+                (pipewire::MediaClass::Sink, outputs),
+                (pipewire::MediaClass::Source, inputs),
+            ] {
+                let devices = page.ordered_devices(&class);
+
+                if devices.is_empty() {
+                    continue;
+                }
+
+                let last = devices.len() - 1;
+                let mut list = settings::view_section(&*descriptions[title]);
+
+                for (pos, (_, device)) in devices.into_iter().enumerate() {
+                    let node_name = device.identifier.clone();
+                    let options = page.device_options.options.get(&node_name);
+
+                    let name_input = widget::text_input(
+                        &*descriptions[name],
+                        options.and_then(|o| o.name.as_deref()).unwrap_or_default(),
+                    )
+                    .on_input({
+                        let node_name = node_name.clone();
+                        move |name| Message::Rename(node_name.clone(), name)
+                    })
+                    .width(cosmic::iced::Length::Fixed(200.0));
+
+                    let visible = widget::toggler(
+                        Some(descriptions[show].to_string()),
+                        !options.map_or(false, |o| o.hidden),
+                        {
+                            let node_name = node_name.clone();
+                            move |show| Message::Hide(node_name.clone(), !show)
+                        },
+                    )
+                    .width(cosmic::iced::Length::Shrink);
+
+                    let raise = widget::button::icon(widget::icon::from_name("go-up-symbolic"))
+                        .on_press_maybe((pos > 0).then(|| Message::Raise(node_name.clone())));
+
+                    let lower = widget::button::icon(widget::icon::from_name("go-down-symbolic"))
+                        .on_press_maybe((pos < last).then(|| Message::Lower(node_name.clone())));
+
+                    let controls = widget::row::with_capacity(4)
+                        .align_items(cosmic::iced::Alignment::Center)
+                        .spacing(8)
+                        .push(name_input)
+                        .push(visible)
+                        .push(raise)
+                        .push(lower);
+
+                    list = list.add(settings::flex_item(device.description.as_str(), controls));
+                }
+
+                column = column.push(list);
+            }
+
+            column
+                .apply(Element::from)
+                .map(super::Message::Devices)
+                .map(crate::pages::Message::Sound)
+        })
+}
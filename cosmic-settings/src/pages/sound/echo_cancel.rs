@@ -0,0 +1,91 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Noise suppression and echo cancellation for the microphone.
+//!
+//! The echo-cancel module creates a processed source from the default input. It is loaded
+//! at login by a drop-in config of the PulseAudio interface of PipeWire, which settings writes
+//! while this is enabled, and loaded or unloaded immediately when this is turned on or off.
+//!
+//! The processed source is given a higher priority than other inputs, so that it becomes the
+//! default input at login unless another input was chosen since.
+
+use std::path::PathBuf;
+
+use super::server::{self, Client, Error};
+
+const MODULE: &str = "module-echo-cancel";
+
+/// Node name of the processed source.
+pub const SOURCE_NAME: &str = "cosmic_echo_cancel_source";
+
+const SOURCE_ARGUMENT: &str = "source_name=cosmic_echo_cancel_source";
+
+/// Arguments of the module, which also name its processed source and its reference sink.
+const ARGUMENTS: &str = "source_name=cosmic_echo_cancel_source \
+    source_properties=priority.session=2500 \
+    sink_name=cosmic_echo_cancel_sink aec_method=webrtc";
+
+fn drop_in_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| {
+        dir.join("pipewire")
+            .join("pipewire-pulse.conf.d")
+            .join("50-cosmic-echo-cancel.conf")
+    })
+}
+
+/// Whether the processed source is loaded at login.
+pub fn is_enabled() -> bool {
+    drop_in_path().map_or(false, |path| path.exists())
+}
+
+/// Writes or removes the drop-in config which loads the processed source at login.
+pub fn persist(enable: bool) -> std::io::Result<()> {
+    let Some(path) = drop_in_path() else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no config directory",
+        ));
+    };
+
+    if !enable {
+        return match std::fs::remove_file(&path) {
+            Err(why) if why.kind() != std::io::ErrorKind::NotFound => Err(why),
+            _ => Ok(()),
+        };
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let config = format!(
+        "# Written by COSMIC Settings while noise suppression is enabled.\n\
+        pulse.cmd = [\n    \
+        {{ cmd = \"load-module\" args = \"{MODULE} {ARGUMENTS}\" flags = [ \"nofail\" ] }}\n\
+        ]\n"
+    );
+
+    std::fs::write(&path, config)
+}
+
+/// Loads the processed source for the current session, unless the drop-in config already
+/// did.
+pub async fn load(client: &Client) -> Result<(), Error> {
+    let loaded = client
+        .is_module_loaded(MODULE.to_owned(), SOURCE_ARGUMENT.to_owned())
+        .await?;
+
+    if loaded {
+        return Ok(());
+    }
+
+    let request = server::Request::LoadModule(MODULE.to_owned(), ARGUMENTS.to_owned());
+    client.apply(request).await
+}
+
+/// Unloads the processed source of the current session.
+pub async fn unload(client: &Client) -> Result<(), Error> {
+    let request = server::Request::UnloadModule(MODULE.to_owned(), SOURCE_ARGUMENT.to_owned());
+    client.apply(request).await
+}
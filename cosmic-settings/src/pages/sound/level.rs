@@ -0,0 +1,109 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Peak level of an input device, for the input level meter.
+
+use libpulse_binding::{
+    def::BufferAttr,
+    sample::{Format, Spec},
+    stream::{self, PeekResult, Stream},
+};
+
+use super::server::{Client, Connection, Error, Watch};
+
+/// How many peaks are measured each second.
+const RATE: u32 = 25;
+
+struct Level {
+    source: String,
+    stream: Stream,
+    tx: futures::channel::mpsc::Sender<f32>,
+}
+
+/// Sends the peak level of a source, from 0.0 to 1.0, until the receiver is dropped.
+pub fn watch(client: &Client, source: String, tx: futures::channel::mpsc::Sender<f32>) {
+    client.watch(move |connection| {
+        let stream = connect(connection, &source)?;
+        Ok(Box::new(Level { source, stream, tx }))
+    });
+}
+
+fn connect(connection: &mut Connection, source: &str) -> Result<Stream, Error> {
+    let spec = Spec {
+        format: Format::FLOAT32NE,
+        channels: 1,
+        rate: RATE,
+    };
+
+    // The server sends one peak per fragment, which holds a single sample.
+    let attr = BufferAttr {
+        maxlength: u32::MAX,
+        tlength: 0,
+        prebuf: 0,
+        minreq: 0,
+        fragsize: std::mem::size_of::<f32>() as u32,
+    };
+
+    let mut stream = Stream::new(&mut connection.context, "Peak detect", &spec, None)
+        .ok_or_else(|| Error::Failed(String::from("failed to create stream")))?;
+
+    stream
+        .connect_record(
+            Some(source),
+            Some(&attr),
+            stream::FlagSet::PEAK_DETECT
+                | stream::FlagSet::ADJUST_LATENCY
+                | stream::FlagSet::DONT_MOVE
+                | stream::FlagSet::DONT_INHIBIT_AUTO_SUSPEND,
+        )
+        .map_err(|why| Error::Failed(why.to_string().unwrap_or_default()))?;
+
+    Ok(stream)
+}
+
+impl Watch for Level {
+    fn poll(&mut self, connection: &mut Connection) -> bool {
+        match self.stream.get_state() {
+            stream::State::Ready => (),
+            stream::State::Failed | stream::State::Terminated => {
+                let why = connection.context.errno().to_string().unwrap_or_default();
+                tracing::error!(why, source = self.source, "failed to measure input level");
+                return false;
+            }
+            _ => return true,
+        }
+
+        let mut peak = None;
+
+        loop {
+            match self.stream.peek() {
+                Ok(PeekResult::Empty) => break,
+                Ok(PeekResult::Hole(_)) => (),
+                Ok(PeekResult::Data(data)) => {
+                    if let Some(sample) = data.rchunks_exact(4).next() {
+                        peak = <[u8; 4]>::try_from(sample).ok().map(f32::from_ne_bytes);
+                    }
+                }
+                Err(why) => {
+                    let why = why.to_string().unwrap_or_default();
+                    tracing::error!(why, source = self.source, "failed to measure input level");
+                    return false;
+                }
+            }
+
+            _ = self.stream.discard();
+        }
+
+        // A peak is skipped if the previous one wasn't shown yet.
+        if let Some(peak) = peak {
+            if let Err(why) = self.tx.try_send(peak.clamp(0.0, 1.0)) {
+                if why.is_disconnected() {
+                    _ = self.stream.disconnect();
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
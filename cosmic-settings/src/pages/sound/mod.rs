@@ -0,0 +1,1619 @@
+// Copyright 2023 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+pub mod alerts;
+pub mod bluetooth;
+pub mod devices;
+pub mod echo_cancel;
+pub mod level;
+pub mod playback;
+pub mod policy;
+pub mod server;
+pub mod speaker_test;
+pub mod streams;
+#[cfg(test)]
+mod tests;
+
+use std::{collections::BTreeMap, time::Duration};
+
+use cosmic::{
+    cosmic_config::{self, ConfigGet, ConfigSet},
+    widget::{self, settings},
+    Command, Element,
+};
+use cosmic_settings_page::{self as page, section, Section};
+use cosmic_settings_subscriptions::{pipewire, pulse};
+use futures::StreamExt;
+use indexmap::IndexMap;
+use slab::Slab;
+use slotmap::SlotMap;
+
+/// Sound settings shared with the sound applet and other desktop components.
+const AUDIO_CONFIG: &str = "com.system76.CosmicAudio";
+
+/// Whether the output volume may be raised above 100%, here and in the sound applet.
+///
+/// The volume keys are handled by cosmic-settings-daemon, which keeps its own limit of 100%
+/// until it reads this key as well.
+pub const AMPLIFICATION_SINK: &str = "amplification_sink";
+
+pub type NodeId = u32;
+pub type ProfileId = u32;
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    /// Get default sinks/sources and their volumes/mute status.
+    Pulse(pulse::Event),
+    /// Get ALSA cards and their profiles.
+    Pipewire(pipewire::DeviceEvent),
+    /// Change the default output.
+    SinkChanged(usize),
+    /// Change the active profile for an output.
+    SinkProfileChanged(usize),
+    /// Select a device from the given card after a profile change.
+    SinkProfileSelect(DeviceId),
+    /// Request to change the default output volume.
+    SinkVolumeChanged(u32),
+    /// Change the output volume.
+    SinkVolumeApply(NodeId),
+    /// Toggle the mute status of the output.
+    SinkMuteToggle,
+    /// Allow or forbid raising the output volume above 100%.
+    SinkAmplification(bool),
+    /// Change the left/right balance of the output, from -100 to 100.
+    SinkBalanceChanged(i32),
+    /// Apply the balance of the output once the slider is released.
+    SinkBalanceApply,
+    /// Change the rear/front fade of the output, from -100 to 100.
+    SinkFadeChanged(i32),
+    /// Apply the fade of the output once the slider is released.
+    SinkFadeApply,
+    /// Change the volume of one channel of the output.
+    SinkChannelVolumeChanged(usize, u32),
+    /// Apply the channel volumes of the output once a slider is released.
+    SinkChannelVolumesApply,
+    /// Show or hide the volume of each channel of the output.
+    SinkChannelsToggle,
+    /// Channels of the default output.
+    SinkChannels(Option<server::Channels>),
+    /// Change the default input output.
+    SourceChanged(usize),
+    /// Change the active profile for an output.
+    SourceProfileChanged(usize),
+    /// Select a device from the given card after a profile change.
+    SourceProfileSelect(DeviceId),
+    /// Request to change the input volume.
+    SourceVolumeChanged(u32),
+    /// Change the input volume.
+    SourceVolumeApply(NodeId),
+    /// Toggle the mute status of the input output.
+    SourceMuteToggle,
+    /// Change the mode or codec of a Bluetooth device.
+    Bluetooth(bluetooth::Message),
+    /// Change when the default devices are switched.
+    Policy(policy::Message),
+    /// Hide, rename or prioritize a device.
+    Devices(devices::Message),
+    /// The devices which were connected when the page opened have all been added.
+    DevicesEnumerated,
+    /// Turn noise suppression and echo cancellation for the microphone on or off.
+    EchoCancel(bool),
+    /// Noise suppression was turned on or off, or failed to be.
+    EchoCancelResult(bool, Result<(), server::Error>),
+    /// Peak level of the default source, from 0.0 to 1.0.
+    SourceLevel(f32),
+    /// Open the dialog which tests the speakers of the default sink.
+    SpeakerTest,
+    /// Close the speaker test dialog.
+    SpeakerTestClose,
+    /// Play a test tone on a channel of the default sink.
+    SpeakerTestChannel(usize),
+    /// A test tone finished playing.
+    SpeakerTestResult(Result<(), server::Error>),
+    /// The sound server accepted or rejected a request.
+    ServerResult(Result<(), server::Error>),
+    /// What the sound server currently uses, after a request failed.
+    ServerState(Result<server::State, server::Error>),
+    /// Alert volume and event sounds.
+    Alerts(alerts::Message),
+    /// Audio streams of applications.
+    Streams(Vec<streams::Stream>),
+    /// Change the volume of an application's stream.
+    StreamVolume(usize, u32),
+    /// Toggle the mute status of an application's stream.
+    StreamMuteToggle(usize),
+    /// Move an application's stream to another device.
+    StreamDevice(usize, usize),
+}
+
+#[derive(Debug)]
+struct Card {
+    devices: IndexMap<NodeId, Device>,
+}
+
+#[derive(Debug)]
+struct Device {
+    class: pipewire::MediaClass,
+    identifier: String,
+    description: String,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum DeviceId {
+    Alsa(u32),
+    Bluez5(String),
+}
+
+#[derive(Default)]
+pub struct Page {
+    sender: Option<tokio::sync::mpsc::Sender<crate::pages::Message>>,
+    pipewire_thread: Option<(tokio::sync::oneshot::Sender<()>, pipewire::Sender<()>)>,
+    pulse_thread: Option<tokio::sync::oneshot::Sender<()>>,
+    server: Option<server::Client>,
+    audio_config: Option<cosmic_config::Config>,
+    alerts: alerts::Alerts,
+    device_options: devices::Devices,
+    policy: policy::Policy,
+    /// Devices which were the default, to return to when the default disconnects.
+    sink_history: Vec<String>,
+    source_history: Vec<String>,
+    bluetooth: Vec<bluetooth::Card>,
+    /// Codecs which Bluetooth devices use, when their profiles don't name them.
+    bluetooth_codecs: BTreeMap<DeviceId, String>,
+    /// Whether devices which are added from now on were newly connected.
+    devices_enumerated: bool,
+    streams_thread: Option<tokio::sync::oneshot::Sender<()>>,
+    streams: Vec<streams::Stream>,
+    /// Error from the last request that the sound server failed to apply.
+    server_error: Option<server::Error>,
+    devices: BTreeMap<DeviceId, Card>,
+    card_names: IndexMap<DeviceId, String>,
+    card_profiles: IndexMap<DeviceId, Vec<pulse::CardProfile>>,
+    active_profiles: IndexMap<DeviceId, Option<String>>,
+
+    default_sink: String,
+    default_source: String,
+
+    sink_volume: u32,
+    sink_volume_text: String,
+    sink_mute: bool,
+    sink_volume_debounce: bool,
+    /// Whether the output volume may be raised above 100%.
+    amplification_sink: bool,
+    sink_channels: Option<server::Channels>,
+    sink_channels_expanded: bool,
+
+    source_volume: u32,
+    source_volume_text: String,
+    source_mute: bool,
+    source_volume_debounce: bool,
+    source_level: f32,
+    /// Whether the processed source with noise suppression and echo cancellation is used.
+    echo_cancel: bool,
+    /// The source whose level is measured, and the cancellation of its measurement.
+    level_meter: Option<(String, tokio::sync::oneshot::Sender<()>)>,
+
+    /// Whether the speaker test dialog is shown, and the channel which is playing a tone.
+    speaker_test: Option<Option<usize>>,
+
+    sinks: Vec<String>,
+    sink_ids: Vec<NodeId>,
+    sink_profiles: Vec<String>,
+    sink_profile_names: Vec<String>,
+    sources: Vec<String>,
+    source_ids: Vec<NodeId>,
+    source_profiles: Vec<String>,
+    source_profile_names: Vec<String>,
+
+    active_sink: Option<usize>,
+    active_sink_device: Option<DeviceId>,
+    active_sink_profile: Option<usize>,
+    active_source: Option<usize>,
+    active_source_device: Option<DeviceId>,
+    active_source_profile: Option<usize>,
+
+    changing_sink_profile: bool,
+    changing_source_profile: bool,
+}
+
+impl page::Page<crate::pages::Message> for Page {
+    fn content(
+        &self,
+        sections: &mut SlotMap<section::Entity, Section<crate::pages::Message>>,
+    ) -> Option<page::Content> {
+        Some(vec![
+            sections.insert(output()),
+            sections.insert(input()),
+            sections.insert(bluetooth::section()),
+            sections.insert(devices::section()),
+            sections.insert(policy::section()),
+            sections.insert(applications()),
+            sections.insert(alerts::section()),
+        ])
+    }
+
+    fn dialog(&self) -> Option<Element<'_, crate::pages::Message>> {
+        let playing = self.speaker_test?;
+        let channels = self.sink_channels.as_ref()?;
+
+        let device = self
+            .active_sink_device
+            .as_ref()
+            .and_then(|device_id| self.devices.get(device_id))
+            .and_then(|card| {
+                card.devices
+                    .values()
+                    .find(|device| device.identifier == self.default_sink)
+            })
+            .map_or(self.default_sink.as_str(), |device| {
+                device.description.as_str()
+            });
+
+        let mut buttons = widget::column::with_capacity(channels.labels.len()).spacing(8);
+
+        for (channel, label) in channels.labels.iter().enumerate() {
+            let button = if playing == Some(channel) {
+                widget::button::suggested(label.as_str())
+            } else {
+                widget::button::standard(label.as_str())
+            };
+
+            buttons = buttons.push(
+                button.width(cosmic::iced::Length::Fill).on_press_maybe(
+                    playing
+                        .is_none()
+                        .then_some(Message::SpeakerTestChannel(channel)),
+                ),
+            );
+        }
+
+        let dialog = widget::dialog(fl!("sound-speaker-test"))
+            .body(fl!("sound-speaker-test", "desc", device = device))
+            .control(buttons)
+            .secondary_action(
+                widget::button::standard(fl!("close")).on_press(Message::SpeakerTestClose),
+            );
+
+        Some(Element::from(dialog).map(crate::pages::Message::Sound))
+    }
+
+    fn info(&self) -> page::Info {
+        page::Info::new("sound", "preferences-sound-symbolic")
+            .title(fl!("sound"))
+            .description(fl!("sound", "desc"))
+    }
+
+    fn on_enter(
+        &mut self,
+        _page: cosmic_settings_page::Entity,
+        sender: tokio::sync::mpsc::Sender<crate::pages::Message>,
+    ) -> Command<crate::pages::Message> {
+        self.sender = Some(sender.clone());
+
+        if self.pulse_thread.is_none() {
+            let sender = sender.clone();
+
+            let (tx, mut rx) = futures::channel::mpsc::channel(1);
+            let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel::<()>();
+
+            // Listen to events from the pulse thread until the tx channel is closed.
+            _ = std::thread::spawn(move || {
+                pulse::thread(tx);
+            });
+
+            // Forward events from the pulse thread to the application until
+            // the application requests to stop listening to the pulse thread.
+            tokio::task::spawn(async move {
+                let forwarder = std::pin::pin!(async move {
+                    while let Some(event) = rx.next().await {
+                        let event = crate::pages::Message::Sound(Message::Pulse(event));
+                        if sender.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                futures::future::select(std::pin::pin!(cancel_rx), forwarder).await;
+            });
+
+            self.pulse_thread = Some(cancel_tx);
+        }
+
+        if self.audio_config.is_none() {
+            self.audio_config = cosmic_config::Config::new(AUDIO_CONFIG, 1)
+                .inspect_err(|why| tracing::error!(?why, "failed to open audio config"))
+                .ok();
+        }
+
+        self.alerts.load(self.audio_config.as_ref());
+        self.device_options.load(self.audio_config.as_ref());
+        self.policy.load(self.audio_config.as_ref());
+        self.echo_cancel = echo_cancel::is_enabled();
+        self.amplification_sink = self
+            .audio_config
+            .as_ref()
+            .map_or(false, |config| get(config, AMPLIFICATION_SINK, false));
+
+        let mut commands = Vec::new();
+
+        if !self.devices_enumerated {
+            // PipeWire adds the connected devices at once, so devices added after a moment
+            // were connected while the page was open.
+            commands.push(cosmic::command::future(async {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                crate::pages::Message::Sound(Message::DevicesEnumerated)
+            }));
+        }
+
+        if self.server.is_none() {
+            let client = server::Client::spawn();
+
+            commands.push(cosmic::command::future({
+                let client = client.clone();
+                async move {
+                    let volume = client.alert_volume().await.unwrap_or_else(|why| {
+                        tracing::error!(?why, "failed to get alert volume");
+                        None
+                    });
+
+                    crate::pages::Message::Sound(Message::Alerts(alerts::Message::VolumeLoaded(
+                        volume,
+                    )))
+                }
+            }));
+
+            self.server = Some(client);
+        }
+
+        if let (None, Some(client)) = (&self.streams_thread, &self.server) {
+            let sender = sender.clone();
+
+            let (tx, mut rx) = futures::channel::mpsc::channel(1);
+            let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel::<()>();
+
+            // Listen to application streams until the tx channel is closed.
+            streams::watch(client, tx);
+
+            tokio::task::spawn(async move {
+                let forwarder = std::pin::pin!(async move {
+                    while let Some(streams) = rx.next().await {
+                        let event = crate::pages::Message::Sound(Message::Streams(streams));
+                        if sender.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                futures::future::select(std::pin::pin!(cancel_rx), forwarder).await;
+            });
+
+            self.streams_thread = Some(cancel_tx);
+        }
+
+        if self.pipewire_thread.is_none() {
+            let (tx, mut rx) = futures::channel::mpsc::channel(1);
+            let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel::<()>();
+
+            // Listen to events from the pipewire thread until the tx channel is closed.
+            let (_handle, terminate) = pipewire::thread(tx);
+
+            // Forward events from the pipewire thread to the application until
+            // the application requests to stop listening to the pulse thread.
+            tokio::task::spawn(async move {
+                let forwarder = std::pin::pin!(async move {
+                    while let Some(event) = rx.next().await {
+                        let event = crate::pages::Message::Sound(Message::Pipewire(event));
+                        if sender.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                futures::future::select(std::pin::pin!(cancel_rx), forwarder).await;
+            });
+
+            self.pipewire_thread = Some((cancel_tx, terminate));
+        }
+
+        Command::batch(commands)
+    }
+
+    fn on_leave(&mut self) -> Command<crate::pages::Message> {
+        if let Some(cancellation) = self.pulse_thread.take() {
+            _ = cancellation.send(());
+        }
+
+        if let Some(cancellation) = self.streams_thread.take() {
+            _ = cancellation.send(());
+        }
+
+        if let Some((_, cancellation)) = self.level_meter.take() {
+            _ = cancellation.send(());
+        }
+
+        if let Some((cancellation, terminate)) = self.pipewire_thread.take() {
+            _ = cancellation.send(());
+            _ = terminate.send(());
+        }
+
+        *self = Page::default();
+
+        Command::none()
+    }
+}
+
+impl page::AutoBind<crate::pages::Message> for Page {}
+
+impl Page {
+    fn device_profiles(&self, device_id: &DeviceId) -> (Vec<String>, Vec<String>, Option<usize>) {
+        let (profiles, profile_descriptions): (Vec<String>, Vec<String>) = self
+            .card_profiles
+            .get(device_id)
+            .map_or((Vec::new(), Vec::new()), |profiles| {
+                profiles
+                    .iter()
+                    // TODO: Allow disabling
+                    .filter(|p| p.available && p.description != "Off")
+                    .map(|p| (p.name.clone(), p.description.clone()))
+                    .collect()
+            });
+
+        let active_profile = self.active_profiles.get(device_id).and_then(|profile| {
+            profile
+                .as_ref()
+                .and_then(|profile| profiles.iter().position(|p| p == profile))
+        });
+
+        (profiles, profile_descriptions, active_profile)
+    }
+
+    fn set_sink_profiles(&mut self, device_id: &DeviceId) {
+        (
+            self.sink_profile_names,
+            self.sink_profiles,
+            self.active_sink_profile,
+        ) = self.device_profiles(device_id);
+    }
+
+    fn set_source_profiles(&mut self, device_id: &DeviceId) {
+        (
+            self.source_profile_names,
+            self.source_profiles,
+            self.active_source_profile,
+        ) = self.device_profiles(device_id);
+    }
+
+    fn set_default_sink(&mut self, sink: String) {
+        if self.default_sink == sink {
+            return;
+        }
+
+        let previous = std::mem::replace(&mut self.default_sink, sink);
+        policy::remember(&mut self.sink_history, previous);
+        self.update_device_lists();
+        self.active_sink_profile = None;
+        self.sink_profiles.clear();
+        self.sink_profile_names.clear();
+
+        for (device_id, card) in &self.devices {
+            for (&node_id, device) in &card.devices {
+                if let pipewire::MediaClass::Sink = device.class {
+                    if device.identifier == self.default_sink {
+                        self.active_sink = self.sink_ids.iter().position(|&id| id == node_id);
+                        let device_id = device_id.clone();
+                        self.set_sink_profiles(&device_id);
+                        self.active_sink_device = Some(device_id);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    fn set_default_source(&mut self, source: String) {
+        if self.default_source == source {
+            return;
+        }
+
+        let previous = std::mem::replace(&mut self.default_source, source);
+        policy::remember(&mut self.source_history, previous);
+        self.update_device_lists();
+        self.watch_source_level();
+        self.active_source_profile = None;
+        self.source_profiles.clear();
+        self.source_profile_names.clear();
+
+        for (device_id, card) in &self.devices {
+            for (&node_id, device) in &card.devices {
+                if let pipewire::MediaClass::Source = device.class {
+                    if device.identifier == self.default_source {
+                        self.active_source = self.source_ids.iter().position(|&id| id == node_id);
+                        let device_id = device_id.clone();
+                        self.set_source_profiles(&device_id);
+                        self.active_source_device = Some(device_id);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Measures the level of the default source, replacing the measurement of the
+    /// previous one.
+    fn watch_source_level(&mut self) {
+        if let Some((source, _)) = self.level_meter.as_ref() {
+            if *source == self.default_source {
+                return;
+            }
+        }
+
+        if let Some((_, cancellation)) = self.level_meter.take() {
+            _ = cancellation.send(());
+        }
+
+        self.source_level = 0.0;
+
+        let (Some(sender), Some(client)) = (self.sender.clone(), self.server.as_ref()) else {
+            return;
+        };
+
+        if self.default_source.is_empty() {
+            return;
+        }
+
+        let source = self.default_source.clone();
+        let (tx, mut rx) = futures::channel::mpsc::channel(1);
+        let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel::<()>();
+
+        // Measure the level until the tx channel is closed.
+        level::watch(client, source.clone(), tx);
+
+        tokio::task::spawn(async move {
+            let forwarder = std::pin::pin!(async move {
+                while let Some(level) = rx.next().await {
+                    let event = crate::pages::Message::Sound(Message::SourceLevel(level));
+                    if sender.send(event).await.is_err() {
+                        break;
+                    }
+                }
+            });
+
+            futures::future::select(std::pin::pin!(cancel_rx), forwarder).await;
+        });
+
+        self.level_meter = Some((source, cancel_tx));
+    }
+
+    /// The node name of a device, by which the sound server knows it.
+    fn node_name(&self, node_id: NodeId) -> Option<String> {
+        self.devices
+            .values()
+            .find_map(|card| card.devices.get(&node_id))
+            .map(|device| device.identifier.clone())
+    }
+
+    /// Fetches the channels of the default output, such as after its volume changed.
+    fn refresh_sink_channels(&self) -> Command<crate::app::Message> {
+        let Some(client) = self.server.clone() else {
+            return Command::none();
+        };
+
+        let sink = self.default_sink.clone();
+
+        cosmic::command::future(async move {
+            let channels = client
+                .channels(sink)
+                .await
+                .inspect_err(|why| tracing::error!(?why, "failed to get output channels"))
+                .ok();
+
+            Message::SinkChannels(channels)
+        })
+        .map(crate::pages::Message::Sound)
+        .map(crate::app::Message::PageMessage)
+    }
+
+    /// Highest volume of the output, as a percentage, which depends on whether it is amplified.
+    fn max_sink_volume(&self) -> u32 {
+        if self.amplification_sink {
+            150
+        } else {
+            100
+        }
+    }
+
+    /// Sends a request to the sound server, reporting back whether it was applied.
+    fn server_request(&self, request: server::Request) -> Command<crate::app::Message> {
+        let Some(client) = self.server.clone() else {
+            return Command::none();
+        };
+
+        cosmic::command::future(async move { Message::ServerResult(client.apply(request).await) })
+            .map(crate::pages::Message::Sound)
+            .map(crate::app::Message::PageMessage)
+    }
+
+    /// Changes a card profile, and then selects a device of that card with `on_select`.
+    fn set_card_profile(
+        &self,
+        card: String,
+        profile: String,
+        select: DeviceId,
+        on_select: fn(DeviceId) -> Message,
+    ) -> Command<crate::app::Message> {
+        let Some(client) = self.server.clone() else {
+            return Command::none();
+        };
+
+        cosmic::command::future(async move {
+            match client
+                .apply(server::Request::CardProfile { card, profile })
+                .await
+            {
+                Ok(()) => on_select(select),
+                Err(why) => Message::ServerResult(Err(why)),
+            }
+        })
+        .map(crate::pages::Message::Sound)
+        .map(crate::app::Message::PageMessage)
+    }
+
+    pub fn update(&mut self, message: Message) -> Command<crate::app::Message> {
+        match message {
+            Message::SourceVolumeChanged(volume) => {
+                self.source_volume = volume;
+                self.source_volume_text = volume.to_string();
+                if self.source_volume_debounce {
+                    return Command::none();
+                }
+
+                let mut command = None;
+                if let Some(&node_id) = self.source_ids.get(self.active_source.unwrap_or(0)) {
+                    command = Some(cosmic::command::future(async move {
+                        tokio::time::sleep(Duration::from_millis(500)).await;
+                        crate::pages::Message::Sound(Message::SourceVolumeApply(node_id))
+                    }));
+                }
+
+                if let Some(command) = command {
+                    self.source_volume_debounce = true;
+                    return command;
+                }
+            }
+
+            Message::Pulse(pulse::Event::SourceVolume(volume)) => {
+                if self.source_volume_debounce {
+                    return Command::none();
+                }
+
+                self.source_volume = volume;
+                self.source_volume_text = volume.to_string();
+            }
+
+            Message::SinkVolumeChanged(volume) => {
+                self.sink_volume = volume;
+                self.sink_volume_text = volume.to_string();
+                if self.sink_volume_debounce {
+                    return Command::none();
+                }
+
+                let mut command = None;
+                if let Some(&node_id) = self.sink_ids.get(self.active_sink.unwrap_or(0)) {
+                    command = Some(cosmic::command::future(async move {
+                        tokio::time::sleep(Duration::from_millis(500)).await;
+                        crate::pages::Message::Sound(Message::SinkVolumeApply(node_id))
+                    }));
+                }
+
+                if let Some(command) = command {
+                    self.sink_volume_debounce = true;
+                    return command;
+                }
+            }
+
+            Message::Pulse(pulse::Event::SinkVolume(volume)) => {
+                if self.sink_volume_debounce {
+                    return Command::none();
+                }
+
+                self.sink_volume = volume;
+                self.sink_volume_text = volume.to_string();
+                return self.refresh_sink_channels();
+            }
+
+            Message::Pulse(pulse::Event::DefaultSink(sink)) => {
+                if !self.changing_sink_profile {
+                    self.set_default_sink(sink);
+                    return self.refresh_sink_channels();
+                }
+            }
+
+            Message::SinkBalanceChanged(balance) => {
+                if let Some(channels) = self.sink_channels.as_mut() {
+                    channels.balance = Some(balance as f32 / 100.0);
+                }
+            }
+
+            Message::SinkBalanceApply => {
+                if let Some(balance) = self.sink_channels.as_ref().and_then(|c| c.balance) {
+                    let request = server::Request::SinkBalance(
+                        self.default_sink.clone(),
+                        balance,
+                        self.max_sink_volume(),
+                    );
+                    return self.server_request(request);
+                }
+            }
+
+            Message::SinkFadeChanged(fade) => {
+                if let Some(channels) = self.sink_channels.as_mut() {
+                    channels.fade = Some(fade as f32 / 100.0);
+                }
+            }
+
+            Message::SinkFadeApply => {
+                if let Some(fade) = self.sink_channels.as_ref().and_then(|c| c.fade) {
+                    let request = server::Request::SinkFade(
+                        self.default_sink.clone(),
+                        fade,
+                        self.max_sink_volume(),
+                    );
+                    return self.server_request(request);
+                }
+            }
+
+            Message::SinkChannelVolumeChanged(channel, volume) => {
+                if let Some(channels) = self.sink_channels.as_mut() {
+                    if let Some(current) = channels.volumes.get_mut(channel) {
+                        *current = volume;
+                    }
+                }
+            }
+
+            Message::SinkChannelVolumesApply => {
+                if let Some(channels) = self.sink_channels.as_ref() {
+                    let request = server::Request::SinkChannelVolumes(
+                        self.default_sink.clone(),
+                        channels.volumes.clone(),
+                        self.max_sink_volume(),
+                    );
+
+                    return self.server_request(request);
+                }
+            }
+
+            Message::SinkChannelsToggle => {
+                self.sink_channels_expanded = !self.sink_channels_expanded;
+            }
+
+            Message::SinkChannels(channels) => {
+                self.sink_channels = channels;
+            }
+
+            Message::EchoCancel(enable) => {
+                if let Err(why) = echo_cancel::persist(enable) {
+                    tracing::error!(?why, "failed to write noise suppression config");
+                    return Command::none();
+                }
+
+                self.echo_cancel = enable;
+
+                let Some(client) = self.server.clone() else {
+                    return Command::none();
+                };
+
+                return cosmic::command::future(async move {
+                    let result = if enable {
+                        echo_cancel::load(&client).await
+                    } else {
+                        echo_cancel::unload(&client).await
+                    };
+
+                    Message::EchoCancelResult(enable, result)
+                })
+                .map(crate::pages::Message::Sound)
+                .map(crate::app::Message::PageMessage);
+            }
+
+            Message::EchoCancelResult(enable, result) => {
+                if result.is_ok() && enable {
+                    let source = echo_cancel::SOURCE_NAME.to_owned();
+                    return self.server_request(server::Request::DefaultSource(source));
+                }
+
+                // Keep the config and the toggle as they were if the server didn't follow.
+                if result.is_err() {
+                    if let Err(why) = echo_cancel::persist(!enable) {
+                        tracing::error!(?why, "failed to restore noise suppression config");
+                    }
+
+                    self.echo_cancel = echo_cancel::is_enabled();
+                }
+
+                return self.update(Message::ServerResult(result));
+            }
+
+            Message::SourceLevel(level) => {
+                self.source_level = level;
+            }
+
+            Message::SpeakerTest => {
+                self.speaker_test = Some(None);
+            }
+
+            Message::SpeakerTestClose => {
+                self.speaker_test = None;
+            }
+
+            Message::SpeakerTestChannel(channel) => {
+                let Some(&position) = self
+                    .sink_channels
+                    .as_ref()
+                    .and_then(|channels| channels.positions.get(channel))
+                else {
+                    return Command::none();
+                };
+
+                let Some(client) = self.server.clone() else {
+                    return Command::none();
+                };
+
+                self.speaker_test = Some(Some(channel));
+                let sink = self.default_sink.clone();
+
+                return cosmic::command::future(async move {
+                    Message::SpeakerTestResult(speaker_test::play(&client, sink, position).await)
+                })
+                .map(crate::pages::Message::Sound)
+                .map(crate::app::Message::PageMessage);
+            }
+
+            Message::SpeakerTestResult(result) => {
+                if let Err(why) = result {
+                    tracing::error!(?why, "failed to play test tone");
+                }
+
+                if self.speaker_test.is_some() {
+                    self.speaker_test = Some(None);
+                }
+            }
+
+            Message::Pulse(pulse::Event::DefaultSource(source)) => {
+                if !self.changing_source_profile {
+                    self.set_default_source(source);
+                }
+            }
+
+            Message::Pulse(pulse::Event::SinkMute(mute)) => {
+                self.sink_mute = mute;
+            }
+
+            Message::Pulse(pulse::Event::SourceMute(mute)) => {
+                self.source_mute = mute;
+            }
+
+            Message::Pulse(pulse::Event::CardInfo(card)) => {
+                let device_id = match card.variant {
+                    pulse::DeviceVariant::Alsa { alsa_card, .. } => DeviceId::Alsa(alsa_card),
+                    pulse::DeviceVariant::Bluez5 { address, .. } => DeviceId::Bluez5(address),
+                };
+
+                self.card_names.insert(device_id.clone(), card.name);
+                self.card_profiles.insert(device_id.clone(), card.profiles);
+                self.active_profiles
+                    .insert(device_id.clone(), card.active_profile.map(|p| p.name));
+
+                self.update_bluetooth_cards();
+                return self.refresh_bluetooth_codec(&device_id);
+            }
+
+            Message::Pipewire(pipewire::DeviceEvent::Add(device)) => {
+                let device_id = match device.variant {
+                    pipewire::DeviceVariant::Alsa { alsa_card, .. } => DeviceId::Alsa(alsa_card),
+                    pipewire::DeviceVariant::Bluez5 { address, .. } => DeviceId::Bluez5(address),
+                };
+
+                let is_sink = matches!(device.media_class, pipewire::MediaClass::Sink);
+                let is_default = if is_sink {
+                    self.default_sink == device.node_name
+                } else {
+                    self.default_source == device.node_name
+                };
+
+                let node_name = device.node_name.clone();
+
+                let card = self
+                    .devices
+                    .entry(device_id.clone())
+                    .or_insert_with(|| Card {
+                        devices: IndexMap::new(),
+                    });
+
+                card.devices.insert(
+                    device.object_id,
+                    Device {
+                        class: device.media_class,
+                        identifier: device.node_name,
+                        description: device.node_description,
+                    },
+                );
+
+                card.devices
+                    .sort_unstable_by(|_, av, _, bv| av.description.cmp(&bv.description));
+
+                self.update_device_lists();
+                self.update_bluetooth_cards();
+
+                let codec = if is_sink {
+                    self.refresh_bluetooth_codec(&device_id)
+                } else {
+                    Command::none()
+                };
+
+                if is_default {
+                    if is_sink {
+                        self.active_sink_device = Some(device_id.clone());
+                        self.set_sink_profiles(&device_id);
+                    } else {
+                        self.active_source_device = Some(device_id.clone());
+                        self.set_source_profiles(&device_id);
+                    }
+                } else if self.devices_enumerated {
+                    let switch = self.device_connected(is_sink, node_name);
+                    return Command::batch(vec![codec, switch]);
+                }
+
+                return codec;
+            }
+
+            Message::Bluetooth(message) => return self.update_bluetooth(message),
+
+            Message::DevicesEnumerated => {
+                self.devices_enumerated = true;
+            }
+
+            Message::Devices(message) => return self.update_devices(message),
+
+            Message::Policy(message) => return self.update_policy(message),
+
+            Message::Pipewire(pipewire::DeviceEvent::Remove(node_id)) => {
+                let active_sink = self.active_sink.and_then(|pos| self.sink_ids.get(pos));
+                let active_source = self.active_source.and_then(|pos| self.source_ids.get(pos));
+                let was_active_sink = active_sink == Some(&node_id);
+                let was_active_source = active_source == Some(&node_id);
+
+                let removed = self
+                    .devices
+                    .values()
+                    .find_map(|card| card.devices.get(&node_id))
+                    .map(|device| {
+                        let is_sink = matches!(device.class, pipewire::MediaClass::Sink);
+                        (is_sink, device.identifier.clone())
+                    });
+
+                let mut remove = None;
+                for (card_id, card) in &mut self.devices {
+                    if card.devices.remove(&node_id).is_some() {
+                        if card.devices.is_empty() {
+                            remove = Some(card_id.clone());
+                        }
+                        break;
+                    }
+                }
+
+                if let Some(card_id) = remove {
+                    _ = self.devices.remove(&card_id);
+                }
+
+                self.update_device_lists();
+                self.update_bluetooth_cards();
+
+                if was_active_sink {
+                    self.active_sink = None;
+                    self.active_sink_device = None;
+                    self.active_sink_profile = None;
+                } else if was_active_source {
+                    self.active_source = None;
+                    self.active_source_device = None;
+                    self.active_source_profile = None;
+                }
+
+                if let Some((is_sink, node_name)) = removed {
+                    return self.device_disconnected(is_sink, &node_name);
+                }
+            }
+
+            Message::SinkChanged(pos) => {
+                if let Some(node_id) = self.sink_ids.get(pos) {
+                    for card in self.devices.values() {
+                        for (nid, device) in &card.devices {
+                            if node_id == nid {
+                                let name = device.identifier.clone();
+                                self.active_sink = Some(pos);
+                                self.set_default_sink(name.clone());
+                                return self.server_request(server::Request::DefaultSink(name));
+                            }
+                        }
+                    }
+                }
+            }
+
+            Message::SourceChanged(pos) => {
+                if let Some(node_id) = self.source_ids.get(pos) {
+                    for card in self.devices.values() {
+                        for (nid, device) in &card.devices {
+                            if node_id == nid {
+                                let name = device.identifier.clone();
+                                self.active_source = Some(pos);
+                                self.set_default_source(name.clone());
+                                return self.server_request(server::Request::DefaultSource(name));
+                            }
+                        }
+                    }
+                }
+            }
+
+            Message::SinkVolumeApply(node_id) => {
+                self.sink_volume_debounce = false;
+                if let Some(name) = self.node_name(node_id) {
+                    return self
+                        .server_request(server::Request::SinkVolume(name, self.sink_volume));
+                }
+            }
+
+            Message::SourceVolumeApply(node_id) => {
+                self.source_volume_debounce = false;
+                if let Some(name) = self.node_name(node_id) {
+                    return self
+                        .server_request(server::Request::SourceVolume(name, self.source_volume));
+                }
+            }
+
+            Message::SinkMuteToggle => {
+                self.sink_mute = !self.sink_mute;
+                if let Some(&node_id) = self.sink_ids.get(self.active_sink.unwrap_or(0)) {
+                    if let Some(name) = self.node_name(node_id) {
+                        return self
+                            .server_request(server::Request::SinkMute(name, self.sink_mute));
+                    }
+                }
+            }
+
+            Message::SinkAmplification(enable) => {
+                self.amplification_sink = enable;
+
+                if let Some(config) = self.audio_config.as_ref() {
+                    set(config, AMPLIFICATION_SINK, enable);
+                }
+
+                // Bring an amplified volume back within the new limit.
+                if !enable && self.sink_volume > 100 {
+                    self.sink_volume = 100;
+                    self.sink_volume_text = self.sink_volume.to_string();
+
+                    if let Some(&node_id) = self.sink_ids.get(self.active_sink.unwrap_or(0)) {
+                        if let Some(name) = self.node_name(node_id) {
+                            return self.server_request(server::Request::SinkVolume(name, 100));
+                        }
+                    }
+                }
+            }
+
+            Message::SourceMuteToggle => {
+                self.source_mute = !self.source_mute;
+                if let Some(&node_id) = self.source_ids.get(self.active_source.unwrap_or(0)) {
+                    if let Some(name) = self.node_name(node_id) {
+                        return self
+                            .server_request(server::Request::SourceMute(name, self.source_mute));
+                    }
+                }
+            }
+
+            Message::ServerResult(Ok(())) => {
+                self.server_error = None;
+            }
+
+            Message::ServerResult(Err(why)) => {
+                tracing::error!(?why, "sound server rejected a request");
+                self.server_error = Some(why);
+                self.changing_sink_profile = false;
+                self.changing_source_profile = false;
+
+                // Correct the page to show what the server actually uses.
+                if let Some(client) = self.server.clone() {
+                    return cosmic::command::future(async move {
+                        Message::ServerState(client.state().await)
+                    })
+                    .map(crate::pages::Message::Sound)
+                    .map(crate::app::Message::PageMessage);
+                }
+            }
+
+            Message::Alerts(message) => return self.update_alerts(message),
+
+            Message::Streams(streams) => {
+                self.streams = streams;
+            }
+
+            Message::StreamVolume(pos, volume) => {
+                if let Some(stream) = self.streams.get_mut(pos) {
+                    stream.volume = volume;
+                    let request =
+                        server::Request::StreamVolume(stream.direction, stream.index, volume);
+                    return self.server_request(request);
+                }
+            }
+
+            Message::StreamMuteToggle(pos) => {
+                if let Some(stream) = self.streams.get_mut(pos) {
+                    stream.mute = !stream.mute;
+                    let request =
+                        server::Request::StreamMute(stream.direction, stream.index, stream.mute);
+                    return self.server_request(request);
+                }
+            }
+
+            Message::StreamDevice(pos, device) => {
+                let Some(stream) = self.streams.get(pos) else {
+                    return Command::none();
+                };
+
+                let node_ids = match stream.direction {
+                    streams::Direction::Playback => &self.sink_ids,
+                    streams::Direction::Recording => &self.source_ids,
+                };
+
+                let Some(name) = node_ids.get(device).and_then(|&id| self.node_name(id)) else {
+                    return Command::none();
+                };
+
+                let request =
+                    server::Request::StreamDevice(stream.direction, stream.index, name.clone());
+
+                if let Some(stream) = self.streams.get_mut(pos) {
+                    stream.device = name;
+                }
+
+                return self.server_request(request);
+            }
+
+            Message::ServerState(Err(why)) => {
+                tracing::error!(?why, "failed to get sound server state");
+            }
+
+            Message::ServerState(Ok(state)) => {
+                for (card, profile) in state.profiles {
+                    if let Some((device_id, _)) =
+                        self.card_names.iter().find(|(_, name)| **name == card)
+                    {
+                        self.active_profiles.insert(device_id.clone(), profile);
+                    }
+                }
+
+                self.update_bluetooth_cards();
+
+                // Force the device lookups to be redone with the corrected profiles.
+                self.default_sink.clear();
+                self.default_source.clear();
+                self.set_default_sink(state.default_sink);
+                self.set_default_source(state.default_source);
+
+                if let Some((volume, mute)) = state.sink {
+                    self.sink_volume = volume;
+                    self.sink_volume_text = volume.to_string();
+                    self.sink_mute = mute;
+                }
+
+                if let Some((volume, mute)) = state.source {
+                    self.source_volume = volume;
+                    self.source_volume_text = volume.to_string();
+                    self.source_mute = mute;
+                }
+
+                return self.refresh_sink_channels();
+            }
+
+            Message::SinkProfileChanged(profile) => {
+                self.active_sink_profile = Some(profile);
+
+                if let Some(profile) = self.sink_profile_names.get(profile).cloned() {
+                    if let Some(device_id) = self.active_sink_device.clone() {
+                        if let Some(name) = self.card_names.get(&device_id).cloned() {
+                            self.active_profiles
+                                .insert(device_id.clone(), Some(profile.clone()));
+                            self.update_bluetooth_cards();
+
+                            self.changing_sink_profile = true;
+                            return self.set_card_profile(
+                                name,
+                                profile,
+                                device_id,
+                                Message::SinkProfileSelect,
+                            );
+                        }
+                    }
+                }
+            }
+
+            Message::SinkProfileSelect(device_id) => {
+                self.changing_sink_profile = false;
+                let sink_pos = self.active_sink.unwrap_or(0);
+
+                if let Some(card) = self.devices.get(&device_id) {
+                    if let Some((_, device)) = card.devices.get_index(sink_pos) {
+                        let name = device.identifier.clone();
+                        self.set_default_sink(name.clone());
+                        return self.server_request(server::Request::DefaultSink(name));
+                    }
+                }
+            }
+
+            Message::SourceProfileChanged(profile) => {
+                self.active_source_profile = Some(profile);
+                if let Some(profile) = self.source_profile_names.get(profile).cloned() {
+                    if let Some(device_id) = self.active_source_device.clone() {
+                        if let Some(name) = self.card_names.get(&device_id).cloned() {
+                            self.active_profiles
+                                .insert(device_id.clone(), Some(profile.clone()));
+                            self.update_bluetooth_cards();
+
+                            self.changing_source_profile = true;
+                            return self.set_card_profile(
+                                name,
+                                profile,
+                                device_id,
+                                Message::SourceProfileSelect,
+                            );
+                        }
+                    }
+                }
+            }
+
+            Message::SourceProfileSelect(device_id) => {
+                self.changing_source_profile = false;
+                let source_pos = self.active_source.unwrap_or(0);
+
+                if let Some(card) = self.devices.get(&device_id) {
+                    if let Some((_, device)) = card.devices.get_index(source_pos) {
+                        let name = device.identifier.clone();
+                        self.set_default_source(name.clone());
+                        return self.server_request(server::Request::DefaultSource(name));
+                    }
+                }
+            }
+        }
+        Command::none()
+    }
+}
+
+fn input() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let volume = descriptions.insert(fl!("sound-input", "volume"));
+    let device = descriptions.insert(fl!("sound-input", "device"));
+    let level = descriptions.insert(fl!("sound-input", "level"));
+    let noise_suppression = descriptions.insert(fl!("sound-input", "noise-suppression"));
+    let profile = descriptions.insert(fl!("profile"));
+
+    Section::default()
+        .title(fl!("sound-input"))
+        .descriptions(descriptions)
+        .view::<Page>(move |_binder, page, section| {
+            let volume_control = widget::row::with_capacity(3)
+                .align_items(cosmic::iced::Alignment::Center)
+                .spacing(4)
+                .push(
+                    widget::button::icon(widget::icon::from_name(if page.source_mute {
+                        "microphone-sensitivity-muted-symbolic"
+                    } else {
+                        "audio-input-microphone-symbolic"
+                    }))
+                    .on_press(Message::SourceMuteToggle),
+                )
+                .push(widget::text::body(&page.source_volume_text))
+                .push(
+                    widget::slider(0..=150, page.source_volume, Message::SourceVolumeChanged)
+                        .breakpoints(&[100]),
+                );
+
+            let devices = widget::dropdown(
+                &page.sources,
+                Some(page.active_source.unwrap_or(0)),
+                Message::SourceChanged,
+            );
+
+            let mut controls = settings::view_section(&section.title)
+                .add(settings::flex_item(
+                    &*section.descriptions[volume],
+                    volume_control,
+                ))
+                .add(settings::flex_item(
+                    &*section.descriptions[level],
+                    cosmic::iced_widget::progress_bar(0.0..=1.0, page.source_level)
+                        .height(cosmic::iced::Length::Fixed(4.0)),
+                ))
+                .add(settings::item(&*section.descriptions[device], devices))
+                .add(settings::item(
+                    &*section.descriptions[noise_suppression],
+                    widget::toggler(None, page.echo_cancel, Message::EchoCancel),
+                ));
+
+            if !page.source_profiles.is_empty() {
+                let dropdown = widget::dropdown(
+                    &page.source_profiles,
+                    page.active_source_profile,
+                    Message::SourceProfileChanged,
+                );
+
+                controls = controls.add(settings::item(&*section.descriptions[profile], dropdown));
+            }
+
+            Element::from(controls).map(crate::pages::Message::Sound)
+        })
+}
+
+fn output() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let volume = descriptions.insert(fl!("sound-output", "volume"));
+    let device = descriptions.insert(fl!("sound-output", "device"));
+    let _level = descriptions.insert(fl!("sound-output", "level"));
+    let profile = descriptions.insert(fl!("profile"));
+    let balance = descriptions.insert(fl!("sound-output", "balance"));
+    let fade = descriptions.insert(fl!("sound-output", "fade"));
+    let channels = descriptions.insert(fl!("sound-output", "channels"));
+    let left = descriptions.insert(fl!("sound-output", "balance-left"));
+    let right = descriptions.insert(fl!("sound-output", "balance-right"));
+    let rear = descriptions.insert(fl!("sound-output", "fade-rear"));
+    let front = descriptions.insert(fl!("sound-output", "fade-front"));
+    let speakers = descriptions.insert(fl!("sound-speaker-test"));
+    let amplification = descriptions.insert(fl!("sound-output", "amplification"));
+
+    Section::default()
+        .title(fl!("sound-output"))
+        .descriptions(descriptions)
+        .view::<Page>(move |_binder, page, section| {
+            let volume_control = widget::row::with_capacity(3)
+                .align_items(cosmic::iced::Alignment::Center)
+                .spacing(4)
+                .push(
+                    widget::button::icon(if page.sink_mute {
+                        widget::icon::from_name("audio-volume-muted-symbolic")
+                    } else {
+                        widget::icon::from_name("audio-volume-high-symbolic")
+                    })
+                    .on_press(Message::SinkMuteToggle),
+                )
+                .push(widget::text::body(&page.sink_volume_text))
+                .push(if page.amplification_sink {
+                    widget::slider(0..=150, page.sink_volume, Message::SinkVolumeChanged)
+                        .breakpoints(&[100])
+                } else {
+                    widget::slider(0..=100, page.sink_volume, Message::SinkVolumeChanged)
+                });
+
+            let devices = widget::dropdown(
+                &page.sinks,
+                Some(page.active_sink.unwrap_or(0)),
+                Message::SinkChanged,
+            );
+
+            let mut controls = settings::view_section(&section.title);
+
+            if let Some(why) = page.server_error.as_ref() {
+                controls = controls.add(settings::item_row(vec![widget::text::body(
+                    why.to_string(),
+                )
+                .into()]));
+            }
+
+            controls = controls
+                .add(settings::flex_item(
+                    &*section.descriptions[volume],
+                    volume_control,
+                ))
+                .add(settings::item(
+                    &*section.descriptions[amplification],
+                    widget::toggler(None, page.amplification_sink, Message::SinkAmplification),
+                ))
+                .add(settings::item(&*section.descriptions[device], devices));
+
+            if !page.sink_profiles.is_empty() {
+                let dropdown = widget::dropdown(
+                    &page.sink_profiles,
+                    page.active_sink_profile,
+                    Message::SinkProfileChanged,
+                );
+
+                controls = controls.add(settings::item(&*section.descriptions[profile], dropdown));
+            }
+
+            if let Some(sink_channels) = page.sink_channels.as_ref() {
+                // A slider between two labelled ends, such as left and right.
+                let between = |start: usize,
+                               value: f32,
+                               end: usize,
+                               on_change: fn(i32) -> Message,
+                               on_release: Message| {
+                    widget::row::with_capacity(3)
+                        .align_items(cosmic::iced::Alignment::Center)
+                        .spacing(8)
+                        .push(widget::text::body(&*section.descriptions[start]))
+                        .push(
+                            widget::slider(-100..=100, (value * 100.0).round() as i32, on_change)
+                                .breakpoints(&[0])
+                                .on_release(on_release),
+                        )
+                        .push(widget::text::body(&*section.descriptions[end]))
+                };
+
+                if let Some(value) = sink_channels.balance {
+                    controls = controls.add(settings::flex_item(
+                        &*section.descriptions[balance],
+                        between(
+                            left,
+                            value,
+                            right,
+                            Message::SinkBalanceChanged,
+                            Message::SinkBalanceApply,
+                        ),
+                    ));
+                }
+
+                if let Some(value) = sink_channels.fade {
+                    controls = controls.add(settings::flex_item(
+                        &*section.descriptions[fade],
+                        between(
+                            rear,
+                            value,
+                            front,
+                            Message::SinkFadeChanged,
+                            Message::SinkFadeApply,
+                        ),
+                    ));
+                }
+
+                controls = controls.add(settings::item(
+                    &*section.descriptions[speakers],
+                    widget::button::standard(fl!("sound-speaker-test", "start"))
+                        .on_press(Message::SpeakerTest),
+                ));
+
+                if sink_channels.volumes.len() > 1 {
+                    controls = controls.add(settings::item(
+                        &*section.descriptions[channels],
+                        widget::button::icon(widget::icon::from_name(
+                            if page.sink_channels_expanded {
+                                "go-up-symbolic"
+                            } else {
+                                "go-down-symbolic"
+                            },
+                        ))
+                        .on_press(Message::SinkChannelsToggle),
+                    ));
+
+                    if page.sink_channels_expanded {
+                        for (channel, (label, &volume)) in sink_channels
+                            .labels
+                            .iter()
+                            .zip(&sink_channels.volumes)
+                            .enumerate()
+                        {
+                            let slider = widget::row::with_capacity(2)
+                                .align_items(cosmic::iced::Alignment::Center)
+                                .spacing(4)
+                                .push(widget::text::body(volume.to_string()))
+                                .push(
+                                    // Limited like the output volume, unless it is amplified.
+                                    if page.amplification_sink {
+                                        widget::slider(0..=150, volume, move |volume| {
+                                            Message::SinkChannelVolumeChanged(channel, volume)
+                                        })
+                                        .breakpoints(&[100])
+                                    } else {
+                                        widget::slider(0..=100, volume, move |volume| {
+                                            Message::SinkChannelVolumeChanged(channel, volume)
+                                        })
+                                    }
+                                    .on_release(Message::SinkChannelVolumesApply),
+                                );
+
+                            controls = controls.add(settings::flex_item(label.as_str(), slider));
+                        }
+                    }
+                }
+            }
+
+            Element::from(controls).map(crate::pages::Message::Sound)
+        })
+}
+
+fn applications() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let _applications = descriptions.insert(fl!("sound-applications", "desc"));
+
+    Section::default()
+        .title(fl!("sound-applications"))
+        .descriptions(descriptions)
+        .show_while::<Page>(|page| !page.streams.is_empty())
+        .view::<Page>(move |_binder, page, section| {
+            let mut controls = settings::view_section(&section.title);
+
+            for (pos, stream) in page.streams.iter().enumerate() {
+                let (devices, node_ids, mute_icon, volume_icon) = match stream.direction {
+                    streams::Direction::Playback => (
+                        &page.sinks,
+                        &page.sink_ids,
+                        "audio-volume-muted-symbolic",
+                        "audio-volume-high-symbolic",
+                    ),
+                    streams::Direction::Recording => (
+                        &page.sources,
+                        &page.source_ids,
+                        "microphone-sensitivity-muted-symbolic",
+                        "audio-input-microphone-symbolic",
+                    ),
+                };
+
+                let active_device = node_ids
+                    .iter()
+                    .position(|&id| page.node_name(id).as_deref() == Some(stream.device.as_str()));
+
+                let volume_control = widget::row::with_capacity(3)
+                    .align_items(cosmic::iced::Alignment::Center)
+                    .spacing(4)
+                    .push(
+                        widget::button::icon(widget::icon::from_name(if stream.mute {
+                            mute_icon
+                        } else {
+                            volume_icon
+                        }))
+                        .on_press(Message::StreamMuteToggle(pos)),
+                    )
+                    .push(widget::text::body(stream.volume.to_string()))
+                    .push(
+                        widget::slider(0..=150, stream.volume, move |volume| {
+                            Message::StreamVolume(pos, volume)
+                        })
+                        .breakpoints(&[100]),
+                    );
+
+                let stream_row = widget::row::with_capacity(3)
+                    .align_items(cosmic::iced::Alignment::Center)
+                    .spacing(12)
+                    .push(widget::icon::from_name(&*stream.icon).size(32).icon())
+                    .push(
+                        widget::column::with_capacity(2)
+                            .spacing(4)
+                            .width(cosmic::iced::Length::Fill)
+                            .push(widget::text::body(&stream.name))
+                            .push(volume_control),
+                    )
+                    .push(widget::dropdown(devices, active_device, move |device| {
+                        Message::StreamDevice(pos, device)
+                    }));
+
+                controls = controls.add(settings::item_row(vec![stream_row.into()]));
+            }
+
+            Element::from(controls).map(crate::pages::Message::Sound)
+        })
+}
+
+/// Reads a sound setting, falling back to `default` if it is unset or invalid.
+fn get<T: serde::de::DeserializeOwned>(config: &cosmic_config::Config, key: &str, default: T) -> T {
+    config.get(key).unwrap_or_else(|why| {
+        if !matches!(why, cosmic_config::Error::NoConfigDirectory) {
+            tracing::error!(?why, "failed to read config '{key}'");
+        }
+
+        default
+    })
+}
+
+fn set<T: serde::Serialize>(config: &cosmic_config::Config, key: &str, value: T) {
+    if let Err(why) = config.set(key, value) {
+        tracing::error!(?why, "failed to set config '{key}'");
+    }
+}
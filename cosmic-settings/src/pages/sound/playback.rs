@@ -0,0 +1,114 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Samples played to the end on a stream of the sound server connection.
+
+use libpulse_binding::{
+    operation::{self, Operation},
+    stream::{self, SeekMode, Stream},
+};
+use tokio::sync::oneshot;
+
+use super::server::{Client, Connection, Error, Watch};
+
+struct Playback {
+    stream: Stream,
+    samples: Vec<u8>,
+    written: usize,
+    drain: Option<Operation<dyn FnMut(bool)>>,
+    result: Option<oneshot::Sender<Result<(), Error>>>,
+}
+
+/// Plays samples on the stream which `connect` creates, returning once they finished playing.
+///
+/// The samples must be in the format of the stream's sample spec.
+pub async fn play(
+    client: &Client,
+    samples: Vec<u8>,
+    connect: impl FnOnce(&mut Connection) -> Result<Stream, Error> + Send + 'static,
+) -> Result<(), Error> {
+    let (tx, rx) = oneshot::channel();
+
+    client.watch(move |connection| {
+        let stream = connect(connection)?;
+
+        Ok(Box::new(Playback {
+            stream,
+            samples,
+            written: 0,
+            drain: None,
+            result: Some(tx),
+        }))
+    });
+
+    rx.await
+        .unwrap_or_else(|_| Err(Error::Connection(String::from("playback was dropped"))))
+}
+
+pub fn failed(why: libpulse_binding::error::PAErr) -> Error {
+    Error::Failed(why.to_string().unwrap_or_default())
+}
+
+impl Playback {
+    /// Writes as many samples as the server accepts, and drains the stream once all of them
+    /// were written. Returns `None` until the samples finished playing.
+    fn write(&mut self, connection: &Connection) -> Option<Result<(), Error>> {
+        match self.stream.get_state() {
+            stream::State::Ready => (),
+            stream::State::Failed | stream::State::Terminated => {
+                return Some(Err(Error::Failed(
+                    connection.context.errno().to_string().unwrap_or_default(),
+                )));
+            }
+            _ => return None,
+        }
+
+        if let Some(drain) = self.drain.as_ref() {
+            return match drain.get_state() {
+                operation::State::Running => None,
+                operation::State::Done => Some(Ok(())),
+                operation::State::Cancelled => Some(Err(Error::Connection(
+                    connection.context.errno().to_string().unwrap_or_default(),
+                ))),
+            };
+        }
+
+        let writable = self.stream.writable_size().unwrap_or(0);
+        let end = self.samples.len().min(self.written + writable);
+
+        if end > self.written {
+            if let Err(why) = self.stream.write(
+                &self.samples[self.written..end],
+                None,
+                0,
+                SeekMode::Relative,
+            ) {
+                return Some(Err(failed(why)));
+            }
+
+            self.written = end;
+        }
+
+        if self.written == self.samples.len() {
+            self.drain = Some(self.stream.drain(None));
+        }
+
+        None
+    }
+}
+
+impl Watch for Playback {
+    fn poll(&mut self, connection: &mut Connection) -> bool {
+        let Some(result) = self.write(connection) else {
+            return true;
+        };
+
+        _ = self.stream.disconnect();
+
+        if let Some(tx) = self.result.take() {
+            _ = tx.send(result);
+        }
+
+        false
+    }
+}
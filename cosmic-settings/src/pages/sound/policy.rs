@@ -0,0 +1,212 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! When to switch the default devices as devices connect and disconnect.
+
+use cosmic::cosmic_config;
+use cosmic::widget::{self, settings};
+use cosmic::{Command, Element};
+use cosmic_settings_page::Section;
+use slab::Slab;
+
+use super::{get, server, set};
+use crate::app;
+
+/// Whether newly connected output devices become the default.
+pub const SWITCH_SINK: &str = "switch_to_new_sink";
+/// Whether newly connected input devices become the default.
+pub const SWITCH_SOURCE: &str = "switch_to_new_source";
+/// Whether the previous default device returns when the default device disconnects.
+pub const RETURN_TO_PREVIOUS: &str = "return_to_previous_device";
+
+/// How many previous default devices are remembered.
+pub(super) const HISTORY: usize = 8;
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    SwitchSink(bool),
+    SwitchSource(bool),
+    ReturnToPrevious(bool),
+}
+
+#[derive(Debug, Default)]
+pub struct Policy {
+    pub switch_sink: bool,
+    pub switch_source: bool,
+    pub return_to_previous: bool,
+}
+
+impl Policy {
+    /// Reads the switching policy.
+    pub fn load(&mut self, config: Option<&cosmic_config::Config>) {
+        self.switch_sink = config.map_or(false, |config| get(config, SWITCH_SINK, false));
+        self.switch_source = config.map_or(false, |config| get(config, SWITCH_SOURCE, false));
+        self.return_to_previous =
+            config.map_or(false, |config| get(config, RETURN_TO_PREVIOUS, false));
+    }
+}
+
+/// Adds a device which was the default to the devices to return to.
+pub fn remember(history: &mut Vec<String>, node_name: String) {
+    if node_name.is_empty() {
+        return;
+    }
+
+    history.retain(|name| *name != node_name);
+    history.push(node_name);
+
+    if history.len() > HISTORY {
+        history.remove(0);
+    }
+}
+
+/// Forgets a disconnected device. If it was the default device, returns the most recently
+/// used device which is still `available`, unless that is already the default.
+pub(super) fn previous_device(
+    history: &mut Vec<String>,
+    default: &str,
+    disconnected: &str,
+    available: impl Fn(&str) -> bool,
+) -> Option<String> {
+    // The server may have already fallen back to another device, which made the
+    // disconnected device the last one to return to.
+    let was_default =
+        default == disconnected || history.last().map(String::as_str) == Some(disconnected);
+    history.retain(|name| name != disconnected);
+
+    if !was_default {
+        return None;
+    }
+
+    history
+        .iter()
+        .rev()
+        .find(|name| available(name))
+        .filter(|name| *name != default)
+        .cloned()
+}
+
+impl super::Page {
+    pub fn update_policy(&mut self, message: Message) -> Command<app::Message> {
+        let (key, value) = match message {
+            Message::SwitchSink(enable) => {
+                self.policy.switch_sink = enable;
+                (SWITCH_SINK, enable)
+            }
+
+            Message::SwitchSource(enable) => {
+                self.policy.switch_source = enable;
+                (SWITCH_SOURCE, enable)
+            }
+
+            Message::ReturnToPrevious(enable) => {
+                self.policy.return_to_previous = enable;
+                (RETURN_TO_PREVIOUS, enable)
+            }
+        };
+
+        if let Some(config) = self.audio_config.as_ref() {
+            set(config, key, value);
+        }
+
+        Command::none()
+    }
+
+    pub(super) fn select_sink(&mut self, node_name: String) -> Command<app::Message> {
+        self.set_default_sink(node_name.clone());
+        self.server_request(server::Request::DefaultSink(node_name))
+    }
+
+    pub(super) fn select_source(&mut self, node_name: String) -> Command<app::Message> {
+        self.set_default_source(node_name.clone());
+        self.server_request(server::Request::DefaultSource(node_name))
+    }
+
+    /// Switches to a newly connected device if the policy or the device priority asks for it.
+    pub(super) fn device_connected(
+        &mut self,
+        is_sink: bool,
+        node_name: String,
+    ) -> Command<app::Message> {
+        let switch = if is_sink {
+            self.policy.switch_sink
+        } else {
+            self.policy.switch_source
+        };
+
+        match (
+            switch && !self.device_options.is_hidden(&node_name),
+            is_sink,
+        ) {
+            (true, true) => self.select_sink(node_name),
+            (true, false) => self.select_source(node_name),
+            (false, true) => self.prefer_sink(node_name),
+            (false, false) => self.prefer_source(node_name),
+        }
+    }
+
+    /// Returns to the previous default device after the default device disconnected.
+    pub(super) fn device_disconnected(
+        &mut self,
+        is_sink: bool,
+        node_name: &str,
+    ) -> Command<app::Message> {
+        let (history, default) = if is_sink {
+            (&mut self.sink_history, &self.default_sink)
+        } else {
+            (&mut self.source_history, &self.default_source)
+        };
+
+        let devices = &self.devices;
+        let previous = previous_device(history, default, node_name, |name| {
+            devices
+                .values()
+                .flat_map(|card| card.devices.values())
+                .any(|device| device.identifier == name)
+        });
+
+        if !self.policy.return_to_previous {
+            return Command::none();
+        }
+
+        match (previous, is_sink) {
+            (Some(previous), true) => self.select_sink(previous),
+            (Some(previous), false) => self.select_source(previous),
+            (None, _) => Command::none(),
+        }
+    }
+}
+
+pub fn section() -> Section<crate::pages::Message> {
+    let mut descriptions = Slab::new();
+
+    let switch_sink = descriptions.insert(fl!("sound-policy", "switch-output"));
+    let switch_source = descriptions.insert(fl!("sound-policy", "switch-input"));
+    let return_to_previous = descriptions.insert(fl!("sound-policy", "return"));
+
+    Section::default()
+        .title(fl!("sound-policy"))
+        .descriptions(descriptions)
+        .view::<super::Page>(move |_binder, page, section| {
+            let descriptions = &section.descriptions;
+            let policy = &page.policy;
+
+            let controls = settings::view_section(&section.title)
+                .add(settings::item(
+                    &*descriptions[switch_sink],
+                    widget::toggler(None, policy.switch_sink, Message::SwitchSink),
+                ))
+                .add(settings::item(
+                    &*descriptions[switch_source],
+                    widget::toggler(None, policy.switch_source, Message::SwitchSource),
+                ))
+                .add(settings::item(
+                    &*descriptions[return_to_previous],
+                    widget::toggler(None, policy.return_to_previous, Message::ReturnToPrevious),
+                ));
+
+            Element::from(controls)
+                .map(super::Message::Policy)
+                .map(crate::pages::Message::Sound)
+        })
+}
@@ -0,0 +1,754 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Changes made on the sound server through its PulseAudio interface, which PipeWire provides.
+//!
+//! The pulse thread of `cosmic-settings-subscriptions` only reports events, and its connection
+//! is private to that crate, so the page keeps a single connection of its own for everything
+//! else. Requests are run on a dedicated thread which keeps that connection open between
+//! requests, and reconnects if it was lost. The same thread and main loop drive the application
+//! streams, the input level meter, and the speaker test between requests.
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    rc::Rc,
+    sync::mpsc,
+    time::Duration,
+};
+
+use libpulse_binding::{
+    callbacks::ListResult,
+    channelmap,
+    context::{self, ext_stream_restore, Context, FlagSet},
+    def,
+    mainloop::standard::{IterateResult, Mainloop},
+    operation::{self, Operation},
+    proplist::UpdateMode,
+    time::MicroSeconds,
+    volume::{ChannelVolumes, Volume},
+};
+use tokio::sync::oneshot;
+
+use super::streams::Direction;
+
+/// The stream-restore entry which sets the volume of event sounds, such as alerts.
+const EVENT_ROLE: &str = "sink-input-by-media-role:event";
+
+/// Longest time that a request waits while the main loop is waiting for events.
+const REQUEST_LATENCY: Duration = Duration::from_millis(50);
+
+/// A change to make on the sound server. Devices are referred to by their node names.
+#[derive(Clone, Debug)]
+pub enum Request {
+    DefaultSink(String),
+    DefaultSource(String),
+    CardProfile {
+        card: String,
+        profile: String,
+    },
+    /// Volume as a percentage, keeping the balance between channels.
+    SinkVolume(String, u32),
+    SourceVolume(String, u32),
+    SinkMute(String, bool),
+    SourceMute(String, bool),
+    /// Volume of an application's stream, by its index, as a percentage.
+    StreamVolume(Direction, u32, u32),
+    StreamMute(Direction, u32, bool),
+    /// Moves an application's stream to another device.
+    StreamDevice(Direction, u32, String),
+    /// Volume of event sounds as a percentage, independent of the output volume.
+    AlertVolume(u32),
+    /// Balance of a sink, from -1.0 for left to 1.0 for right, with no channel louder than the
+    /// maximum percentage.
+    SinkBalance(String, f32, u32),
+    /// Fade of a sink, from -1.0 for rear to 1.0 for front, with no channel louder than the
+    /// maximum percentage.
+    SinkFade(String, f32, u32),
+    /// Volume of each channel of a sink as a percentage, in the order of its channel map, with
+    /// each clamped to the maximum percentage.
+    SinkChannelVolumes(String, Vec<u32>, u32),
+    /// Loads a module by its name, with its arguments.
+    LoadModule(String, String),
+    /// Unloads the module of a name which was loaded with an argument, such as
+    /// `source_name=mic`, if it is loaded.
+    UnloadModule(String, String),
+}
+
+#[derive(Clone, Debug)]
+pub enum Error {
+    /// The sound server could not be reached.
+    Connection(String),
+    /// The sound server rejected the request.
+    Failed(String),
+    /// The device no longer exists.
+    NotFound(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connection(why) => {
+                f.write_str(&fl!("sound-error", "connection", why = why.as_str()))
+            }
+            Self::Failed(why) => f.write_str(&fl!("sound-error", "failed", why = why.as_str())),
+            Self::NotFound(name) => {
+                f.write_str(&fl!("sound-error", "not-found", name = name.as_str()))
+            }
+        }
+    }
+}
+
+/// Channels of a sink, from its channel map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channels {
+    /// Positions of the channels, such as the front left speaker.
+    pub positions: Vec<channelmap::Position>,
+    /// Names of the channel positions, such as "Front Left".
+    pub labels: Vec<String>,
+    /// Volume of each channel as a percentage.
+    pub volumes: Vec<u32>,
+    /// From -1.0 for left to 1.0 for right, if the channels can be balanced.
+    pub balance: Option<f32>,
+    /// From -1.0 for rear to 1.0 for front, if the channels can be faded.
+    pub fade: Option<f32>,
+}
+
+/// What the sound server currently uses, to correct the page after a failed request.
+#[derive(Clone, Debug, Default)]
+pub struct State {
+    pub default_sink: String,
+    pub default_source: String,
+    /// Volume percentage and mute status of the default sink.
+    pub sink: Option<(u32, bool)>,
+    /// Volume percentage and mute status of the default source.
+    pub source: Option<(u32, bool)>,
+    /// Active profile of each card, by card name.
+    pub profiles: Vec<(String, Option<String>)>,
+}
+
+/// Something which runs on the connection between requests, such as the level meter.
+pub(super) trait Watch {
+    /// Handles what the main loop received. Returns `false` once the watch is finished.
+    fn poll(&mut self, connection: &mut Connection) -> bool;
+}
+
+type Start = Box<dyn FnOnce(&mut Connection) -> Result<Box<dyn Watch>, Error> + Send>;
+
+enum Job {
+    Run(Box<dyn FnOnce(&mut Option<Connection>) + Send>),
+    Watch(Start),
+}
+
+/// Sends requests to the thread connected to the sound server, which stops once every
+/// client is dropped.
+#[derive(Clone)]
+pub struct Client {
+    jobs: mpsc::Sender<Job>,
+}
+
+impl Client {
+    /// Starts the thread which connects to the sound server.
+    pub fn spawn() -> Self {
+        let (jobs, rx) = mpsc::channel::<Job>();
+
+        _ = std::thread::spawn(move || {
+            let mut connection: Option<Connection> = None;
+            let mut watches: Vec<Box<dyn Watch>> = Vec::new();
+
+            loop {
+                // Only wait for requests as long as nothing needs the main loop.
+                let job = if watches.is_empty() {
+                    match rx.recv() {
+                        Ok(job) => Some(job),
+                        Err(_) => return,
+                    }
+                } else {
+                    match rx.try_recv() {
+                        Ok(job) => Some(job),
+                        Err(mpsc::TryRecvError::Empty) => None,
+                        Err(mpsc::TryRecvError::Disconnected) => return,
+                    }
+                };
+
+                match job {
+                    Some(Job::Run(run)) => run(&mut connection),
+
+                    Some(Job::Watch(start)) => {
+                        let watch = match connection.as_mut() {
+                            Some(active) => start(active),
+                            None => Connection::new().and_then(|new| start(connection.insert(new))),
+                        };
+
+                        match watch {
+                            Ok(watch) => watches.push(watch),
+                            Err(why) => tracing::error!(?why, "failed to watch sound server"),
+                        }
+                    }
+
+                    None => (),
+                }
+
+                // Watches belong to the connection that they were started on.
+                let Some(active) = connection.as_mut() else {
+                    watches.clear();
+                    continue;
+                };
+
+                if watches.is_empty() {
+                    continue;
+                }
+
+                // Sleeps until the server sends something, rather than on a fixed interval.
+                if !active.wait(REQUEST_LATENCY) {
+                    tracing::error!("sound server main loop stopped");
+                    watches.clear();
+                    connection = None;
+                    continue;
+                }
+
+                watches.retain_mut(|watch| watch.poll(active));
+            }
+        });
+
+        Self { jobs }
+    }
+
+    /// Starts a watch on the connection, which runs until it is finished or the connection
+    /// is lost.
+    pub(super) fn watch(
+        &self,
+        start: impl FnOnce(&mut Connection) -> Result<Box<dyn Watch>, Error> + Send + 'static,
+    ) {
+        _ = self.jobs.send(Job::Watch(Box::new(start)));
+    }
+
+    /// Applies a change, returning once the server has accepted or rejected it.
+    pub async fn apply(&self, request: Request) -> Result<(), Error> {
+        self.run(move |connection| connection.apply(&request)).await
+    }
+
+    /// Fetches the volume of event sounds, if it was ever set.
+    pub async fn alert_volume(&self) -> Result<Option<u32>, Error> {
+        self.run(Connection::alert_volume).await
+    }
+
+    /// Fetches the codec which a Bluetooth sink currently uses, such as `ldac`.
+    pub async fn bluetooth_codec(&self, sink: String) -> Result<Option<String>, Error> {
+        self.run(move |connection| connection.bluetooth_codec(&sink))
+            .await
+    }
+
+    /// Fetches the channels of a sink.
+    pub async fn channels(&self, sink: String) -> Result<Channels, Error> {
+        self.run(move |connection| connection.channels(&sink)).await
+    }
+
+    /// Whether a module of a name is loaded with an argument, such as `source_name=mic`.
+    pub async fn is_module_loaded(&self, name: String, argument: String) -> Result<bool, Error> {
+        self.run(move |connection| {
+            connection
+                .module_index(&name, &argument)
+                .map(|index| index.is_some())
+        })
+        .await
+    }
+
+    /// Fetches what the server currently uses.
+    pub async fn state(&self) -> Result<State, Error> {
+        self.run(Connection::state).await
+    }
+
+    async fn run<T: Send + 'static>(
+        &self,
+        func: impl FnOnce(&mut Connection) -> Result<T, Error> + Send + 'static,
+    ) -> Result<T, Error> {
+        let (tx, rx) = oneshot::channel();
+
+        let job = Job::Run(Box::new(move |connection: &mut Option<Connection>| {
+            if connection.is_none() {
+                match Connection::new() {
+                    Ok(new) => *connection = Some(new),
+                    Err(why) => {
+                        _ = tx.send(Err(why));
+                        return;
+                    }
+                }
+            }
+
+            let Some(active) = connection.as_mut() else {
+                return;
+            };
+
+            let result = func(active);
+
+            // Reconnect on the next request if the connection was lost.
+            if let Err(Error::Connection(_)) = result {
+                *connection = None;
+            }
+
+            _ = tx.send(result);
+        }));
+
+        if self.jobs.send(job).is_err() {
+            return Err(Error::Connection(String::from(
+                "sound server thread stopped",
+            )));
+        }
+
+        rx.await
+            .unwrap_or_else(|_| Err(Error::Connection(String::from("request was dropped"))))
+    }
+}
+
+pub(super) struct Connection {
+    pub(super) mainloop: Mainloop,
+    pub(super) context: Context,
+}
+
+impl Connection {
+    /// Waits up to the timeout for events from the server, and dispatches them. Returns
+    /// `false` if the main loop stopped.
+    fn wait(&mut self, timeout: Duration) -> bool {
+        let timeout = MicroSeconds(timeout.as_micros() as u64);
+
+        self.mainloop.prepare(Some(timeout)).is_ok()
+            && self.mainloop.poll().is_ok()
+            && self.mainloop.dispatch().is_ok()
+    }
+
+    fn new() -> Result<Self, Error> {
+        let mut mainloop = Mainloop::new()
+            .ok_or_else(|| Error::Connection(String::from("failed to create main loop")))?;
+
+        let mut context = Context::new(&mainloop, "cosmic-settings")
+            .ok_or_else(|| Error::Connection(String::from("failed to create context")))?;
+
+        context
+            .connect(None, FlagSet::NOFLAGS, None)
+            .map_err(|why| Error::Connection(why.to_string().unwrap_or_default()))?;
+
+        loop {
+            if let IterateResult::Quit(_) | IterateResult::Err(_) = mainloop.iterate(true) {
+                return Err(Error::Connection(String::from("main loop stopped")));
+            }
+
+            match context.get_state() {
+                context::State::Ready => break,
+                context::State::Failed | context::State::Terminated => {
+                    return Err(Error::Connection(
+                        context.errno().to_string().unwrap_or_default(),
+                    ));
+                }
+                _ => (),
+            }
+        }
+
+        Ok(Self { mainloop, context })
+    }
+
+    fn apply(&mut self, request: &Request) -> Result<(), Error> {
+        let success = Rc::new(Cell::new(false));
+        let done = {
+            let success = success.clone();
+            move |ok: bool| success.set(ok)
+        };
+
+        let mut introspect = self.context.introspect();
+
+        let operation = match request {
+            Request::DefaultSink(name) => self.context.set_default_sink(name, done),
+
+            Request::DefaultSource(name) => self.context.set_default_source(name, done),
+
+            Request::CardProfile { card, profile } => {
+                introspect.set_card_profile_by_name(card, profile, Some(Box::new(done)))
+            }
+
+            Request::SinkVolume(name, volume) => {
+                let (mut volumes, _) = self.sink_channels(name)?;
+                volumes.scale(to_volume(*volume));
+                introspect.set_sink_volume_by_name(name, &volumes, Some(Box::new(done)))
+            }
+
+            Request::SinkBalance(name, balance, max) => {
+                let (mut volumes, map) = self.sink_channels(name)?;
+                volumes.set_balance(&map, *balance);
+                limit(&mut volumes, *max);
+                introspect.set_sink_volume_by_name(name, &volumes, Some(Box::new(done)))
+            }
+
+            Request::SinkFade(name, fade, max) => {
+                let (mut volumes, map) = self.sink_channels(name)?;
+                volumes.set_fade(&map, *fade);
+                limit(&mut volumes, *max);
+                introspect.set_sink_volume_by_name(name, &volumes, Some(Box::new(done)))
+            }
+
+            Request::SinkChannelVolumes(name, percents, max) => {
+                let (mut volumes, _) = self.sink_channels(name)?;
+
+                for (volume, &percent) in volumes.get_mut().iter_mut().zip(percents) {
+                    *volume = to_volume(percent.min(*max));
+                }
+
+                introspect.set_sink_volume_by_name(name, &volumes, Some(Box::new(done)))
+            }
+
+            Request::SourceVolume(name, volume) => {
+                let mut volumes = self.source_volumes(name)?;
+                volumes.scale(to_volume(*volume));
+                introspect.set_source_volume_by_name(name, &volumes, Some(Box::new(done)))
+            }
+
+            Request::SinkMute(name, mute) => {
+                introspect.set_sink_mute_by_name(name, *mute, Some(Box::new(done)))
+            }
+
+            Request::SourceMute(name, mute) => {
+                introspect.set_source_mute_by_name(name, *mute, Some(Box::new(done)))
+            }
+
+            Request::StreamVolume(direction, index, volume) => {
+                let mut volumes = self.stream_volumes(*direction, *index)?;
+                volumes.scale(to_volume(*volume));
+
+                match direction {
+                    Direction::Playback => {
+                        introspect.set_sink_input_volume(*index, &volumes, Some(Box::new(done)))
+                    }
+                    Direction::Recording => {
+                        introspect.set_source_output_volume(*index, &volumes, Some(Box::new(done)))
+                    }
+                }
+            }
+
+            Request::StreamMute(Direction::Playback, index, mute) => {
+                introspect.set_sink_input_mute(*index, *mute, Some(Box::new(done)))
+            }
+
+            Request::StreamMute(Direction::Recording, index, mute) => {
+                introspect.set_source_output_mute(*index, *mute, Some(Box::new(done)))
+            }
+
+            Request::StreamDevice(Direction::Playback, index, device) => {
+                introspect.move_sink_input_by_name(*index, device, Some(Box::new(done)))
+            }
+
+            Request::StreamDevice(Direction::Recording, index, device) => {
+                introspect.move_source_output_by_name(*index, device, Some(Box::new(done)))
+            }
+
+            Request::AlertVolume(volume) => {
+                let mut channel_map = channelmap::Map::default();
+                channel_map.init_mono();
+
+                let mut volumes = ChannelVolumes::default();
+                volumes.set(1, to_volume(*volume));
+
+                let info = ext_stream_restore::Info {
+                    name: Some(EVENT_ROLE.into()),
+                    channel_map,
+                    volume: volumes,
+                    device: None,
+                    mute: false,
+                };
+
+                self.context
+                    .stream_restore()
+                    .write(UpdateMode::Replace, &[&info], true, done)
+            }
+
+            Request::LoadModule(name, arguments) => {
+                let operation = introspect.load_module(name, arguments, move |index| {
+                    done(index != def::INVALID_INDEX);
+                });
+
+                self.wait(&operation)?;
+                return self.result(success.get());
+            }
+
+            Request::UnloadModule(name, argument) => {
+                let Some(index) = self.module_index(name, argument)? else {
+                    return Ok(());
+                };
+
+                introspect.unload_module(index, done)
+            }
+        };
+
+        self.wait(&operation)?;
+        self.result(success.get())
+    }
+
+    fn result(&self, success: bool) -> Result<(), Error> {
+        if success {
+            Ok(())
+        } else {
+            Err(Error::Failed(
+                self.context.errno().to_string().unwrap_or_default(),
+            ))
+        }
+    }
+
+    /// The index of the loaded module of a name which was loaded with an argument.
+    fn module_index(&mut self, name: &str, argument: &str) -> Result<Option<u32>, Error> {
+        let index = Rc::new(Cell::new(None));
+
+        let operation = self.context.introspect().get_module_info_list({
+            let index = index.clone();
+            let name = name.to_owned();
+            let argument = argument.to_owned();
+
+            move |result| {
+                if let ListResult::Item(info) = result {
+                    let loaded_with = info.argument.as_deref().map_or(false, |arguments| {
+                        arguments.split_whitespace().any(|a| a == argument)
+                    });
+
+                    if info.name.as_deref() == Some(name.as_str()) && loaded_with {
+                        index.set(Some(info.index));
+                    }
+                }
+            }
+        });
+
+        self.wait(&operation)?;
+        Ok(index.get())
+    }
+
+    fn state(&mut self) -> Result<State, Error> {
+        let state = Rc::new(RefCell::new(State::default()));
+        let introspect = self.context.introspect();
+
+        let operation = introspect.get_server_info({
+            let state = state.clone();
+            move |info| {
+                let mut state = state.borrow_mut();
+                state.default_sink = info.default_sink_name.as_deref().unwrap_or("").to_owned();
+                state.default_source = info.default_source_name.as_deref().unwrap_or("").to_owned();
+            }
+        });
+
+        self.wait(&operation)?;
+
+        let (default_sink, default_source) = {
+            let state = state.borrow();
+            (state.default_sink.clone(), state.default_source.clone())
+        };
+
+        let operation = introspect.get_sink_info_by_name(&default_sink, {
+            let state = state.clone();
+            move |result| {
+                if let ListResult::Item(info) = result {
+                    state.borrow_mut().sink = Some((to_percent(info.volume.avg()), info.mute));
+                }
+            }
+        });
+
+        self.wait(&operation)?;
+
+        let operation = introspect.get_source_info_by_name(&default_source, {
+            let state = state.clone();
+            move |result| {
+                if let ListResult::Item(info) = result {
+                    state.borrow_mut().source = Some((to_percent(info.volume.avg()), info.mute));
+                }
+            }
+        });
+
+        self.wait(&operation)?;
+
+        let operation = introspect.get_card_info_list({
+            let state = state.clone();
+            move |result| {
+                if let ListResult::Item(card) = result {
+                    let name = card.name.as_deref().unwrap_or("").to_owned();
+                    let profile = card
+                        .active_profile
+                        .as_ref()
+                        .and_then(|profile| profile.name.as_deref())
+                        .map(str::to_owned);
+
+                    state.borrow_mut().profiles.push((name, profile));
+                }
+            }
+        });
+
+        self.wait(&operation)?;
+
+        Ok(state.take())
+    }
+
+    fn alert_volume(&mut self) -> Result<Option<u32>, Error> {
+        let volume = Rc::new(Cell::new(None));
+
+        let operation = self.context.stream_restore().read({
+            let volume = volume.clone();
+            move |result| {
+                if let ListResult::Item(info) = result {
+                    if info.name.as_deref() == Some(EVENT_ROLE) {
+                        volume.set(Some(to_percent(info.volume.max())));
+                    }
+                }
+            }
+        });
+
+        self.wait(&operation)?;
+        Ok(volume.get())
+    }
+
+    fn bluetooth_codec(&mut self, name: &str) -> Result<Option<String>, Error> {
+        let codec = Rc::new(RefCell::new(None));
+
+        let operation = self.context.introspect().get_sink_info_by_name(name, {
+            let codec = codec.clone();
+            move |result| {
+                if let ListResult::Item(info) = result {
+                    *codec.borrow_mut() = info.proplist.get_str("api.bluez5.codec");
+                }
+            }
+        });
+
+        self.wait(&operation)?;
+        Ok(codec.take())
+    }
+
+    fn channels(&mut self, name: &str) -> Result<Channels, Error> {
+        let (volumes, map) = self.sink_channels(name)?;
+
+        Ok(Channels {
+            positions: map.get().to_vec(),
+            labels: map
+                .get()
+                .iter()
+                .map(|&position| {
+                    channelmap::Position::to_pretty_string(position)
+                        .unwrap_or_else(|| format!("{position:?}"))
+                })
+                .collect(),
+            volumes: volumes
+                .get()
+                .iter()
+                .map(|&volume| to_percent(volume))
+                .collect(),
+            balance: map.can_balance().then(|| volumes.get_balance(&map)),
+            fade: map.can_fade().then(|| volumes.get_fade(&map)),
+        })
+    }
+
+    /// The volume of each channel of a sink, and its channel map.
+    fn sink_channels(&mut self, name: &str) -> Result<(ChannelVolumes, channelmap::Map), Error> {
+        let channels = Rc::new(Cell::new(None));
+
+        let operation = self.context.introspect().get_sink_info_by_name(name, {
+            let channels = channels.clone();
+            move |result| {
+                if let ListResult::Item(info) = result {
+                    channels.set(Some((info.volume, info.channel_map)));
+                }
+            }
+        });
+
+        self.wait(&operation)?;
+        channels
+            .get()
+            .ok_or_else(|| Error::NotFound(name.to_owned()))
+    }
+
+    fn source_volumes(&mut self, name: &str) -> Result<ChannelVolumes, Error> {
+        let volumes = Rc::new(Cell::new(None));
+
+        let operation = self.context.introspect().get_source_info_by_name(name, {
+            let volumes = volumes.clone();
+            move |result| {
+                if let ListResult::Item(info) = result {
+                    volumes.set(Some(info.volume));
+                }
+            }
+        });
+
+        self.wait(&operation)?;
+        volumes
+            .get()
+            .ok_or_else(|| Error::NotFound(name.to_owned()))
+    }
+
+    fn stream_volumes(
+        &mut self,
+        direction: Direction,
+        index: u32,
+    ) -> Result<ChannelVolumes, Error> {
+        let volumes = Rc::new(Cell::new(None));
+        let introspect = self.context.introspect();
+
+        // The operations differ in their callback types, so each is waited on separately.
+        match direction {
+            Direction::Playback => {
+                let operation = introspect.get_sink_input_info(index, {
+                    let volumes = volumes.clone();
+                    move |result| {
+                        if let ListResult::Item(info) = result {
+                            volumes.set(Some(info.volume));
+                        }
+                    }
+                });
+
+                self.wait(&operation)?;
+            }
+
+            Direction::Recording => {
+                let operation = introspect.get_source_output_info(index, {
+                    let volumes = volumes.clone();
+                    move |result| {
+                        if let ListResult::Item(info) = result {
+                            volumes.set(Some(info.volume));
+                        }
+                    }
+                });
+
+                self.wait(&operation)?;
+            }
+        }
+
+        volumes
+            .get()
+            .ok_or_else(|| Error::NotFound(index.to_string()))
+    }
+
+    /// Runs the main loop until the operation is finished.
+    pub(super) fn wait<C: ?Sized>(&mut self, operation: &Operation<C>) -> Result<(), Error> {
+        loop {
+            match operation.get_state() {
+                operation::State::Done => return Ok(()),
+                operation::State::Cancelled => {
+                    return Err(Error::Connection(
+                        self.context.errno().to_string().unwrap_or_default(),
+                    ));
+                }
+                operation::State::Running => (),
+            }
+
+            if let IterateResult::Quit(_) | IterateResult::Err(_) = self.mainloop.iterate(true) {
+                return Err(Error::Connection(String::from("main loop stopped")));
+            }
+        }
+    }
+}
+
+pub(super) fn to_volume(percent: u32) -> Volume {
+    Volume((f64::from(Volume::NORMAL.0) * f64::from(percent) / 100.0).round() as u32)
+}
+
+/// Scales every channel down, keeping their balance, so that none is louder than the percentage.
+fn limit(volumes: &mut ChannelVolumes, max: u32) {
+    let max = to_volume(max);
+
+    if volumes.max().0 > max.0 {
+        volumes.scale(max);
+    }
+}
+
+pub(super) fn to_percent(volume: Volume) -> u32 {
+    (f64::from(volume.0) * 100.0 / f64::from(Volume::NORMAL.0)).round() as u32
+}
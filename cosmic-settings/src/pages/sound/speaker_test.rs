@@ -0,0 +1,65 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Test tones played on one speaker of an output at a time.
+
+use std::f32::consts::TAU;
+
+use libpulse_binding::{
+    channelmap::{self, Position},
+    sample::{Format, Spec},
+    stream::{self, Stream},
+};
+
+use super::playback::{self, failed};
+use super::server::{Client, Connection, Error};
+
+const RATE: u32 = 48_000;
+const FREQUENCY: f32 = 440.0;
+const VOLUME: f32 = 0.4;
+/// Length of the tone, in samples.
+const LENGTH: usize = RATE as usize;
+/// Length of the fade in and out which keeps the tone from clicking, in samples.
+const FADE: usize = RATE as usize / 50;
+
+/// Plays a tone on the speaker at `position` of a sink, returning once it finished playing.
+///
+/// The tone is a mono stream mapped to the single position, so the server sends it to that
+/// speaker alone.
+pub async fn play(client: &Client, sink: String, position: Position) -> Result<(), Error> {
+    let samples = tone().flat_map(f32::to_ne_bytes).collect();
+
+    playback::play(client, samples, move |connection| {
+        connect(connection, &sink, position)
+    })
+    .await
+}
+
+fn connect(connection: &mut Connection, sink: &str, position: Position) -> Result<Stream, Error> {
+    let spec = Spec {
+        format: Format::FLOAT32NE,
+        channels: 1,
+        rate: RATE,
+    };
+
+    let mut map = channelmap::Map::default();
+    map.set_len(1);
+    map.get_mut()[0] = position;
+
+    let mut stream = Stream::new(&mut connection.context, "Speaker test", &spec, Some(&map))
+        .ok_or_else(|| Error::Failed(String::from("failed to create stream")))?;
+
+    stream
+        .connect_playback(Some(sink), None, stream::FlagSet::NOFLAGS, None, None)
+        .map_err(failed)?;
+
+    Ok(stream)
+}
+
+/// A sine wave which fades in and out.
+fn tone() -> impl Iterator<Item = f32> {
+    (0..LENGTH).map(|n| {
+        let fade = n.min(LENGTH - 1 - n).min(FADE) as f32 / FADE as f32;
+        (TAU * FREQUENCY * n as f32 / RATE as f32).sin() * VOLUME * fade
+    })
+}
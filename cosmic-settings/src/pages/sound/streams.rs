@@ -0,0 +1,325 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Audio streams of applications, for the per-application mixer.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    path::Path,
+    rc::Rc,
+};
+
+use freedesktop_desktop_entry::DesktopEntry;
+use libpulse_binding::{
+    callbacks::ListResult, context::subscribe::InterestMaskSet, proplist::Proplist,
+};
+
+use super::server::{self, Client, Connection, Error, Watch};
+
+const DEFAULT_ICON: &str = "application-x-executable";
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    /// Sink inputs, which play to an output device.
+    Playback,
+    /// Source outputs, which record from an input device.
+    Recording,
+}
+
+/// A stream of an application, such as the audio of a video call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stream {
+    pub direction: Direction,
+    pub index: u32,
+    pub name: String,
+    pub icon: String,
+    /// Volume as a percentage.
+    pub volume: u32,
+    pub mute: bool,
+    /// Node name of the device that the stream plays to or records from.
+    pub device: String,
+}
+
+/// Properties of a stream which identify its application.
+struct Properties {
+    name: Option<String>,
+    icon: Option<String>,
+    app_id: Option<String>,
+    binary: Option<String>,
+    pid: Option<u32>,
+}
+
+impl Properties {
+    fn new(proplist: &Proplist) -> Self {
+        Self {
+            name: proplist.get_str("application.name"),
+            icon: proplist.get_str("application.icon_name"),
+            app_id: proplist
+                .get_str("pipewire.access.portal.app_id")
+                .or_else(|| proplist.get_str("application.id")),
+            binary: proplist.get_str("application.process.binary"),
+            pid: proplist
+                .get_str("application.process.id")
+                .and_then(|pid| pid.parse().ok()),
+        }
+    }
+}
+
+struct RawStream {
+    direction: Direction,
+    index: u32,
+    device: u32,
+    volume: u32,
+    mute: bool,
+    properties: Properties,
+}
+
+/// Name and icon of an application, from its desktop entry.
+#[derive(Clone)]
+struct App {
+    name: String,
+    icon: String,
+}
+
+struct Streams {
+    apps: HashMap<String, App>,
+    changed: Rc<Cell<bool>>,
+    previous: Option<Vec<Stream>>,
+    /// Streams which the page hasn't taken yet.
+    pending: Option<Vec<Stream>>,
+    tx: futures::channel::mpsc::Sender<Vec<Stream>>,
+}
+
+/// Sends the streams of applications, and again whenever they appear, disappear or change,
+/// until the receiver is dropped.
+pub fn watch(client: &Client, tx: futures::channel::mpsc::Sender<Vec<Stream>>) {
+    client.watch(move |connection| {
+        let changed = Rc::new(Cell::new(true));
+
+        connection.context.set_subscribe_callback(Some(Box::new({
+            let changed = changed.clone();
+            move |_facility, _operation, _index| changed.set(true)
+        })));
+
+        let operation = connection.context.subscribe(
+            InterestMaskSet::SINK_INPUT
+                | InterestMaskSet::SOURCE_OUTPUT
+                | InterestMaskSet::SINK
+                | InterestMaskSet::SOURCE,
+            |_| (),
+        );
+
+        connection.wait(&operation)?;
+
+        Ok(Box::new(Streams {
+            apps: desktop_apps(),
+            changed,
+            previous: None,
+            pending: None,
+            tx,
+        }))
+    });
+}
+
+impl Watch for Streams {
+    fn poll(&mut self, connection: &mut Connection) -> bool {
+        if self.tx.is_closed() {
+            connection.context.set_subscribe_callback(None);
+            return false;
+        }
+
+        if self.changed.replace(false) {
+            let streams = match streams(connection, &self.apps) {
+                Ok(streams) => streams,
+                Err(why) => {
+                    tracing::error!(?why, "failed to watch application audio streams");
+                    return false;
+                }
+            };
+
+            if self.previous.as_ref() != Some(&streams) {
+                self.previous = Some(streams.clone());
+                self.pending = Some(streams);
+            }
+        }
+
+        if let Some(streams) = self.pending.take() {
+            match self.tx.try_send(streams) {
+                Ok(()) => (),
+                // Sent again once the page has taken the previous streams.
+                Err(why) if why.is_full() => self.pending = Some(why.into_inner()),
+                Err(_) => return false,
+            }
+        }
+
+        true
+    }
+}
+
+fn streams(connection: &mut Connection, apps: &HashMap<String, App>) -> Result<Vec<Stream>, Error> {
+    let introspect = connection.context.introspect();
+
+    let sinks = Rc::new(RefCell::new(HashMap::new()));
+    let operation = introspect.get_sink_info_list({
+        let sinks = sinks.clone();
+        move |result| {
+            if let ListResult::Item(info) = result {
+                let name = info.name.as_deref().unwrap_or("").to_owned();
+                sinks.borrow_mut().insert(info.index, name);
+            }
+        }
+    });
+
+    connection.wait(&operation)?;
+
+    let sources = Rc::new(RefCell::new(HashMap::new()));
+    let operation = introspect.get_source_info_list({
+        let sources = sources.clone();
+        move |result| {
+            if let ListResult::Item(info) = result {
+                let name = info.name.as_deref().unwrap_or("").to_owned();
+                sources.borrow_mut().insert(info.index, name);
+            }
+        }
+    });
+
+    connection.wait(&operation)?;
+
+    let raw = Rc::new(RefCell::new(Vec::new()));
+    let operation = introspect.get_sink_input_info_list({
+        let raw = raw.clone();
+        move |result| {
+            if let ListResult::Item(info) = result {
+                raw.borrow_mut().push(RawStream {
+                    direction: Direction::Playback,
+                    index: info.index,
+                    device: info.sink,
+                    volume: server::to_percent(info.volume.avg()),
+                    mute: info.mute,
+                    properties: Properties::new(&info.proplist),
+                });
+            }
+        }
+    });
+
+    connection.wait(&operation)?;
+
+    let operation = introspect.get_source_output_info_list({
+        let raw = raw.clone();
+        move |result| {
+            if let ListResult::Item(info) = result {
+                raw.borrow_mut().push(RawStream {
+                    direction: Direction::Recording,
+                    index: info.index,
+                    device: info.source,
+                    volume: server::to_percent(info.volume.avg()),
+                    mute: info.mute,
+                    properties: Properties::new(&info.proplist),
+                });
+            }
+        }
+    });
+
+    connection.wait(&operation)?;
+
+    let own_pid = std::process::id();
+    let sinks = sinks.take();
+    let sources = sources.take();
+
+    let mut streams = raw
+        .take()
+        .into_iter()
+        // Streams of this application, such as its level meter, are not shown.
+        .filter(|stream| stream.properties.pid != Some(own_pid))
+        .map(|stream| {
+            let devices = match stream.direction {
+                Direction::Playback => &sinks,
+                Direction::Recording => &sources,
+            };
+
+            let app = app(apps, &stream.properties);
+
+            Stream {
+                direction: stream.direction,
+                index: stream.index,
+                name: app
+                    .as_ref()
+                    .map(|app| app.name.clone())
+                    .or(stream.properties.name)
+                    .unwrap_or_else(|| fl!("sound-applications", "unknown")),
+                icon: stream
+                    .properties
+                    .icon
+                    .or(app.map(|app| app.icon))
+                    .unwrap_or_else(|| DEFAULT_ICON.to_owned()),
+                volume: stream.volume,
+                mute: stream.mute,
+                device: devices.get(&stream.device).cloned().unwrap_or_default(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    streams.sort_by(|a, b| {
+        (a.direction == Direction::Recording, &a.name, a.index).cmp(&(
+            b.direction == Direction::Recording,
+            &b.name,
+            b.index,
+        ))
+    });
+
+    Ok(streams)
+}
+
+/// Finds the desktop entry of a stream's application, by its app ID or else its binary.
+fn app(apps: &HashMap<String, App>, properties: &Properties) -> Option<App> {
+    properties
+        .app_id
+        .as_deref()
+        .map(|id| id.trim_end_matches(".desktop").to_lowercase())
+        .and_then(|id| apps.get(&id))
+        .or_else(|| {
+            properties
+                .binary
+                .as_deref()
+                .and_then(|binary| apps.get(&binary.to_lowercase()))
+        })
+        .cloned()
+}
+
+/// Applications with desktop entries, by their lowercase desktop IDs and executables.
+fn desktop_apps() -> HashMap<String, App> {
+    let mut apps = HashMap::new();
+
+    for path in freedesktop_desktop_entry::Iter::new(freedesktop_desktop_entry::default_paths()) {
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let Ok(entry) = DesktopEntry::from_str(&path, &content, None::<&[&str]>) else {
+            continue;
+        };
+
+        let app = App {
+            name: entry
+                .name::<&str>(&[])
+                .map_or_else(|| entry.id().to_owned(), |name| name.to_string()),
+            icon: entry.icon().unwrap_or(DEFAULT_ICON).to_owned(),
+        };
+
+        let binary = entry
+            .exec()
+            .and_then(|exec| exec.split_whitespace().next())
+            .and_then(|exec| Path::new(exec).file_name())
+            .and_then(|name| name.to_str())
+            .map(str::to_lowercase);
+
+        if let Some(binary) = binary {
+            apps.entry(binary).or_insert_with(|| app.clone());
+        }
+
+        apps.insert(entry.id().to_lowercase(), app);
+    }
+
+    apps
+}
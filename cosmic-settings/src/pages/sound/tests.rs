@@ -0,0 +1,246 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::path::{Path, PathBuf};
+
+use libpulse_binding::sample::Format;
+
+use super::bluetooth::{self, Mode};
+use super::{alerts, policy};
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|&name| name.to_owned()).collect()
+}
+
+#[test]
+fn remember_moves_repeated_devices_to_the_end() {
+    let mut history = names(&["speakers", "headphones"]);
+
+    policy::remember(&mut history, String::from("speakers"));
+    assert_eq!(history, names(&["headphones", "speakers"]));
+
+    // Devices without a node name are never returned to.
+    policy::remember(&mut history, String::new());
+    assert_eq!(history, names(&["headphones", "speakers"]));
+}
+
+#[test]
+fn remember_forgets_the_oldest_devices() {
+    let mut history = Vec::new();
+
+    for device in 0..=policy::HISTORY {
+        policy::remember(&mut history, device.to_string());
+    }
+
+    assert_eq!(history.len(), policy::HISTORY);
+    assert_eq!(history.first().map(String::as_str), Some("1"));
+    assert_eq!(
+        history.last().map(String::as_str),
+        Some(policy::HISTORY.to_string().as_str())
+    );
+}
+
+#[test]
+fn return_to_the_previous_device() {
+    let connected = |name: &str| name != "unplugged";
+
+    // The default device disconnected, so the most recent available device returns.
+    let mut history = names(&["speakers", "unplugged", "hdmi", "headphones"]);
+    assert_eq!(
+        policy::previous_device(&mut history, "headphones", "headphones", connected),
+        Some(String::from("hdmi"))
+    );
+    assert_eq!(history, names(&["speakers", "unplugged", "hdmi"]));
+
+    // The server already fell back to another device before the disconnect was seen.
+    let mut history = names(&["speakers", "headphones"]);
+    assert_eq!(
+        policy::previous_device(&mut history, "hdmi", "headphones", connected),
+        Some(String::from("speakers"))
+    );
+
+    // Nothing changes when a device other than the default disconnects.
+    let mut history = names(&["speakers", "headphones", "hdmi"]);
+    assert_eq!(
+        policy::previous_device(&mut history, "hdmi", "speakers", connected),
+        None
+    );
+    assert_eq!(history, names(&["headphones", "hdmi"]));
+
+    // The server already fell back to the previous device.
+    let mut history = names(&["speakers", "headphones"]);
+    assert_eq!(
+        policy::previous_device(&mut history, "speakers", "headphones", connected),
+        None
+    );
+}
+
+fn theme(id: &str, index: &str) -> alerts::Theme {
+    alerts::parse_index(id.to_owned(), index).unwrap()
+}
+
+#[test]
+fn parse_sound_theme_index() {
+    let index = "[Icon Theme]\nName=Icons\n\n[Sound Theme]\nName=Ocean\nInherits=freedesktop\n";
+    assert_eq!(theme("ocean", index).name, "Ocean");
+
+    // The ID names a theme without a name.
+    assert_eq!(theme("unnamed", "[Sound Theme]\n").name, "unnamed");
+
+    assert!(alerts::parse_index(
+        String::from("hidden"),
+        "[Sound Theme]\nName=Hidden\nHidden=true\n"
+    )
+    .is_none());
+}
+
+/// Sound theme directories, which are removed once dropped.
+struct SoundDirs(PathBuf);
+
+impl SoundDirs {
+    fn new(files: &[&str]) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("cosmic-settings-sounds-{}", std::process::id()));
+
+        for file in files {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, []).unwrap();
+        }
+
+        Self(dir)
+    }
+
+    fn event_file(&self, themes: &[alerts::Theme], event: &str) -> Option<PathBuf> {
+        alerts::find_event_file(std::slice::from_ref(&self.0), themes, "child", event)
+            .map(|path| path.strip_prefix(&self.0).unwrap().to_owned())
+    }
+}
+
+impl Drop for SoundDirs {
+    fn drop(&mut self) {
+        _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn event_files_follow_inherited_themes() {
+    let dirs = SoundDirs::new(&[
+        "child/stereo/message-new-instant.oga",
+        "child/message-new-instant.wav",
+        "parent-a/stereo/dialog-warning.wav",
+        "parent-b/stereo/bell.oga",
+        "parent-b/stereo/dialog-warning.oga",
+        "freedesktop/stereo/bell.oga",
+        "freedesktop/stereo/complete.oga",
+    ]);
+
+    let themes = [
+        theme("child", "[Sound Theme]\nInherits=parent-a, parent-b\n"),
+        // Inheriting back from the child must not loop forever.
+        theme("parent-a", "[Sound Theme]\nInherits=child\n"),
+        theme("parent-b", "[Sound Theme]\n"),
+    ];
+
+    // Stereo sounds come first.
+    assert_eq!(
+        dirs.event_file(&themes, "message-new-instant"),
+        Some(Path::new("child/stereo/message-new-instant.oga").to_owned())
+    );
+
+    // Inherited themes are searched in order.
+    assert_eq!(
+        dirs.event_file(&themes, "dialog-warning"),
+        Some(Path::new("parent-a/stereo/dialog-warning.wav").to_owned())
+    );
+    assert_eq!(
+        dirs.event_file(&themes, "bell"),
+        Some(Path::new("parent-b/stereo/bell.oga").to_owned())
+    );
+
+    // Every theme falls back to the freedesktop theme.
+    assert_eq!(
+        dirs.event_file(&themes, "complete"),
+        Some(Path::new("freedesktop/stereo/complete.oga").to_owned())
+    );
+
+    assert_eq!(dirs.event_file(&themes, "trash-empty"), None);
+}
+
+/// A WAV file with a `fmt ` chunk and a `data` chunk, and an odd-sized chunk between them.
+fn wav(format_tag: u16, channels: u16, rate: u32, bits: u16, data: &[u8]) -> Vec<u8> {
+    let block_align = channels * bits / 8;
+
+    let mut fmt = Vec::new();
+    fmt.extend_from_slice(&format_tag.to_le_bytes());
+    fmt.extend_from_slice(&channels.to_le_bytes());
+    fmt.extend_from_slice(&rate.to_le_bytes());
+    fmt.extend_from_slice(&(rate * u32::from(block_align)).to_le_bytes());
+    fmt.extend_from_slice(&block_align.to_le_bytes());
+    fmt.extend_from_slice(&bits.to_le_bytes());
+
+    let mut chunks = Vec::new();
+
+    for (id, body) in [
+        (b"fmt ", &fmt[..]),
+        (b"LIST", &[1, 2, 3][..]),
+        (b"data", data),
+    ] {
+        chunks.extend_from_slice(id);
+        chunks.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        chunks.extend_from_slice(body);
+
+        if body.len() % 2 == 1 {
+            chunks.push(0);
+        }
+    }
+
+    let mut file = Vec::new();
+    file.extend_from_slice(b"RIFF");
+    file.extend_from_slice(&(chunks.len() as u32 + 4).to_le_bytes());
+    file.extend_from_slice(b"WAVE");
+    file.extend_from_slice(&chunks);
+    file
+}
+
+#[test]
+fn decode_wav_files() {
+    // The incomplete frame at the end is left out.
+    let file = wav(1, 2, 44_100, 16, &[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    let (spec, samples) = alerts::decode_wav(&file).unwrap();
+
+    assert_eq!(spec.format, Format::S16le);
+    assert_eq!(spec.channels, 2);
+    assert_eq!(spec.rate, 44_100);
+    assert_eq!(samples, &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+    let file = wav(3, 1, 48_000, 32, &[0; 8]);
+    assert_eq!(alerts::decode_wav(&file).unwrap().0.format, Format::F32le);
+
+    // Compressed formats, such as ADPCM, can't be played without a decoder.
+    assert!(alerts::decode_wav(&wav(2, 1, 8_000, 4, &[0; 8])).is_none());
+    assert!(alerts::decode_wav(b"OggS").is_none());
+}
+
+#[test]
+fn parse_bluetooth_profiles() {
+    assert_eq!(
+        bluetooth::parse_profile("a2dp-sink"),
+        Some((Mode::HighFidelity, None))
+    );
+    assert_eq!(
+        bluetooth::parse_profile("a2dp-sink-aptx_hd"),
+        Some((Mode::HighFidelity, Some("aptx_hd")))
+    );
+    assert_eq!(
+        bluetooth::parse_profile("headset-head-unit-msbc"),
+        Some((Mode::Headset, Some("msbc")))
+    );
+
+    // Profiles which are neither mode, or which only start with the name of one.
+    assert_eq!(bluetooth::parse_profile("off"), None);
+    assert_eq!(bluetooth::parse_profile("a2dp-sinkx"), None);
+
+    assert_eq!(bluetooth::codec_label("aptx_hd"), "aptX HD");
+    assert_eq!(bluetooth::codec_label("future_codec"), "FUTURE_CODEC");
+}
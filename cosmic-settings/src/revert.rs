@@ -0,0 +1,190 @@
+// Copyright 2024 System76 <info@system76.com>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Confirm or revert risky changes.
+//!
+//! A page captures what it is about to change in a [`Revert`], makes the change, and asks
+//! for it to be confirmed. The app then shows a dialog which undoes the change unless it
+//! is kept before the countdown ends.
+
+use std::{fmt, io, sync::Arc};
+
+use cosmic::cosmic_config::{self, ConfigGet, ConfigSet};
+use cosmic::iced::time;
+use cosmic::{command, Command, Element};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Seconds before an unconfirmed change is reverted.
+pub const TIMEOUT: usize = 10;
+
+type Restore = Arc<dyn Fn() -> Command<crate::Message> + Send + Sync>;
+
+/// Undoes a change if it is not confirmed.
+#[derive(Clone)]
+pub struct Revert {
+    title: String,
+    restore: Vec<Restore>,
+}
+
+impl fmt::Debug for Revert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Revert")
+            .field("title", &self.title)
+            .field("restore", &self.restore.len())
+            .finish()
+    }
+}
+
+impl Revert {
+    /// A revert for a change described by the dialog's `title`, such as "Keep these settings?".
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            restore: Vec::new(),
+        }
+    }
+
+    /// Writes back a previous value of a config key.
+    #[must_use]
+    pub fn config<T: Serialize + Send + Sync + 'static>(
+        self,
+        config: &cosmic_config::Config,
+        key: &str,
+        previous: T,
+    ) -> Self {
+        let config = config.clone();
+        let key = key.to_owned();
+
+        self.command(move || {
+            if let Err(why) = config.set(&key, &previous) {
+                tracing::error!(?why, "failed to revert config '{key}'");
+            }
+
+            Command::none()
+        })
+    }
+
+    /// Captures the current value of a config key before it is changed, to write it back
+    /// if the change is reverted. A key which is not set yet is reverted to its default.
+    #[must_use]
+    pub fn capture<T: Default + DeserializeOwned + Serialize + Send + Sync + 'static>(
+        self,
+        config: &cosmic_config::Config,
+        key: &str,
+    ) -> Self {
+        match config.get::<T>(key) {
+            Ok(previous) => self.config(config, key, previous),
+            Err(cosmic_config::Error::GetKey(_, why)) if why.kind() == io::ErrorKind::NotFound => {
+                self.config(config, key, T::default())
+            }
+            Err(why) => {
+                tracing::error!(?why, "failed to capture config '{key}' for reverting");
+                self
+            }
+        }
+    }
+
+    /// Runs a command when the change is reverted, such as one which reloads a page.
+    #[must_use]
+    pub fn command(
+        mut self,
+        restore: impl Fn() -> Command<crate::Message> + Send + Sync + 'static,
+    ) -> Self {
+        self.restore.push(Arc::new(restore));
+        self
+    }
+
+    /// Asks for the change to be confirmed.
+    pub fn confirm(self) -> Command<crate::Message> {
+        command::message(crate::Message::Revert(Message::Confirm(self)))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    /// Asks for a change to be kept before the countdown ends.
+    Confirm(Revert),
+    /// One second of the countdown passed.
+    Countdown,
+    /// Keeps the change.
+    Keep,
+    /// Undoes the change.
+    Revert,
+}
+
+/// A change which is waiting to be confirmed.
+#[derive(Debug, Default)]
+pub struct Pending {
+    revert: Option<Revert>,
+    countdown: usize,
+    /// Whether a countdown is running, so that only one is ever started.
+    counting: bool,
+}
+
+impl Pending {
+    pub fn update(&mut self, message: Message) -> Command<crate::Message> {
+        match message {
+            Message::Confirm(mut revert) => {
+                // A change made while another awaits confirmation is reverted along with it,
+                // newest first, so that everything returns to how it was before either.
+                if let Some(pending) = self.revert.take() {
+                    revert.restore.extend(pending.restore);
+                }
+
+                self.revert = Some(revert);
+                self.countdown = TIMEOUT;
+
+                if !self.counting {
+                    self.counting = true;
+                    return countdown();
+                }
+            }
+
+            Message::Countdown => {
+                if self.revert.is_none() {
+                    self.counting = false;
+                } else if self.countdown == 0 {
+                    self.counting = false;
+                    return self.update(Message::Revert);
+                } else {
+                    self.countdown -= 1;
+                    return countdown();
+                }
+            }
+
+            Message::Keep => self.revert = None,
+
+            Message::Revert => {
+                if let Some(revert) = self.revert.take() {
+                    return Command::batch(revert.restore.iter().map(|restore| restore()));
+                }
+            }
+        }
+
+        Command::none()
+    }
+
+    pub fn dialog(&self) -> Option<Element<crate::Message>> {
+        let revert = self.revert.as_ref()?;
+
+        let dialog = cosmic::widget::dialog(&revert.title)
+            .body(fl!("dialog", "change-prompt", time = self.countdown))
+            .primary_action(
+                cosmic::widget::button::suggested(fl!("dialog", "keep-changes"))
+                    .on_press(crate::Message::Revert(Message::Keep)),
+            )
+            .secondary_action(
+                cosmic::widget::button::standard(fl!("dialog", "revert-settings"))
+                    .on_press(crate::Message::Revert(Message::Revert)),
+            );
+
+        Some(dialog.into())
+    }
+}
+
+fn countdown() -> Command<crate::Message> {
+    command::future(async {
+        tokio::time::sleep(time::Duration::from_secs(1)).await;
+        crate::Message::Revert(Message::Countdown)
+    })
+}
@@ -38,3 +38,21 @@ pub fn display_container_screen() -> cosmic::theme::Container {
         }
     })
 }
+
+#[must_use]
+pub fn display_identify() -> cosmic::theme::Container {
+    theme::Container::custom(|theme| {
+        let cosmic = theme.cosmic();
+        cosmic::widget::container::Appearance {
+            icon_color: Some(cosmic.on_bg_color().into()),
+            text_color: Some(cosmic.on_bg_color().into()),
+            background: Some(cosmic::iced::Background::Color(cosmic.bg_color().into())),
+            border: Border {
+                color: cosmic.accent_color().into(),
+                radius: cosmic.corner_radii.radius_m.into(),
+                width: 4.0,
+            },
+            shadow: Default::default(),
+        }
+    })
+}
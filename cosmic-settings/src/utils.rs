@@ -4,22 +4,48 @@ use futures::{future::select, StreamExt};
 
 /// Normalize the labeling of displays across settings pages.
 pub fn display_name(name: &str, physical: (u32, u32)) -> String {
-    let inches = ((physical.0.pow(2) + physical.1.pow(2)) as f32).sqrt() * 0.039_370_1;
-    let inches_string = format!("{inches:.1}\"");
-
-    match name {
-        "eDP-1" | "LVDS1" => {
-            fl!("display", "laptop", size = inches_string.as_str())
-        }
-        output => fl!(
+    let inches_string = format!("{:.1}\"", diagonal_inches(physical));
+
+    if is_laptop_display(name) {
+        fl!("display", "laptop", size = inches_string.as_str())
+    } else {
+        fl!(
             "display",
             "external",
             size = inches_string.as_str(),
-            output = output
-        ),
+            output = name
+        )
     }
 }
 
+/// Recommends a display scale percentage, in 25% steps, from the output's physical size in
+/// millimeters and its resolution in pixels.
+///
+/// Returns `None` for outputs which don't report a physical size, such as projectors.
+pub fn recommended_scale(name: &str, physical: (u32, u32), resolution: (u32, u32)) -> Option<u32> {
+    let inches = diagonal_inches(physical);
+
+    if inches < 1.0 {
+        return None;
+    }
+
+    let pixels = ((resolution.0.pow(2) + resolution.1.pow(2)) as f32).sqrt();
+
+    // Laptop displays are viewed from closer than external displays.
+    let reference_dpi = if is_laptop_display(name) { 125.0 } else { 96.0 };
+    let scale = pixels / inches / reference_dpi;
+
+    Some(((scale * 4.0).round() as u32 * 25).clamp(100, 300))
+}
+
+fn diagonal_inches(physical: (u32, u32)) -> f32 {
+    ((physical.0.pow(2) + physical.1.pow(2)) as f32).sqrt() * 0.039_370_1
+}
+
+pub fn is_laptop_display(name: &str) -> bool {
+    matches!(name, "eDP-1" | "LVDS1")
+}
+
 /// Spawn a background tasks and forward its messages
 pub fn forward_event_loop<M: 'static + Send, T: Future<Output = ()> + Send + 'static>(
     sender: tokio::sync::mpsc::Sender<crate::pages::Message>,
@@ -46,3 +72,24 @@ pub fn forward_event_loop<M: 'static + Send, T: Future<Output = ()> + Send + 'st
 
     cancel_tx
 }
+
+#[test]
+fn test_recommended_scale() {
+    // 27" 4K
+    assert_eq!(
+        recommended_scale("DP-1", (597, 336), (3840, 2160)),
+        Some(175)
+    );
+    // 24" 1080p
+    assert_eq!(
+        recommended_scale("DP-1", (531, 299), (1920, 1080)),
+        Some(100)
+    );
+    // 14" 2880x1800 laptop
+    assert_eq!(
+        recommended_scale("eDP-1", (302, 189), (2880, 1800)),
+        Some(200)
+    );
+    // Projector
+    assert_eq!(recommended_scale("HDMI-A-1", (0, 0), (1920, 1080)), None);
+}
@@ -40,6 +40,7 @@ fit-to-screen = Fit to Screen
 open-new-folder = Open new folder
 recent-folders = Recent Folders
 
+never = Never
 x-minutes = { $number } minutes
 x-hours = { $number ->
     [1] 1 hour
@@ -222,30 +223,59 @@ display = Displays
     .desc = Manage displays, graphics switching, and night light
     .arrangement = Display Arrangement
     .arrangement-desc = Drag displays to rearrange them.
+    .brightness = Brightness
     .enable = Enable display
     .external = { $size } { $output } External Display
+    .identify = Identify
     .laptop = { $size } Laptop Display
+    .numbered = { $number }. { $name }
     .options = Display Options
     .refresh-rate = Refresh rate
     .resolution = Resolution
     .scale = Scale
+    .scale-recommended = { $scale }% (Recommended)
+    .scale-custom = Custom
+    .scale-custom-label = Custom scale
+    .scale-custom-invalid = Enter a scale from 50% to 300%, in steps of 5%.
+    .apply = Apply
+    .show-all-modes = Show all modes
+
+adaptive-sync = Adaptive sync
+    .disabled = Off
+    .automatic = Fullscreen applications
+    .always = Always
+
+custom-mode = Custom mode
+    .desc = Use a resolution and refresh rate that the display does not advertise.
+    .invalid = Enter a width from 320 to 16384, a height from 200 to 16384, and a refresh rate from 1 to 1000 Hz.
+    .width = Width
+    .height = Height
+    .refresh-rate = Refresh rate
 
 mirroring = Mirroring
     .id = Mirroring { $id }
     .dont = Don't mirror
     .mirror = Mirror { $display }
-    .project = Project to { $display ->
-        [all] all displays
-        *[other] { $display }
-    }
+    .project = Project to { $display }
+    .project-all = Project to all displays
     .project-count = Projecting to { $count} other { $count ->
         [1] display
         *[other] displays
     }
+    .group = Mirror group
+    .source = Source
+    .targets = Shown on
+    .fit = Mismatched resolutions
+    .fit-scale = Scale to fit
+    .fit-letterbox = Letterbox
 
 night-light = Night Light
     .auto = Automatic (sunset to sunrise)
     .desc = Reduce blue light with warmer colors.
+    .active = Currently active
+    .inactive = Currently inactive
+    .unavailable = Couldn't change the color temperature
+    .location-unavailable = Your location is unavailable, so sunset and sunrise can't be determined.
 
 orientation = Orientation
     .standard = Standard
@@ -255,9 +285,26 @@ orientation = Orientation
 
 scheduling = Scheduling
     .manual = Manual schedule
+    .from = From
+    .to = To
+
+display-profiles = Display Profiles
+    .desc = Save the current arrangement to restore it automatically when the same displays are connected.
+    .name = Profile name
+    .save = Save
+    .apply = Apply
+    .rename = Rename
+    .connected = Matches the connected displays
+
+display-error = Display settings could not be applied
+    .connection = Display settings could not be applied: { $why }
+    .configuration = Display settings could not be applied: { $why }
+    .failed = The display settings were rejected by the compositor.
+    .cancelled = The display settings were changed by another application before they could be applied.
 
 dialog = Dialog
     .title = Keep These Display Settings?
+    .title-settings = Keep These Settings?
     .keep-changes = Keep Changes
     .change-prompt = Settings changes will automatically revert in { $time } seconds.
     .revert-settings = Revert Settings
@@ -271,20 +318,72 @@ sound-output = Output
     .volume = Output volume
     .device = Output device
     .level = Output level
+    .amplification = Allow volume above 100%
     .config = Configuration
     .balance = Balance
+    .balance-left = Left
+    .balance-right = Right
+    .fade = Fade
+    .fade-rear = Rear
+    .fade-front = Front
+    .channels = Channel volumes
+
+sound-speaker-test = Test speakers
+    .desc = Play a test tone on each speaker of { $device }.
+    .start = Test
 
 sound-input = Input
     .volume = Input volume
     .device = Input device
     .level = Input level
+    .noise-suppression = Noise suppression and echo cancellation
+
+sound-bluetooth = Bluetooth audio
+    .mode = Mode
+    .mode-desc = High fidelity playback sounds best, but turns off the microphone of a headset. Headset mode turns the microphone on, at a lower sound quality.
+    .high-fidelity = High fidelity playback (A2DP)
+    .headset = Headset with microphone (HFP)
+    .codec = Codec
+
+sound-devices = Devices
+    .desc = Hidden devices are left out of the device lists. Devices higher in a list are preferred, and become the default when connected.
+    .outputs = Output devices
+    .inputs = Input devices
+    .name = Name
+    .show = Show
+
+sound-policy = Device switching
+    .switch-output = Switch to newly connected output devices
+    .switch-input = Switch to newly connected input devices
+    .return = Return to the previous device when one disconnects
 
 sound-alerts = Alerts
     .volume = Alerts volume
     .sound = Alerts sound
+    .theme = Sound theme
+    .ui-sounds = Sounds for clicks and other interactions
+    .events = Event sounds
+    .event-bell = Alert
+    .event-message = New message
+    .event-warning = Warning
+    .event-complete = Task complete
+    .event-device-added = Device connected
+    .event-device-removed = Device disconnected
+    .event-power-plug = Power connected
+    .event-power-unplug = Power disconnected
+    .event-volume-change = Volume change
+    .event-screen-capture = Screenshot
+    .event-trash-empty = Trash emptied
+    .preview-error = The event sound could not be played: { $why }
 
 sound-applications = Applications
     .desc = Application volumes and settings
+    .unknown = Unknown application
+
+sound-error = Sound settings could not be applied
+    .connection = Could not connect to the sound server: { $why }
+    .failed = The sound server rejected the change: { $why }
+    .not-found = The sound device { $name } is no longer available.
 
 profile = Profile
 
@@ -305,6 +404,44 @@ power-mode = Power Mode
     .performance-desc = Peak performance and power usage.
     .no-backend = Backend not found. Install system76-power or power-profiles-daemon.
 
+power-rules = Automatic Power Mode
+    .desc = Switches the power mode when the power source or battery level changes while Settings is open.
+    .on-battery = When unplugged
+    .low-battery = When the battery is low
+    .threshold = Low battery level
+    .on-ac = When plugged in
+    .unchanged = Don't change
+
+graphics-mode = Graphics
+    .integrated = Integrated graphics
+    .integrated-desc = Use only the integrated GPU for the longest battery life.
+    .hybrid = Hybrid graphics
+    .hybrid-desc = Use the integrated GPU, with the discrete GPU available to applications on request.
+    .nvidia = NVIDIA graphics
+    .nvidia-desc = Use the discrete GPU for everything. Required for some external displays.
+    .compute = Compute graphics
+    .compute-desc = Use the integrated GPU for the display, with the discrete GPU reserved for computation.
+    .discrete-power = Power on the discrete GPU
+    .restart-required = Restart to finish switching graphics modes.
+
+power-idle = Screen and Suspend
+    .screen-off = Turn off the screen
+    .suspend-ac = Automatic suspend when plugged in
+    .suspend-battery = Automatic suspend on battery
+
+power-buttons = Lid and Power Button
+    .lid-battery = When the lid is closed on battery
+    .lid-ac = When the lid is closed and plugged in
+    .lid-docked = When the lid is closed with an external display
+    .power-button = When the power button is pressed
+
+power-action = Action
+    .nothing = Do nothing
+    .lock = Lock the screen
+    .suspend = Suspend
+    .hibernate = Hibernate
+    .power-off = Power off
+
 ## Input
 
 acceleration-desc = Automatically adjusts tracking sensitivity based on speed.